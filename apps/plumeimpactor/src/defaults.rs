@@ -62,3 +62,13 @@ pub fn get_data_path() -> PathBuf {
 
     dir
 }
+
+/// Network settings chosen in the settings screen.
+pub fn get_network_store_path() -> PathBuf {
+    get_data_path().join("network.json")
+}
+
+/// App IDs and certificates created per team, see [`plume_store::QuotaLedger`].
+pub fn get_quota_ledger_path() -> PathBuf {
+    get_data_path().join("quota.json")
+}
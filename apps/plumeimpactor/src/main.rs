@@ -13,6 +13,13 @@ fn main() -> iced::Result {
     env_logger::init();
     let _ = rustls::crypto::ring::default_provider().install_default();
 
+    let network = plume_store::NetworkStore::load_sync(&Some(defaults::get_network_store_path()))
+        .unwrap_or_default();
+    plume_core::set_network_config(network.config().clone().with_env_defaults());
+
+    // Leftovers of earlier runs that crashed or were killed
+    std::thread::spawn(plume_utils::sweep_stale_stages);
+
     #[cfg(target_os = "linux")]
     {
         gtk::init().expect("GTK init failed");
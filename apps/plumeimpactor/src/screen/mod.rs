@@ -359,9 +359,9 @@ impl Impactor {
 
                     if let general::Message::NavigateToInstaller(package) = msg {
                         let options = SignerOptions::default();
-                        self.current_screen = ImpactorScreen::Installer(
-                            package::PackageScreen::new(Some(package), options),
-                        );
+                        let (screen, inspect) = package::PackageScreen::new(Some(package), options);
+                        self.current_screen = ImpactorScreen::Installer(screen);
+                        return Task::batch([task, inspect.map(Message::InstallerScreen)]);
                     } else if let general::Message::NavigateToUtilities = msg {
                         self.current_screen = ImpactorScreen::Utilities(
                             utilties::UtilitiesScreen::new(self.selected_device.clone()),
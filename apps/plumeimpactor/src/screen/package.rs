@@ -1,8 +1,14 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
 use iced::widget::{
     button, checkbox, column, container, pick_list, row, scrollable, text, text_input,
 };
-use iced::{Alignment, Center, Element, Fill, Length, Task};
-use plume_utils::{Package, PlistInfoTrait, SignerInstallMode, SignerMode, SignerOptions, t};
+use iced::{Alignment, Center, Color, Element, Fill, Length, Task};
+use plume_utils::{
+    DebControl, InjectedTweak, Package, PlistInfoTrait, SignerInstallMode, SignerMode,
+    SignerOptions, SignerRuntime, TweakSettings, TweakTarget, missing_dependencies, t, tf,
+};
 
 use crate::appearance;
 
@@ -23,6 +29,15 @@ pub enum Message {
     AddTweak,
     AddBundle,
     RemoveTweak(usize),
+    UpdateTweakTarget(usize, TweakTarget),
+    ToggleTweakRequired(usize, bool),
+    ToggleRemoveInjectedTweak(usize, bool),
+    UpdateRuntime(SignerRuntime),
+    ToggleIgnoreTweakFilters(bool),
+    UpdateTweakSettings(TweakSettings),
+    PickCustomRuntime,
+    PackageInspected(bool, Vec<InjectedTweak>),
+    TweakControlsLoaded(Vec<(PathBuf, DebControl)>),
     Back,
     RequestInstallation,
 }
@@ -31,14 +46,85 @@ pub enum Message {
 pub struct PackageScreen {
     pub selected_package: Option<Package>,
     pub options: SignerOptions,
+    is_encrypted: bool,
+    injected_tweaks: Vec<InjectedTweak>,
+    tweak_controls: HashMap<PathBuf, DebControl>,
+    app_extensions: Vec<String>,
 }
 
 impl PackageScreen {
-    pub fn new(package: Option<Package>, options: SignerOptions) -> Self {
-        Self {
+    pub fn new(package: Option<Package>, options: SignerOptions) -> (Self, Task<Message>) {
+        // Both read the executable out of the archive, which is slow for large apps
+        let inspect = match package.clone() {
+            Some(package) => Task::perform(
+                async move {
+                    std::thread::spawn(move || {
+                        let is_encrypted = package.is_encrypted().unwrap_or(false);
+                        let injected_tweaks = package.injected_tweaks().unwrap_or_default();
+                        (is_encrypted, injected_tweaks)
+                    })
+                    .join()
+                    .unwrap_or_default()
+                },
+                |(is_encrypted, injected_tweaks)| {
+                    Message::PackageInspected(is_encrypted, injected_tweaks)
+                },
+            ),
+            None => Task::none(),
+        };
+
+        let app_extensions = package
+            .as_ref()
+            .map(|p| p.app_extension_names())
+            .unwrap_or_default();
+
+        let mut screen = Self {
             selected_package: package,
             options,
+            is_encrypted: false,
+            injected_tweaks: Vec::new(),
+            tweak_controls: HashMap::new(),
+            app_extensions,
+        };
+        let load_controls = screen.refresh_tweak_controls();
+        (screen, Task::batch([inspect, load_controls]))
+    }
+
+    /// Drops controls of removed tweaks and reads the ones of new `.deb`s in the background.
+    fn refresh_tweak_controls(&mut self) -> Task<Message> {
+        let tweaks = self.options.tweaks.as_deref().unwrap_or_default();
+
+        self.tweak_controls.retain(|path, _| tweaks.contains(path));
+        let pending = tweaks
+            .iter()
+            .filter(|tweak| {
+                !self.tweak_controls.contains_key(*tweak)
+                    && tweak.extension().and_then(|e| e.to_str()) == Some("deb")
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if pending.is_empty() {
+            return Task::none();
         }
+
+        let limits = self.options.extract_limits;
+        Task::perform(
+            async move {
+                std::thread::spawn(move || {
+                    pending
+                        .into_iter()
+                        .filter_map(|deb| {
+                            let control = DebControl::from_deb(&deb, &limits).ok()?;
+                            Some((deb, control))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .join()
+                .unwrap_or_default()
+            },
+            Message::TweakControlsLoaded,
+        )
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
@@ -125,7 +211,7 @@ impl PackageScreen {
                 let filter_name = t("tweak_files");
                 let title = t("select_tweak_file");
                 let path = rfd::FileDialog::new()
-                    .add_filter(&filter_name, &["deb", "dylib"])
+                    .add_filter(&filter_name, &["deb", "dylib", "zip"])
                     .set_title(&title)
                     .pick_file();
 
@@ -134,6 +220,7 @@ impl PackageScreen {
                         Some(vec) => vec.push(path),
                         None => self.options.tweaks = Some(vec![path]),
                     }
+                    return self.refresh_tweak_controls();
                 }
 
                 Task::none()
@@ -142,15 +229,13 @@ impl PackageScreen {
                 let title = t("select_bundle_folder");
                 let path = rfd::FileDialog::new().set_title(&title).pick_folder();
 
+                // .framework/.bundle/.appex, or an unpacked rootfs-style tweak folder
                 if let Some(path) = path {
-                    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                        if ["framework", "bundle", "appex"].contains(&ext) {
-                            match &mut self.options.tweaks {
-                                Some(vec) => vec.push(path),
-                                None => self.options.tweaks = Some(vec![path]),
-                            }
-                        }
+                    match &mut self.options.tweaks {
+                        Some(vec) => vec.push(path),
+                        None => self.options.tweaks = Some(vec![path]),
                     }
+                    return self.refresh_tweak_controls();
                 }
 
                 Task::none()
@@ -158,9 +243,85 @@ impl PackageScreen {
             Message::RemoveTweak(index) => {
                 if let Some(tweaks) = &mut self.options.tweaks {
                     if index < tweaks.len() {
-                        tweaks.remove(index);
+                        let removed = tweaks.remove(index);
+                        if !tweaks.contains(&removed) {
+                            self.options.tweak_injection.remove(&removed);
+                        }
                     }
                 }
+                self.refresh_tweak_controls()
+            }
+            Message::UpdateTweakTarget(index, target) => {
+                if let Some(tweak) = self.options.tweaks.as_ref().and_then(|t| t.get(index)) {
+                    let injection = self
+                        .options
+                        .tweak_injection
+                        .entry(tweak.clone())
+                        .or_default();
+                    injection.target = target;
+                }
+                Task::none()
+            }
+            Message::ToggleTweakRequired(index, required) => {
+                if let Some(tweak) = self.options.tweaks.as_ref().and_then(|t| t.get(index)) {
+                    let injection = self
+                        .options
+                        .tweak_injection
+                        .entry(tweak.clone())
+                        .or_default();
+                    injection.weak = !required;
+                }
+                Task::none()
+            }
+            Message::PackageInspected(is_encrypted, injected_tweaks) => {
+                self.is_encrypted = is_encrypted;
+                self.injected_tweaks = injected_tweaks;
+                Task::none()
+            }
+            Message::TweakControlsLoaded(controls) => {
+                let tweaks = self.options.tweaks.as_deref().unwrap_or_default();
+                for (deb, control) in controls {
+                    // The tweak may have been removed while its control was read
+                    if tweaks.contains(&deb) {
+                        self.tweak_controls.insert(deb, control);
+                    }
+                }
+                Task::none()
+            }
+            Message::ToggleRemoveInjectedTweak(index, remove) => {
+                if let Some(injected) = self.injected_tweaks.get(index) {
+                    let remove_tweaks = self.options.remove_tweaks.get_or_insert_with(Vec::new);
+                    remove_tweaks.retain(|name| name != &injected.name);
+                    if remove {
+                        remove_tweaks.push(injected.name.clone());
+                    }
+                }
+                Task::none()
+            }
+            Message::UpdateRuntime(runtime) => {
+                self.options.runtime = runtime;
+                Task::none()
+            }
+            Message::ToggleIgnoreTweakFilters(value) => {
+                self.options.ignore_tweak_filters = value;
+                Task::none()
+            }
+            Message::UpdateTweakSettings(settings) => {
+                self.options.tweak_settings = settings;
+                Task::none()
+            }
+            Message::PickCustomRuntime => {
+                let filter_name = t("tweak_files");
+                let title = t("select_runtime_file");
+                let path = rfd::FileDialog::new()
+                    .add_filter(&filter_name, &["deb", "dylib", "zip"])
+                    .set_title(&title)
+                    .pick_file();
+
+                if let Some(path) = path {
+                    self.options.runtime = SignerRuntime::Custom(path);
+                }
+
                 Task::none()
             }
             _ => Task::none(),
@@ -180,12 +341,20 @@ impl PackageScreen {
             .spacing(appearance::THEME_PADDING),
         );
 
-        column![
-            container(content).width(Fill).height(Fill),
-            self.view_buttons(has_device)
-        ]
-        .spacing(appearance::THEME_PADDING)
-        .into()
+        let mut screen = column![].spacing(appearance::THEME_PADDING);
+
+        if self.is_encrypted {
+            screen = screen.push(
+                text(t("package_encrypted"))
+                    .size(12)
+                    .color(Color::from_rgb(0.9, 0.2, 0.2)),
+            );
+        }
+
+        screen
+            .push(container(content).width(Fill).height(Fill))
+            .push(self.view_buttons(has_device))
+            .into()
     }
 
     fn view_no_package(&self) -> Element<'_, Message> {
@@ -203,7 +372,7 @@ impl PackageScreen {
         let pkg_id = pkg.get_bundle_identifier().unwrap_or_default();
         let pkg_ver = pkg.get_version().unwrap_or_default();
 
-        column![
+        let mut info_column = column![
             text(t("name")).size(12),
             text_input(
                 &t("app_name_placeholder"),
@@ -236,10 +405,34 @@ impl PackageScreen {
                     .style(appearance::p_button),
             ]
             .spacing(8),
+            text(t("tweak_runtime")).size(12),
+            self.view_runtime_picker(),
+            checkbox(self.options.ignore_tweak_filters)
+                .label(t("ignore_tweak_filters"))
+                .on_toggle(Message::ToggleIgnoreTweakFilters),
+            text(t("tweak_settings")).size(12),
+            pick_list(
+                &[
+                    TweakSettings::InApp,
+                    TweakSettings::SettingsBundle,
+                    TweakSettings::None,
+                ][..],
+                Some(self.options.tweak_settings.clone()),
+                Message::UpdateTweakSettings
+            )
+            .style(appearance::s_pick_list)
+            .width(Fill),
         ]
         .spacing(8)
-        .width(Fill)
-        .into()
+        .width(Fill);
+
+        if !self.injected_tweaks.is_empty() {
+            info_column = info_column
+                .push(text(t("injected_tweaks")).size(12))
+                .push(self.view_injected_tweaks());
+        }
+
+        info_column.into()
     }
 
     fn view_options_column(&self) -> Element<'_, Message> {
@@ -323,10 +516,25 @@ impl PackageScreen {
             let mut tweak_list = column![].spacing(4);
 
             for (i, tweak) in tweaks.iter().enumerate() {
+                let injection = self
+                    .options
+                    .tweak_injection
+                    .get(tweak)
+                    .cloned()
+                    .unwrap_or_default();
+
                 let tweak_row = row![
-                    text(tweak.file_name().and_then(|n| n.to_str()).unwrap_or("???"))
-                        .size(12)
-                        .width(Fill),
+                    self.view_tweak_label(tweak),
+                    pick_list(
+                        self.tweak_targets(),
+                        Some(injection.target),
+                        move |target| { Message::UpdateTweakTarget(i, target) }
+                    )
+                    .text_size(12),
+                    checkbox(!injection.weak)
+                        .label(t("tweak_required"))
+                        .on_toggle(move |required| Message::ToggleTweakRequired(i, required))
+                        .size(14),
                     button(text(t("remove")).align_x(Center))
                         .on_press(Message::RemoveTweak(i))
                         .style(appearance::p_button)
@@ -338,9 +546,124 @@ impl PackageScreen {
                 tweak_list = tweak_list.push(tweak_row);
             }
 
+            let controls = self.tweak_controls.values().collect::<Vec<_>>();
+            for (package, dependency) in missing_dependencies(&controls) {
+                tweak_list = tweak_list.push(
+                    text(tf(
+                        "missing_dependency",
+                        &[package.as_str(), dependency.as_str()],
+                    ))
+                    .size(12)
+                    .color(Color::from_rgb(0.9, 0.6, 0.1)),
+                );
+            }
+
             scrollable(tweak_list).height(Length::Fixed(100.0)).into()
         } else {
             text(t("no_tweaks_added")).size(12).into()
         }
     }
+
+    fn tweak_targets(&self) -> Vec<TweakTarget> {
+        let mut targets = vec![TweakTarget::MainApp];
+        if !self.app_extensions.is_empty() {
+            targets.push(TweakTarget::AllExtensions);
+        }
+        targets.extend(
+            self.app_extensions
+                .iter()
+                .map(|name| TweakTarget::Bundles(vec![name.clone()])),
+        );
+        targets
+    }
+
+    fn view_tweak_label(&self, tweak: &Path) -> Element<'_, Message> {
+        let file_name = tweak.file_name().and_then(|n| n.to_str()).unwrap_or("???");
+
+        let Some(control) = self.tweak_controls.get(tweak) else {
+            return text(file_name.to_string()).size(12).width(Fill).into();
+        };
+
+        let mut title = control.display_name().to_string();
+        if let Some(version) = &control.version {
+            title.push_str(&format!(" ({version})"));
+        }
+
+        let mut details = vec![control.package.clone()];
+        if let Some(architecture) = &control.architecture {
+            let kind = if control.is_rootless() {
+                t("rootless")
+            } else {
+                t("rootful")
+            };
+            details.push(format!("{architecture}, {kind}"));
+        }
+        if !control.depends.is_empty() {
+            details.push(control.depends.join(", "));
+        }
+
+        let mut label = column![
+            text(title).size(12),
+            text(details.join(" · "))
+                .size(10)
+                .color(Color::from_rgb(0.6, 0.6, 0.6)),
+        ]
+        .spacing(2)
+        .width(Fill);
+
+        if let Some(description) = control
+            .description
+            .as_deref()
+            .and_then(|d| d.lines().next())
+        {
+            label = label.push(
+                text(description.to_string())
+                    .size(10)
+                    .color(Color::from_rgb(0.6, 0.6, 0.6)),
+            );
+        }
+
+        label.into()
+    }
+
+    fn view_injected_tweaks(&self) -> Element<'_, Message> {
+        let remove_tweaks = self.options.remove_tweaks.as_deref().unwrap_or_default();
+        let mut injected_list = column![].spacing(4);
+
+        for (i, injected) in self.injected_tweaks.iter().enumerate() {
+            injected_list = injected_list.push(
+                checkbox(remove_tweaks.contains(&injected.name))
+                    .label(injected.name.clone())
+                    .on_toggle(move |remove| Message::ToggleRemoveInjectedTweak(i, remove))
+                    .size(14),
+            );
+        }
+
+        scrollable(injected_list)
+            .height(Length::Fixed(100.0))
+            .into()
+    }
+
+    fn view_runtime_picker(&self) -> Element<'_, Message> {
+        let mut runtimes = vec![SignerRuntime::ElleKit, SignerRuntime::None];
+        if let SignerRuntime::Custom(_) = &self.options.runtime {
+            runtimes.push(self.options.runtime.clone());
+        }
+
+        row![
+            pick_list(
+                runtimes,
+                Some(self.options.runtime.clone()),
+                Message::UpdateRuntime
+            )
+            .style(appearance::s_pick_list)
+            .width(Fill),
+            button(text(t("choose_runtime")).align_x(Center))
+                .on_press(Message::PickCustomRuntime)
+                .style(appearance::p_button),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
 }
@@ -1,10 +1,18 @@
-use iced::widget::{button, column, container, pick_list, row, text};
-use iced::{Alignment, Center, Element, Fill, Length, Task};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use iced::widget::{
+    button, checkbox, column, container, pick_list, row, scrollable, text, text_input,
+};
+use iced::{Alignment, Center, Color, Element, Fill, Length, Task};
 use iced_aw::SelectionList;
-use plume_store::AccountStore;
-use plume_utils::{Language, t};
+use plume_core::NetworkConfig;
+use plume_store::{AccountStore, GsaAccount, NetworkStore};
+use plume_utils::{Language, t, tf};
 
-use crate::appearance;
+use crate::subscriptions::{self, AppIdRow};
+use crate::{appearance, defaults};
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -13,12 +21,101 @@ pub enum Message {
     RemoveAccount(usize),
     ExportP12,
     LanguageChanged(Language),
+    ShowNetwork,
+    HideNetwork,
+    ProxyChanged(String),
+    ProxyUsernameChanged(String),
+    ProxyPasswordChanged(String),
+    AddCertificate,
+    RemoveCertificate(usize),
+    ConnectTimeoutChanged(String),
+    RequestTimeoutChanged(String),
+    ToggleAcceptInvalidCerts(bool),
+    SaveNetwork,
+    ShowAppIds,
+    HideAppIds,
+    AppIdsLoaded(Result<(String, Vec<AppIdRow>), String>),
+    ToggleAppId(String, bool),
+    DeleteAppIds,
+    CancelDeleteAppIds,
+    ConfirmDeleteAppIds,
+    AppIdsDeleted(Result<(), String>),
 }
 
 #[derive(Debug)]
 pub struct SettingsScreen {
     pub account_store: Option<AccountStore>,
     selected_language: Language,
+    /// Network settings being edited, shown instead of the accounts.
+    network: Option<NetworkForm>,
+    /// The selected account's App IDs, shown instead of the accounts.
+    app_ids: Option<AppIdsPanel>,
+}
+
+#[derive(Debug, Default)]
+struct AppIdsPanel {
+    team_id: Option<String>,
+    app_ids: Vec<AppIdRow>,
+    /// Portal IDs of the App IDs ticked for deletion.
+    selected: HashSet<String>,
+    loading: bool,
+    confirming: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug)]
+struct NetworkForm {
+    store: NetworkStore,
+    proxy: String,
+    proxy_username: String,
+    proxy_password: String,
+    ca_certificates: Vec<PathBuf>,
+    connect_timeout: String,
+    request_timeout: String,
+    accept_invalid_certs: bool,
+    error: Option<String>,
+}
+
+impl NetworkForm {
+    fn load() -> Self {
+        let store =
+            NetworkStore::load_sync(&Some(defaults::get_network_store_path())).unwrap_or_default();
+        let config = store.config().clone();
+        let seconds = |s: Option<u64>| s.map(|s| s.to_string()).unwrap_or_default();
+
+        Self {
+            proxy: config.proxy.unwrap_or_default(),
+            proxy_username: config.proxy_username.unwrap_or_default(),
+            // Not on disk, the one in use is the only copy
+            proxy_password: plume_core::network_config()
+                .proxy_password
+                .unwrap_or_default(),
+            ca_certificates: config.ca_certificates,
+            connect_timeout: seconds(config.connect_timeout),
+            request_timeout: seconds(config.timeout),
+            accept_invalid_certs: config.accept_invalid_certs,
+            store,
+            error: None,
+        }
+    }
+
+    fn to_config(&self) -> Result<NetworkConfig, String> {
+        let text = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        let seconds = |s: &str| match text(s) {
+            Some(s) => s.parse().map(Some).map_err(|_| t("error_invalid_timeout")),
+            None => Ok(None),
+        };
+
+        Ok(NetworkConfig {
+            proxy: text(&self.proxy),
+            proxy_username: text(&self.proxy_username),
+            proxy_password: Some(self.proxy_password.clone()).filter(|s| !s.is_empty()),
+            ca_certificates: self.ca_certificates.clone(),
+            connect_timeout: seconds(&self.connect_timeout)?,
+            timeout: seconds(&self.request_timeout)?,
+            accept_invalid_certs: self.accept_invalid_certs,
+        })
+    }
 }
 
 impl SettingsScreen {
@@ -27,6 +124,8 @@ impl SettingsScreen {
         Self {
             account_store,
             selected_language: current_language,
+            network: None,
+            app_ids: None,
         }
     }
 
@@ -78,9 +177,115 @@ impl SettingsScreen {
                 Task::none()
             }
             Message::ShowLogin => Task::none(),
+            Message::ShowNetwork => {
+                self.network = Some(NetworkForm::load());
+                Task::none()
+            }
+            Message::HideNetwork => {
+                self.network = None;
+                Task::none()
+            }
+            Message::SaveNetwork => {
+                let Some(form) = &mut self.network else {
+                    return Task::none();
+                };
+
+                let saved = form.to_config().and_then(|config| {
+                    form.store
+                        .set_config_sync(config.clone())
+                        .map(|_| config)
+                        .map_err(|e| tf("error_saving_network", &[&e.to_string()]))
+                });
+                match saved {
+                    Ok(config) => {
+                        plume_core::set_network_config(config.with_env_defaults());
+                        self.network = None;
+                    }
+                    Err(e) => form.error = Some(e),
+                }
+                Task::none()
+            }
+            Message::ShowAppIds => {
+                let Some(account) = self.selected_account() else {
+                    self.app_ids = Some(AppIdsPanel {
+                        error: Some(t("error_no_account_selected")),
+                        ..Default::default()
+                    });
+                    return Task::none();
+                };
+
+                self.app_ids = Some(AppIdsPanel {
+                    loading: true,
+                    ..Default::default()
+                });
+                spawn_task(subscriptions::list_app_ids(account), Message::AppIdsLoaded)
+            }
+            Message::HideAppIds => {
+                self.app_ids = None;
+                Task::none()
+            }
+            Message::ConfirmDeleteAppIds => {
+                let account = self.selected_account();
+                let Some(panel) = &mut self.app_ids else {
+                    return Task::none();
+                };
+                let (Some(account), Some(team_id)) = (account, panel.team_id.clone()) else {
+                    return Task::none();
+                };
+
+                panel.confirming = false;
+                panel.loading = true;
+                panel.error = None;
+                let app_id_ids = panel.selected.drain().collect();
+                spawn_task(
+                    subscriptions::delete_app_ids(account, team_id, app_id_ids),
+                    Message::AppIdsDeleted,
+                )
+            }
+            Message::AppIdsDeleted(result) => {
+                let account = self.selected_account();
+                let Some(panel) = &mut self.app_ids else {
+                    return Task::none();
+                };
+
+                if let Err(e) = result {
+                    panel.error = Some(tf("error_deleting_app_ids", &[&e]));
+                }
+                // Reload so the list shows what the portal has left
+                match account {
+                    Some(account) => {
+                        spawn_task(subscriptions::list_app_ids(account), Message::AppIdsLoaded)
+                    }
+                    None => {
+                        panel.loading = false;
+                        Task::none()
+                    }
+                }
+            }
+            message @ (Message::AppIdsLoaded(_)
+            | Message::ToggleAppId(..)
+            | Message::DeleteAppIds
+            | Message::CancelDeleteAppIds) => {
+                if let Some(panel) = &mut self.app_ids {
+                    panel.update(message);
+                }
+                Task::none()
+            }
+            message => {
+                if let Some(form) = &mut self.network {
+                    form.update(message);
+                }
+                Task::none()
+            }
         }
     }
 
+    fn selected_account(&self) -> Option<GsaAccount> {
+        self.account_store
+            .as_ref()
+            .and_then(|s| s.selected_account().cloned())
+    }
+
     pub fn view(&self) -> Element<'_, Message> {
         let Some(store) = &self.account_store else {
             return column![text(t("loading_accounts"))]
@@ -96,10 +301,20 @@ impl SettingsScreen {
             .selected_account()
             .and_then(|acc| accounts.iter().position(|(e, _)| *e == acc.email()));
 
+        if let Some(form) = &self.network {
+            return form.view();
+        }
+
+        if let Some(panel) = &self.app_ids {
+            return panel.view();
+        }
+
         let mut content = column![].spacing(appearance::THEME_PADDING);
 
         // Language selector
         content = content.push(self.view_language_selector());
+        content = content.push(self.view_network_row());
+        content = content.push(self.view_app_ids_row());
 
         // Accounts section
         content = content.push(text(t("accounts")));
@@ -133,6 +348,34 @@ impl SettingsScreen {
         .into()
     }
 
+    fn view_network_row(&self) -> Element<'_, Message> {
+        row![
+            text(t("network")).width(Fill),
+            button(text(t("configure")).align_x(Center))
+                .on_press(Message::ShowNetwork)
+                .style(appearance::s_button)
+                .width(Length::Fixed(200.0))
+        ]
+        .spacing(appearance::THEME_PADDING)
+        .align_y(Alignment::Center)
+        .padding([0.0, appearance::THEME_PADDING])
+        .into()
+    }
+
+    fn view_app_ids_row(&self) -> Element<'_, Message> {
+        row![
+            text(t("app_ids")).width(Fill),
+            button(text(t("manage")).align_x(Center))
+                .on_press(Message::ShowAppIds)
+                .style(appearance::s_button)
+                .width(Length::Fixed(200.0))
+        ]
+        .spacing(appearance::THEME_PADDING)
+        .align_y(Alignment::Center)
+        .padding([0.0, appearance::THEME_PADDING])
+        .into()
+    }
+
     fn view_account_list(
         &self,
         accounts: &[(&String, &plume_store::GsaAccount)],
@@ -207,3 +450,268 @@ impl SettingsScreen {
         buttons.align_y(Alignment::Center).into()
     }
 }
+
+impl NetworkForm {
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::ProxyChanged(value) => self.proxy = value,
+            Message::ProxyUsernameChanged(value) => self.proxy_username = value,
+            Message::ProxyPasswordChanged(value) => self.proxy_password = value,
+            Message::ConnectTimeoutChanged(value) => self.connect_timeout = value,
+            Message::RequestTimeoutChanged(value) => self.request_timeout = value,
+            Message::ToggleAcceptInvalidCerts(value) => self.accept_invalid_certs = value,
+            Message::AddCertificate => {
+                let filter_name = t("certificate_files");
+                let title = t("add_certificate");
+                let path = rfd::FileDialog::new()
+                    .add_filter(&filter_name, &["pem", "crt", "cer", "der"])
+                    .set_title(&title)
+                    .pick_file();
+
+                if let Some(path) = path {
+                    self.ca_certificates.push(path);
+                }
+            }
+            Message::RemoveCertificate(index) => {
+                if index < self.ca_certificates.len() {
+                    self.ca_certificates.remove(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let mut certificates = column![text(t("ca_certificates")).size(12)].spacing(4);
+        for (index, path) in self.ca_certificates.iter().enumerate() {
+            certificates = certificates.push(
+                row![
+                    text(path.display().to_string()).width(Fill),
+                    button(text(t("remove")).align_x(Center))
+                        .on_press(Message::RemoveCertificate(index))
+                        .style(appearance::s_button)
+                ]
+                .spacing(appearance::THEME_PADDING)
+                .align_y(Alignment::Center),
+            );
+        }
+        certificates = certificates.push(
+            button(text(t("add_certificate")).align_x(Center))
+                .on_press(Message::AddCertificate)
+                .style(appearance::s_button),
+        );
+
+        let mut content = column![
+            labeled_input("proxy", &self.proxy, Message::ProxyChanged, false),
+            row![
+                labeled_input(
+                    "proxy_username",
+                    &self.proxy_username,
+                    Message::ProxyUsernameChanged,
+                    false
+                ),
+                labeled_input(
+                    "proxy_password",
+                    &self.proxy_password,
+                    Message::ProxyPasswordChanged,
+                    true
+                ),
+            ]
+            .spacing(appearance::THEME_PADDING),
+            text(t("proxy_password_not_saved")).size(12),
+            row![
+                labeled_input(
+                    "connect_timeout",
+                    &self.connect_timeout,
+                    Message::ConnectTimeoutChanged,
+                    false
+                ),
+                labeled_input(
+                    "request_timeout",
+                    &self.request_timeout,
+                    Message::RequestTimeoutChanged,
+                    false
+                ),
+            ]
+            .spacing(appearance::THEME_PADDING),
+            certificates,
+            checkbox(self.accept_invalid_certs)
+                .label(t("accept_invalid_certs"))
+                .on_toggle(Message::ToggleAcceptInvalidCerts),
+        ]
+        .spacing(appearance::THEME_PADDING);
+
+        if let Some(error) = &self.error {
+            content = content.push(text(error.clone()).color(Color::from_rgb(0.9, 0.2, 0.2)));
+        }
+
+        column![
+            scrollable(content).height(Fill),
+            row![
+                button(text(t("back")).align_x(Center))
+                    .on_press(Message::HideNetwork)
+                    .style(appearance::s_button),
+                button(text(t("save")).align_x(Center))
+                    .on_press(Message::SaveNetwork)
+                    .style(appearance::s_button),
+            ]
+            .spacing(appearance::THEME_PADDING),
+        ]
+        .spacing(appearance::THEME_PADDING)
+        .into()
+    }
+}
+
+impl AppIdsPanel {
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::AppIdsLoaded(result) => {
+                self.loading = false;
+                match result {
+                    Ok((team_id, app_ids)) => {
+                        self.team_id = Some(team_id);
+                        self.app_ids = app_ids;
+                    }
+                    Err(e) => {
+                        self.error = Some(tf("error_loading_app_ids", &[&e]));
+                        self.app_ids.clear();
+                    }
+                }
+            }
+            Message::ToggleAppId(app_id_id, checked) => {
+                if checked {
+                    self.selected.insert(app_id_id);
+                } else {
+                    self.selected.remove(&app_id_id);
+                }
+            }
+            Message::DeleteAppIds => self.confirming = !self.selected.is_empty(),
+            Message::CancelDeleteAppIds => self.confirming = false,
+            _ => {}
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let mut list = column![].spacing(4);
+
+        if self.loading {
+            list = list.push(text(t("loading_app_ids")));
+        } else if self.app_ids.is_empty() && self.error.is_none() {
+            list = list.push(text(t("no_app_ids")));
+        }
+
+        for app_id in &self.app_ids {
+            let mut details = vec![app_id.identifier.clone()];
+            if !app_id.app_groups.is_empty() {
+                details.push(tf("app_groups_list", &[&app_id.app_groups.join(", ")]));
+            }
+            if let Some(created_at) = app_id.created_at {
+                details.push(tf(
+                    "created_days_ago",
+                    &[&days_since(created_at).to_string()],
+                ));
+            }
+
+            let app_id_id = app_id.app_id_id.clone();
+            list = list.push(
+                checkbox(self.selected.contains(&app_id.app_id_id))
+                    .label(format!("{} ({})", app_id.name, details.join(", ")))
+                    .on_toggle(move |checked| Message::ToggleAppId(app_id_id.clone(), checked)),
+            );
+        }
+
+        let mut content = column![scrollable(list).height(Fill)].spacing(appearance::THEME_PADDING);
+
+        if let Some(error) = &self.error {
+            content = content.push(text(error.clone()).color(Color::from_rgb(0.9, 0.2, 0.2)));
+        }
+
+        let buttons = if self.confirming {
+            row![
+                text(tf(
+                    "confirm_delete_app_ids",
+                    &[&self.selected.len().to_string()]
+                ))
+                .width(Fill),
+                button(text(t("cancel")).align_x(Center))
+                    .on_press(Message::CancelDeleteAppIds)
+                    .style(appearance::s_button),
+                button(text(t("delete")).align_x(Center))
+                    .on_press(Message::ConfirmDeleteAppIds)
+                    .style(appearance::s_button),
+            ]
+        } else {
+            row![
+                button(text(t("back")).align_x(Center))
+                    .on_press(Message::HideAppIds)
+                    .style(appearance::s_button),
+                button(text(t("delete_selected")).align_x(Center))
+                    .on_press_maybe(
+                        (!self.loading && !self.selected.is_empty())
+                            .then_some(Message::DeleteAppIds)
+                    )
+                    .style(appearance::s_button),
+            ]
+        };
+
+        content
+            .push(
+                buttons
+                    .spacing(appearance::THEME_PADDING)
+                    .align_y(Alignment::Center),
+            )
+            .into()
+    }
+}
+
+fn days_since(unix_seconds: u64) -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    now.saturating_sub(unix_seconds) / (24 * 60 * 60)
+}
+
+/// Runs `future` on a runtime of its own thread, like the utilities screen does for device calls.
+fn spawn_task<T, F>(future: F, on_done: fn(Result<T, String>) -> Message) -> Task<Message>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T, String>> + Send + 'static,
+{
+    let (tx, rx) = std::sync::mpsc::sync_channel(1);
+
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let _ = tx.send(rt.block_on(future));
+    });
+
+    Task::perform(
+        async move {
+            std::thread::spawn(move || {
+                rx.recv()
+                    .unwrap_or_else(|_| Err("Failed to receive result".to_string()))
+            })
+            .join()
+            .unwrap()
+        },
+        on_done,
+    )
+}
+
+fn labeled_input<'a>(
+    label: &str,
+    value: &str,
+    on_input: fn(String) -> Message,
+    secure: bool,
+) -> Element<'a, Message> {
+    column![
+        text(t(label)).size(12),
+        text_input(&t(label), value)
+            .on_input(on_input)
+            .secure(secure)
+            .padding(8)
+            .width(Fill),
+    ]
+    .spacing(4)
+    .into()
+}
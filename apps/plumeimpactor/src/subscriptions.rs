@@ -240,6 +240,7 @@ pub(crate) async fn run_installation(
     team_selection_rx: Option<std::sync::mpsc::Receiver<Result<usize, String>>>,
 ) -> Result<(), String> {
     use plume_core::{AnisetteConfiguration, CertificateIdentity, developer::DeveloperSession};
+    use plume_store::{QuotaKind, QuotaLedger};
     use plume_utils::{Signer, SignerInstallMode, SignerMode};
 
     let package_file: std::path::PathBuf;
@@ -273,8 +274,8 @@ pub(crate) async fn run_installation(
                 return Err("No teams available for this account".to_string());
             }
 
-            let team_id = if teams_response.teams.len() == 1 {
-                &teams_response.teams[0].team_id
+            let team = if teams_response.teams.len() == 1 {
+                &teams_response.teams[0]
             } else {
                 let team_names: Vec<String> = teams_response
                     .teams
@@ -291,11 +292,12 @@ pub(crate) async fn run_installation(
                         .map_err(|_| "Team selection channel closed".to_string())?
                         .map_err(|e| format!("Team selection error: {}", e))?;
 
-                    &teams_response.teams[selected_index].team_id
+                    &teams_response.teams[selected_index]
                 } else {
-                    &teams_response.teams[0].team_id
+                    &teams_response.teams[0]
                 }
             };
+            let team_id = &team.team_id;
 
             let identity = CertificateIdentity::new_with_session(
                 &session,
@@ -306,6 +308,17 @@ pub(crate) async fn run_installation(
             .await
             .map_err(|e| e.to_string())?;
 
+            let mut ledger = QuotaLedger::load(&Some(crate::defaults::get_quota_ledger_path()))
+                .await
+                .map_err(|e| e.to_string())?;
+            if let (true, Some(serial_number)) = (identity.created, &identity.serial_number) {
+                ledger
+                    .record(team_id, QuotaKind::Certificate, serial_number)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            options.app_id_budget = Some(ledger.status(team_id, QuotaKind::AppId).remaining());
+
             send("Ensuring device is registered...".to_string(), 30);
 
             if let Some(dev) = &device {
@@ -327,10 +340,26 @@ pub(crate) async fn run_installation(
                 .modify_bundle(&bundle, &Some(team_id.clone()))
                 .await
                 .map_err(|e| e.to_string())?;
-            signer
-                .register_bundle(&bundle, &session, team_id)
-                .await
-                .map_err(|e| e.to_string())?;
+            notify_skipped_tweaks(&signer.skipped_tweaks);
+            let registered = signer
+                .register_bundle(&bundle, &session, team_id, team.team_type())
+                .await;
+            if let Some(report) = &signer.registration_report {
+                ledger
+                    .record_registration(team_id, report.created_app_ids())
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            // The report says which bundles went through and what was rolled back
+            if let Err(e) = registered {
+                return Err(match &signer.registration_report {
+                    Some(report) => format!("{e}\n\n{report}"),
+                    None => e.to_string(),
+                });
+            }
+            if let Some(report) = &signer.registration_report {
+                notify_skipped_capabilities(&report.skipped_capabilities());
+            }
             signer
                 .sign_bundle(&bundle)
                 .await
@@ -352,6 +381,7 @@ pub(crate) async fn run_installation(
                 .modify_bundle(&bundle, &None)
                 .await
                 .map_err(|e| e.to_string())?;
+            notify_skipped_tweaks(&signer.skipped_tweaks);
             signer
                 .sign_bundle(&bundle)
                 .await
@@ -440,6 +470,51 @@ pub(crate) async fn run_installation(
     Ok(())
 }
 
+/// Tells the user which tweak dylibs were left out because their filters target other apps.
+fn notify_skipped_tweaks(skipped_tweaks: &[plume_utils::SkippedTweak]) {
+    if skipped_tweaks.is_empty() {
+        return;
+    }
+
+    let description = skipped_tweaks
+        .iter()
+        .map(|skipped| skipped.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    std::thread::spawn(move || {
+        rfd::MessageDialog::new()
+            .set_title("Skipped Tweaks")
+            .set_description(format!(
+                "These dylibs target other apps and were not injected:\n\n{description}"
+            ))
+            .set_level(rfd::MessageLevel::Warning)
+            .set_buttons(rfd::MessageButtons::Ok)
+            .show();
+    });
+}
+
+/// Tells the user which capabilities the team couldn't enable, the features behind them won't work.
+fn notify_skipped_capabilities(skipped_capabilities: &[String]) {
+    if skipped_capabilities.is_empty() {
+        return;
+    }
+
+    let description = skipped_capabilities.join("\n");
+
+    std::thread::spawn(move || {
+        rfd::MessageDialog::new()
+            .set_title("Skipped Capabilities")
+            .set_description(format!(
+                "Your team can't enable these capabilities, features relying on them \
+                 won't work in the installed app:\n\n{description}"
+            ))
+            .set_level(rfd::MessageLevel::Warning)
+            .set_buttons(rfd::MessageButtons::Ok)
+            .show();
+    });
+}
+
 #[allow(dead_code)]
 pub(crate) async fn export_certificate(account: plume_store::GsaAccount) -> Result<(), String> {
     use plume_core::{AnisetteConfiguration, CertificateIdentity, developer::DeveloperSession};
@@ -504,3 +579,121 @@ pub(crate) async fn export_certificate(account: plume_store::GsaAccount) -> Resu
 
     Ok(())
 }
+
+/// An App ID as the settings screen lists it.
+#[derive(Debug, Clone)]
+pub(crate) struct AppIdRow {
+    pub app_id_id: String,
+    pub identifier: String,
+    pub name: String,
+    /// Unix seconds, only known for App IDs this machine created within the quota window.
+    pub created_at: Option<u64>,
+    /// Identifiers (`group.…`) of the app groups assigned to it.
+    pub app_groups: Vec<String>,
+}
+
+async fn developer_session(
+    account: &plume_store::GsaAccount,
+) -> Result<(plume_core::developer::DeveloperSession, String), String> {
+    use plume_core::{AnisetteConfiguration, developer::DeveloperSession};
+
+    let session = DeveloperSession::new(
+        account.adsid().clone(),
+        account.xcode_gs_token().clone(),
+        AnisetteConfiguration::default().set_configuration_path(crate::defaults::get_data_path()),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // TODO: Add team selection support, like export_certificate this uses the first team
+    let team_id = session
+        .qh_list_teams()
+        .await
+        .map_err(|e| e.to_string())?
+        .teams
+        .first()
+        .map(|team| team.team_id.clone())
+        .ok_or_else(|| "No teams available for this account".to_string())?;
+
+    Ok((session, team_id))
+}
+
+/// The team's App IDs, with creation dates from the quota ledger.
+pub(crate) async fn list_app_ids(
+    account: plume_store::GsaAccount,
+) -> Result<(String, Vec<AppIdRow>), String> {
+    let (session, team_id) = developer_session(&account).await?;
+
+    let ledger = plume_store::QuotaLedger::load(&Some(crate::defaults::get_quota_ledger_path()))
+        .await
+        .map_err(|e| e.to_string())?;
+    let created_at = |app_id_id: &str| {
+        ledger
+            .teams()
+            .get(&team_id)?
+            .app_ids
+            .iter()
+            .find(|entry| entry.identifier == app_id_id)
+            .map(|entry| entry.created_at)
+    };
+
+    let app_ids = session
+        .qh_list_app_ids(&team_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .app_ids;
+
+    let mut rows = Vec::with_capacity(app_ids.len());
+    for app_id in app_ids {
+        // Only the detail lists the groups, skip the request for App IDs without any
+        let group_count = app_id
+            .associated_application_groups_count
+            .and_then(|count| count.as_unsigned());
+        let app_groups = if group_count == Some(0) {
+            Vec::new()
+        } else {
+            session
+                .qh_get_app_id_detail(&team_id, &app_id.app_id_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .app_id
+                .associated_application_groups
+                .unwrap_or_default()
+                .into_iter()
+                .map(|group| group.identifier)
+                .collect()
+        };
+
+        rows.push(AppIdRow {
+            created_at: created_at(&app_id.app_id_id),
+            app_groups,
+            app_id_id: app_id.app_id_id,
+            identifier: app_id.identifier,
+            name: app_id.name,
+        });
+    }
+
+    Ok((team_id, rows))
+}
+
+/// Deletes every App ID in `app_id_ids`, carrying on past failures.
+pub(crate) async fn delete_app_ids(
+    account: plume_store::GsaAccount,
+    team_id: String,
+    app_id_ids: Vec<String>,
+) -> Result<(), String> {
+    let (session, _) = developer_session(&account).await?;
+
+    let mut failures = Vec::new();
+    for app_id_id in &app_id_ids {
+        if let Err(e) = session.qh_delete_app_id(&team_id, app_id_id).await {
+            failures.push(format!("{app_id_id}: {e}"));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
@@ -1,14 +1,23 @@
 use std::io::Write;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::{Ok, Result};
 use clap::{Args, Subcommand};
-use dialoguer::Select;
+use dialoguer::{Confirm, Select};
 
-use plume_core::{AnisetteConfiguration, auth::Account, developer::DeveloperSession};
-use plume_store::AccountStore;
+use plume_core::{
+    AnisetteConfiguration,
+    auth::Account,
+    developer::{
+        DeveloperSession,
+        qh::{app_ids::AppID, teams::Team},
+    },
+};
+use plume_store::{AccountStore, QuotaKind, QuotaLedger};
+use plume_utils::unused_app_ids;
 
-use crate::get_data_path;
+use crate::{commands::device::select_device, get_data_path};
 
 #[derive(Debug, Args)]
 #[command(arg_required_else_help = true)]
@@ -28,14 +37,20 @@ pub enum AccountCommands {
     List,
     /// Switch to a different account
     Switch(SwitchArgs),
-    /// List certificates for a team
+    /// List, download or revoke certificates for a team
     Certificates(CertificatesArgs),
-    /// List devices registered to the account
+    /// List, enable, disable or rename devices registered to the account
     Devices(DevicesArgs),
+    /// List, download or delete provisioning profiles
+    Profiles(ProfilesArgs),
     /// Register a new device
     RegisterDevice(RegisterDeviceArgs),
     /// List all app IDs for a team
     AppIds(AppIdsArgs),
+    /// Show App IDs and certificates created per team and when free account slots free up
+    Quota(QuotaArgs),
+    /// List app groups and the App IDs they're assigned to
+    AppGroups(AppGroupsArgs),
 }
 
 #[derive(Debug, Args)]
@@ -57,6 +72,23 @@ pub struct CertificatesArgs {
     /// Filter by certificate type (development, distribution)
     #[arg(long = "type", value_name = "TYPE")]
     pub cert_type: Option<String>,
+    /// Revoke a certificate by ID
+    #[arg(long = "revoke", value_name = "ID", conflicts_with = "download")]
+    pub revoke: Option<String>,
+    /// Download a certificate (DER, without its private key) by ID
+    #[arg(long = "download", value_name = "ID", requires = "output")]
+    pub download: Option<String>,
+    /// Where --download saves to
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "PATH",
+        requires = "download"
+    )]
+    pub output: Option<PathBuf>,
+    /// Revoke without asking for confirmation
+    #[arg(short = 'y', long = "yes", requires = "revoke")]
+    pub yes: bool,
 }
 
 #[derive(Debug, Args)]
@@ -67,6 +99,42 @@ pub struct DevicesArgs {
     /// Filter by device platform (ios, tvos, watchos)
     #[arg(long = "platform", value_name = "PLATFORM")]
     pub platform: Option<String>,
+    /// Enable a device by ID
+    #[arg(long = "enable", value_name = "ID", conflicts_with_all = ["disable", "rename"])]
+    pub enable: Option<String>,
+    /// Disable a device by ID
+    #[arg(long = "disable", value_name = "ID", conflicts_with = "rename")]
+    pub disable: Option<String>,
+    /// Rename a device by ID
+    #[arg(long = "rename", value_name = "ID", requires = "name")]
+    pub rename: Option<String>,
+    /// New name for --rename
+    #[arg(long = "name", value_name = "NAME", requires = "rename")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ProfilesArgs {
+    /// Team ID to list provisioning profiles for
+    #[arg(short = 't', long = "team", value_name = "TEAM_ID")]
+    pub team_id: Option<String>,
+    /// Delete a profile by ID
+    #[arg(long = "delete", value_name = "ID", conflicts_with = "download")]
+    pub delete: Option<String>,
+    /// Download a profile by ID
+    #[arg(long = "download", value_name = "ID", requires = "output")]
+    pub download: Option<String>,
+    /// Where --download saves the .mobileprovision to
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "PATH",
+        requires = "download"
+    )]
+    pub output: Option<PathBuf>,
+    /// Delete without asking for confirmation
+    #[arg(short = 'y', long = "yes", requires = "delete")]
+    pub yes: bool,
 }
 
 #[derive(Debug, Args)]
@@ -87,6 +155,66 @@ pub struct AppIdsArgs {
     /// Team ID to list app IDs for
     #[arg(short = 't', long = "team", value_name = "TEAM_ID")]
     pub team_id: Option<String>,
+    /// Delete App IDs, by ID or bundle identifier
+    #[arg(long = "delete", value_name = "ID", num_args = 1.., conflicts_with = "delete_unused")]
+    pub delete: Option<Vec<String>>,
+    /// Delete App IDs that no app installed on the device uses. Only that one device is
+    /// checked, so App IDs of apps installed on other devices are deleted too
+    #[arg(long = "delete-unused")]
+    pub delete_unused: bool,
+    /// Device to check installed apps on (will prompt if not provided)
+    #[arg(
+        short = 'u',
+        long = "udid",
+        value_name = "UDID",
+        requires = "delete_unused"
+    )]
+    pub udid: Option<String>,
+    /// Delete without asking for confirmation
+    #[arg(short = 'y', long = "yes")]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct AppGroupsArgs {
+    /// Team ID to list app groups for
+    #[arg(short = 't', long = "team", value_name = "TEAM_ID")]
+    pub team_id: Option<String>,
+    /// Delete app groups, by ID or group identifier
+    #[arg(
+        long = "delete",
+        value_name = "GROUP",
+        num_args = 1..,
+        conflicts_with_all = ["rename", "unassign"]
+    )]
+    pub delete: Option<Vec<String>>,
+    /// Rename an app group, by ID or group identifier
+    #[arg(
+        long = "rename",
+        value_name = "GROUP",
+        requires = "name",
+        conflicts_with = "unassign"
+    )]
+    pub rename: Option<String>,
+    /// New name for --rename
+    #[arg(long = "name", value_name = "NAME", requires = "rename")]
+    pub name: Option<String>,
+    /// Remove an app group from an App ID, by ID or group identifier
+    #[arg(long = "unassign", value_name = "GROUP", requires = "app_id")]
+    pub unassign: Option<String>,
+    /// App ID to remove the group from with --unassign, by ID or bundle identifier
+    #[arg(long = "app-id", value_name = "ID", requires = "unassign")]
+    pub app_id: Option<String>,
+    /// Delete without asking for confirmation
+    #[arg(short = 'y', long = "yes")]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct QuotaArgs {
+    /// Only show this team
+    #[arg(short = 't', long = "team", value_name = "TEAM_ID")]
+    pub team_id: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -104,8 +232,11 @@ pub async fn execute(args: AccountArgs) -> Result<()> {
         AccountCommands::Switch(switch_args) => switch_account(switch_args).await,
         AccountCommands::Certificates(cert_args) => certificates(cert_args).await,
         AccountCommands::Devices(device_args) => devices(device_args).await,
+        AccountCommands::Profiles(profile_args) => profiles(profile_args).await,
         AccountCommands::RegisterDevice(register_args) => register_device(register_args).await,
         AccountCommands::AppIds(app_id_args) => app_ids(app_id_args).await,
+        AccountCommands::Quota(quota_args) => quota(quota_args).await,
+        AccountCommands::AppGroups(app_group_args) => app_groups(app_group_args).await,
     }
 }
 
@@ -113,6 +244,10 @@ fn get_settings_path() -> PathBuf {
     get_data_path().join("accounts.json")
 }
 
+pub fn get_quota_ledger_path() -> PathBuf {
+    get_data_path().join("quota.json")
+}
+
 pub async fn get_authenticated_account() -> Result<DeveloperSession> {
     let settings_path = get_settings_path();
     let settings = AccountStore::load(&Some(settings_path.clone())).await?;
@@ -216,7 +351,42 @@ async fn certificates(args: CertificatesArgs) -> Result<()> {
         args.team_id.unwrap()
     };
 
-    let p = session.qh_list_certs(&team_id).await?.certificates;
+    if let Some(id) = &args.revoke {
+        if !args.yes
+            && !Confirm::new()
+                .with_prompt(format!(
+                    "Revoke certificate {id}? Apps signed with it will stop launching."
+                ))
+                .default(false)
+                .interact()?
+        {
+            return Ok(());
+        }
+
+        session.v1_revoke_certificate(&team_id, id).await?;
+        log::info!("Revoked certificate {}", id);
+        return Ok(());
+    }
+
+    if let (Some(id), Some(output)) = (&args.download, &args.output) {
+        let certificate = session.v1_download_certificate(&team_id, id).await?;
+        tokio::fs::write(output, certificate).await?;
+        log::info!("Saved certificate to {}", output.display());
+        return Ok(());
+    }
+
+    let p: Vec<_> = session
+        .v1_list_certificates(&team_id)
+        .await?
+        .data
+        .into_iter()
+        .filter(|c| matches_filter(c.attributes.certificate_type.as_deref(), &args.cert_type))
+        .map(|mut c| {
+            // Download it with --download instead
+            c.attributes.certificate_content = None;
+            c
+        })
+        .collect();
 
     log::info!("{:#?}", p);
 
@@ -232,13 +402,84 @@ async fn devices(args: DevicesArgs) -> Result<()> {
         args.team_id.unwrap()
     };
 
-    let p = session.qh_list_devices(&team_id).await?.devices;
+    let updated = if let Some(id) = &args.enable {
+        Some(session.v1_enable_device(&team_id, id).await?)
+    } else if let Some(id) = &args.disable {
+        Some(session.v1_disable_device(&team_id, id).await?)
+    } else if let (Some(id), Some(name)) = (&args.rename, &args.name) {
+        Some(session.v1_rename_device(&team_id, id, name).await?)
+    } else {
+        None
+    };
+
+    if let Some(device) = updated {
+        log::info!("{:#?}", device.data);
+        return Ok(());
+    }
+
+    let p: Vec<_> = session
+        .v1_list_devices(&team_id)
+        .await?
+        .data
+        .into_iter()
+        .filter(|d| matches_filter(d.attributes.platform.as_deref(), &args.platform))
+        .collect();
 
     log::info!("{:#?}", p);
 
     Ok(())
 }
 
+async fn profiles(args: ProfilesArgs) -> Result<()> {
+    let session = get_authenticated_account().await?;
+
+    let team_id = if args.team_id.is_none() {
+        teams(&session).await?
+    } else {
+        args.team_id.unwrap()
+    };
+
+    if let Some(id) = &args.delete {
+        if !args.yes
+            && !Confirm::new()
+                .with_prompt(format!("Delete profile {id}?"))
+                .default(false)
+                .interact()?
+        {
+            return Ok(());
+        }
+
+        session.v1_delete_profile(&team_id, id).await?;
+        log::info!("Deleted profile {}", id);
+        return Ok(());
+    }
+
+    if let (Some(id), Some(output)) = (&args.download, &args.output) {
+        let profile = session.v1_download_profile(&team_id, id).await?;
+        tokio::fs::write(output, profile).await?;
+        log::info!("Saved profile to {}", output.display());
+        return Ok(());
+    }
+
+    let mut p = session.v1_list_profiles(&team_id).await?.data;
+    // The encoded profiles would drown out everything else
+    for profile in &mut p {
+        profile.attributes.profile_content = None;
+    }
+
+    log::info!("{:#?}", p);
+
+    Ok(())
+}
+
+/// Case-insensitive substring match, everything matches without a filter.
+fn matches_filter(value: Option<&str>, filter: &Option<String>) -> bool {
+    match filter {
+        Some(filter) => value.is_some_and(|v| v.to_lowercase().contains(&filter.to_lowercase())),
+        None => true,
+    }
+}
+
 async fn register_device(args: RegisterDeviceArgs) -> Result<()> {
     let session = get_authenticated_account().await?;
 
@@ -259,10 +500,15 @@ async fn register_device(args: RegisterDeviceArgs) -> Result<()> {
 }
 
 pub async fn teams(session: &DeveloperSession) -> Result<String> {
-    let teams = session.qh_list_teams().await?.teams;
+    Ok(select_team(session).await?.team_id)
+}
+
+/// Picks the only team of the account, or asks which one to use.
+pub async fn select_team(session: &DeveloperSession) -> Result<Team> {
+    let mut teams = session.qh_list_teams().await?.teams;
 
     if teams.len() == 1 {
-        return Ok(teams[0].team_id.clone());
+        return Ok(teams.remove(0));
     }
 
     let team_names: Vec<String> = teams
@@ -272,7 +518,7 @@ pub async fn teams(session: &DeveloperSession) -> Result<String> {
 
     let selection = Select::new().items(&team_names).default(0).interact()?;
 
-    Ok(teams[selection].team_id.clone())
+    Ok(teams.remove(selection))
 }
 
 pub async fn app_ids(args: AppIdsArgs) -> Result<()> {
@@ -284,13 +530,250 @@ pub async fn app_ids(args: AppIdsArgs) -> Result<()> {
         args.team_id.unwrap()
     };
 
-    let p = session.v1_list_app_ids(&team_id).await?.data;
+    if args.delete.is_none() && !args.delete_unused {
+        let p = session.v1_list_app_ids(&team_id).await?.data;
 
-    log::info!("{:#?}", p);
+        log::info!("{:#?}", p);
+
+        return Ok(());
+    }
+
+    let app_ids = session.qh_list_app_ids(&team_id).await?.app_ids;
+
+    let selected: Vec<&AppID> = if let Some(ids) = &args.delete {
+        for id in ids {
+            if !app_ids
+                .iter()
+                .any(|a| a.app_id_id == *id || a.identifier == *id)
+            {
+                return Err(anyhow::anyhow!("App ID '{}' not found", id));
+            }
+        }
+
+        app_ids
+            .iter()
+            .filter(|a| ids.contains(&a.app_id_id) || ids.contains(&a.identifier))
+            .collect()
+    } else {
+        let device = select_device(args.udid).await?;
+        let installed = device.installed_bundle_identifiers().await?;
+        unused_app_ids(&app_ids, &installed)
+    };
+
+    if selected.is_empty() {
+        log::info!("No App IDs to delete.");
+        return Ok(());
+    }
+
+    log::info!("App IDs to delete:");
+    for app_id in &selected {
+        log::info!(" {} ({})", app_id.identifier, app_id.app_id_id);
+    }
+
+    let prompt = if args.delete_unused {
+        format!(
+            "Delete {} App IDs? Only this device was checked, apps on other devices signed \
+             with them will need new ones.",
+            selected.len()
+        )
+    } else {
+        format!("Delete {} App IDs?", selected.len())
+    };
+    if !args.yes
+        && !Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for app_id in selected {
+        match session.qh_delete_app_id(&team_id, &app_id.app_id_id).await {
+            std::result::Result::Ok(_) => log::info!("Deleted {}", app_id.identifier),
+            Err(e) => {
+                log::error!("Failed to delete {}: {}", app_id.identifier, e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow::anyhow!("Failed to delete {} App IDs", failed));
+    }
+
+    Ok(())
+}
+
+pub async fn app_groups(args: AppGroupsArgs) -> Result<()> {
+    let session = get_authenticated_account().await?;
+
+    let team_id = if args.team_id.is_none() {
+        teams(&session).await?
+    } else {
+        args.team_id.unwrap()
+    };
+
+    let groups = session
+        .qh_list_app_groups(&team_id)
+        .await?
+        .application_group_list;
+    let find_group = |id: &String| {
+        groups
+            .iter()
+            .find(|g| g.application_group == *id || g.identifier == *id)
+            .ok_or_else(|| anyhow::anyhow!("App group '{}' not found", id))
+    };
+
+    if let Some(ids) = &args.delete {
+        let selected = ids.iter().map(find_group).collect::<Result<Vec<_>>>()?;
+
+        log::info!("App groups to delete:");
+        for group in &selected {
+            log::info!(" {} ({})", group.identifier, group.application_group);
+        }
+
+        if !args.yes
+            && !Confirm::new()
+                .with_prompt(format!("Delete {} app groups?", selected.len()))
+                .default(false)
+                .interact()?
+        {
+            return Ok(());
+        }
+
+        let mut failed = 0;
+        for group in selected {
+            match session
+                .qh_delete_app_group(&team_id, &group.application_group)
+                .await
+            {
+                std::result::Result::Ok(_) => log::info!("Deleted {}", group.identifier),
+                Err(e) => {
+                    log::error!("Failed to delete {}: {}", group.identifier, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        if failed > 0 {
+            return Err(anyhow::anyhow!("Failed to delete {} app groups", failed));
+        }
+
+        return Ok(());
+    }
+
+    if let (Some(id), Some(name)) = (&args.rename, &args.name) {
+        let group = find_group(id)?;
+        session
+            .qh_update_app_group(&team_id, &group.application_group, name)
+            .await?;
+        log::info!("Renamed {} to {}", group.identifier, name);
+
+        return Ok(());
+    }
+
+    if let (Some(id), Some(app_id)) = (&args.unassign, &args.app_id) {
+        let group = find_group(id)?;
+        let app_id = session
+            .qh_list_app_ids(&team_id)
+            .await?
+            .app_ids
+            .into_iter()
+            .find(|a| a.app_id_id == *app_id || a.identifier == *app_id)
+            .ok_or_else(|| anyhow::anyhow!("App ID '{}' not found", app_id))?;
+
+        let unassigned = session
+            .qh_unassign_app_group(&team_id, &app_id.app_id_id, &group.application_group)
+            .await?;
+        match unassigned {
+            Some(_) => log::info!("Removed {} from {}", group.identifier, app_id.identifier),
+            None => log::info!(
+                "{} is not assigned to {}",
+                group.identifier,
+                app_id.identifier
+            ),
+        }
+
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        log::info!("No app groups found.");
+        return Ok(());
+    }
+
+    let assignments = session.qh_app_group_assignments(&team_id).await?;
+
+    for group in &groups {
+        log::info!(
+            "{} ({}) {}",
+            group.identifier,
+            group.application_group,
+            group.name
+        );
+        match assignments.get(&group.application_group) {
+            Some(app_ids) => log::info!("  assigned to: {}", app_ids.join(", ")),
+            None => log::info!("  not assigned to any App ID"),
+        }
+    }
 
     Ok(())
 }
 
+async fn quota(args: QuotaArgs) -> Result<()> {
+    let ledger = QuotaLedger::load(&Some(get_quota_ledger_path())).await?;
+
+    let mut team_ids = ledger
+        .teams()
+        .keys()
+        .filter(|team_id| args.team_id.as_ref().is_none_or(|t| t == *team_id))
+        .collect::<Vec<_>>();
+    team_ids.sort();
+
+    if team_ids.is_empty() {
+        log::info!("Nothing recorded yet, App IDs and certificates are recorded when signing.");
+        return Ok(());
+    }
+
+    for team_id in team_ids {
+        log::info!("Team {team_id}:");
+        for (name, kind) in [
+            ("App IDs", QuotaKind::AppId),
+            ("Certificates", QuotaKind::Certificate),
+        ] {
+            let status = ledger.status(team_id, kind);
+            log::info!(
+                " {name}: {} of {} used, {} left",
+                status.used(),
+                status.limit,
+                status.remaining()
+            );
+            for slot in &status.in_use {
+                log::info!(
+                    "  {} (frees up in {})",
+                    slot.identifier,
+                    format_duration(slot.frees_in)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs().div_ceil(60);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h"),
+    }
+}
+
 async fn list_accounts() -> Result<()> {
     let settings_path = get_settings_path();
     let settings = AccountStore::load(&Some(settings_path)).await?;
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use clap::Args;
-use plume_core::{MachO, MachOExt};
+use plume_core::{MachO, MachOExt, MachOInfo};
 use std::path::PathBuf;
 
 #[derive(Debug, Args)]
@@ -10,6 +10,12 @@ pub struct MachArgs {
     pub binary: PathBuf,
     #[arg(long)]
     pub entitlements: bool,
+    /// Print header, load commands, segments and code signature of every slice
+    #[arg(long)]
+    pub info: bool,
+    /// Print `--info` as JSON
+    #[arg(long, requires = "info")]
+    pub json: bool,
     /// List all dylib dependencies
     #[arg(long)]
     pub list_dylibs: bool,
@@ -19,6 +25,9 @@ pub struct MachArgs {
     /// Replace an existing dylib dependency
     #[arg(long, value_names = &["OLD", "NEW"], num_args = 2)]
     pub replace_dylib: Option<Vec<String>>,
+    /// Remove the code signature from every slice
+    #[arg(long)]
+    pub strip_signature: bool,
     /// Set the SDK version (e.g., 26.0.0)
     #[arg(long, value_name = "SDK_VERSION")]
     pub sdk_version: Option<String>,
@@ -39,20 +48,32 @@ pub async fn execute(args: MachArgs) -> Result<()> {
         }
     }
 
+    if args.info {
+        let info = macho.info()?;
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+        } else {
+            for slice in &info {
+                print_info(slice);
+            }
+        }
+        return Ok(());
+    }
+
     if args.list_dylibs {
         // TODO: add index argument
-        let d = macho
-            .macho_file()
-            .nth_macho(0)
-            .unwrap()
-            .dylib_load_paths()
-            .unwrap();
-        for path in d {
+        let macho_file = macho.macho_file()?;
+        for path in macho_file.nth_macho(0)?.dylib_load_paths()? {
             println!("{path}");
         }
         return Ok(());
     }
 
+    if args.strip_signature {
+        macho.remove_code_signature()?;
+        return Ok(());
+    }
+
     if let Some(sdk_version) = &args.sdk_version {
         macho.replace_sdk_version(sdk_version)?;
         return Ok(());
@@ -70,3 +91,86 @@ pub async fn execute(args: MachArgs) -> Result<()> {
 
     Ok(())
 }
+
+fn print_info(info: &MachOInfo) {
+    println!("Slice: {}", info.arch);
+    println!(
+        "  Header: cputype={:#x} cpusubtype={:#x} filetype={} flags={:#x} ncmds={} sizeofcmds={}",
+        info.cputype, info.cpusubtype, info.filetype, info.flags, info.ncmds, info.sizeofcmds
+    );
+    if let Some(uuid) = &info.uuid {
+        println!("  UUID: {uuid}");
+    }
+    if let Some(id) = &info.id_dylib {
+        println!("  Install name: {id}");
+    }
+    if let Some(build) = &info.build_version {
+        println!(
+            "  Build version: {} (min {}, sdk {})",
+            build.platform, build.minos, build.sdk
+        );
+    }
+    if let Some(enc) = &info.encryption_info {
+        println!(
+            "  Encryption: cryptid={} cryptoff={:#x} cryptsize={:#x}",
+            enc.cryptid, enc.cryptoff, enc.cryptsize
+        );
+    }
+    for rpath in &info.rpaths {
+        println!("  Rpath: {rpath}");
+    }
+
+    println!("  Load commands:");
+    for cmd in &info.load_commands {
+        let fields = cmd
+            .fields
+            .iter()
+            .map(|f| format!("{}={}", f.name, f.value))
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!(
+            "    [{:#06x}] {} ({} bytes) {}",
+            cmd.offset, cmd.name, cmd.cmdsize, fields
+        );
+    }
+
+    println!("  Segments:");
+    for segment in &info.segments {
+        println!(
+            "    {} vmaddr={:#x} vmsize={:#x} fileoff={:#x} filesize={:#x}",
+            segment.name, segment.vmaddr, segment.vmsize, segment.fileoff, segment.filesize
+        );
+        for section in &segment.sections {
+            println!(
+                "      {} addr={:#x} size={:#x} offset={:#x}",
+                section.name, section.addr, section.size, section.offset
+            );
+        }
+    }
+
+    match &info.code_signature {
+        Some(sig) if !sig.parsed => println!(
+            "  Code signature: {} bytes at {:#x}, could not be parsed",
+            sig.datasize, sig.dataoff
+        ),
+        Some(sig) => {
+            println!(
+                "  Code signature: {} bytes at {:#x}, entitlements={}, cms={}",
+                sig.datasize, sig.dataoff, sig.has_entitlements, sig.has_cms_signature
+            );
+            for cd in &sig.code_directories {
+                println!(
+                    "    CodeDirectory v{:#x} ({}) cdhash={}",
+                    cd.version, cd.hash_type, cd.cdhash
+                );
+                println!(
+                    "      identifier={} team={} flags={:#x}",
+                    cd.identifier.as_deref().unwrap_or("-"),
+                    cd.team_id.as_deref().unwrap_or("-"),
+                    cd.flags
+                );
+            }
+        }
+        None => println!("  Code signature: none"),
+    }
+}
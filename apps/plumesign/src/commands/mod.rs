@@ -1,9 +1,13 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use plume_core::NetworkConfig;
 
 pub mod account;
 pub mod device;
 pub mod macho;
 pub mod sign;
+pub mod tweaks;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -15,10 +19,57 @@ pub mod sign;
     arg_required_else_help = true
 )]
 pub struct Cli {
+    /// Directory for temporary files (defaults to PLUME_STAGE_DIR or the system temp dir)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub stage_dir: Option<PathBuf>,
+    #[command(flatten)]
+    pub network: NetworkArgs,
     #[command(subcommand)]
     pub command: Commands,
 }
 
+/// Settings for requests to Apple, unset ones fall back to the `PLUME_*` environment variables.
+/// Anisette data is fetched without them
+#[derive(Debug, Args)]
+pub struct NetworkArgs {
+    /// Proxy for requests to Apple (http://, https://, socks5:// or socks5h:// URL) [env: PLUME_PROXY]
+    #[arg(long, global = true, value_name = "URL")]
+    pub proxy: Option<String>,
+    /// Proxy username [env: PLUME_PROXY_USERNAME]
+    #[arg(long, global = true, value_name = "USER")]
+    pub proxy_username: Option<String>,
+    /// Proxy password, prefer PLUME_PROXY_PASSWORD to keep it out of the process list
+    #[arg(long, global = true, value_name = "PASSWORD")]
+    pub proxy_password: Option<String>,
+    /// Extra trusted CA certificate, PEM or DER (can be repeated) [env: PLUME_CA_CERTS]
+    #[arg(long = "ca-cert", global = true, value_name = "FILE")]
+    pub ca_certs: Vec<PathBuf>,
+    /// Seconds to wait for a connection [env: PLUME_CONNECT_TIMEOUT]
+    #[arg(long, global = true, value_name = "SECS")]
+    pub connect_timeout: Option<u64>,
+    /// Seconds a request may take [env: PLUME_TIMEOUT]
+    #[arg(long, global = true, value_name = "SECS")]
+    pub timeout: Option<u64>,
+    /// Skip TLS certificate validation, only for debugging [env: PLUME_ACCEPT_INVALID_CERTS]
+    #[arg(long, global = true)]
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl NetworkArgs {
+    pub fn to_config(&self) -> NetworkConfig {
+        NetworkConfig {
+            proxy: self.proxy.clone(),
+            proxy_username: self.proxy_username.clone(),
+            proxy_password: self.proxy_password.clone(),
+            ca_certificates: self.ca_certs.clone(),
+            connect_timeout: self.connect_timeout,
+            timeout: self.timeout,
+            accept_invalid_certs: self.danger_accept_invalid_certs,
+        }
+        .with_env_defaults()
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Sign an iOS app bundle with certificate and provisioning profile
@@ -29,4 +80,6 @@ pub enum Commands {
     Account(account::AccountArgs),
     /// Device management commands
     Device(device::DeviceArgs),
+    /// List tweaks injected into an app bundle or package
+    Tweaks(tweaks::TweaksArgs),
 }
@@ -4,11 +4,15 @@ use anyhow::Result;
 use clap::Args;
 
 use plume_core::{CertificateIdentity, MobileProvision};
-use plume_utils::{Bundle, Package, Signer, SignerMode, SignerOptions};
+use plume_store::{QuotaKind, QuotaLedger};
+use plume_utils::{
+    Bundle, ExtractLimits, Package, Signer, SignerMode, SignerOptions, SignerRuntime,
+    TweakInjection, TweakSettings, TweakTarget,
+};
 
 use crate::{
     commands::{
-        account::{get_authenticated_account, teams},
+        account::{get_authenticated_account, get_quota_ledger_path, select_team},
         device::select_device,
     },
     get_data_path,
@@ -38,9 +42,46 @@ pub struct SignArgs {
     /// Custom bundle version to set
     #[arg(long = "custom-version", value_name = "VERSION")]
     pub version: Option<String>,
-    /// Perform ad-hoc signing (no certificate required)
+    /// Tweaks to inject: .deb, .dylib, .zip, .framework, .bundle, .appex or an unpacked rootfs folder
     #[arg(long, short, num_args = 1..)]
     pub tweaks: Option<Vec<PathBuf>>,
+    /// Hooking runtime for tweaks: `ellekit`, `none`, or a path to an ElleKit/Substitute/libhooker build
+    #[arg(long, value_name = "RUNTIME", value_parser = parse_runtime, default_value = "ellekit")]
+    pub runtime: SignerRuntime,
+    /// Remove previously injected tweaks by name (see `plumesign tweaks`)
+    #[arg(long = "remove-tweak", value_name = "NAME", num_args = 1..)]
+    pub remove_tweaks: Option<Vec<String>>,
+    /// Inject tweak dylibs even when their MobileSubstrate filter targets other apps
+    #[arg(long)]
+    pub ignore_tweak_filters: bool,
+    /// Binaries that load the tweaks: `main`, `extensions`, or comma separated bundle names/identifiers
+    #[arg(long, value_name = "TARGET", value_parser = parse_tweak_target, default_value = "main")]
+    pub tweak_target: TweakTarget,
+    /// Link tweaks with LC_LOAD_DYLIB instead of LC_LOAD_WEAK_DYLIB, the app won't launch without them
+    #[arg(long)]
+    pub require_tweaks: bool,
+    /// Where tweak settings go: `in-app`, `settings-bundle` (also shown in the Settings app) or `none`
+    #[arg(long, value_name = "MODE", value_parser = parse_tweak_settings, default_value = "in-app")]
+    pub tweak_settings: TweakSettings,
+    /// Refuse packages and tweaks that unpack to more than this many MiB
+    #[arg(long, value_name = "MIB")]
+    pub max_extract_size: Option<u64>,
+    /// Refuse packages and tweaks with more than this many archive entries
+    #[arg(long, value_name = "COUNT")]
+    pub max_extract_entries: Option<usize>,
+    /// Bundles registered with the developer portal at the same time
+    #[arg(long, value_name = "COUNT", default_value_t = 3)]
+    pub register_concurrency: usize,
+    /// Keep App IDs and app groups created by a registration that failed
+    #[arg(long, conflicts_with = "rollback")]
+    pub no_rollback: bool,
+    /// Delete what a failed registration created on free teams too, where deleted App IDs
+    /// still count against the weekly limit
+    #[arg(long)]
+    pub rollback: bool,
+    /// Managed capability Apple granted the team (e.g. NETWORK_SLICING), can be repeated
+    #[arg(long = "granted-capability", value_name = "CAPABILITY")]
+    pub granted_capabilities: Vec<String>,
     /// Register device and install after signing
     #[arg(long)]
     pub register_and_install: bool,
@@ -56,6 +97,45 @@ pub struct SignArgs {
     pub mac: bool,
 }
 
+fn parse_runtime(value: &str) -> Result<SignerRuntime, String> {
+    match value.to_lowercase().as_str() {
+        "ellekit" => Ok(SignerRuntime::ElleKit),
+        "none" => Ok(SignerRuntime::None),
+        _ => {
+            let path = PathBuf::from(value);
+            if path.exists() {
+                Ok(SignerRuntime::Custom(path))
+            } else {
+                Err(format!("runtime not found: {value}"))
+            }
+        }
+    }
+}
+
+fn parse_tweak_target(value: &str) -> Result<TweakTarget, String> {
+    match value.to_lowercase().as_str() {
+        "main" => Ok(TweakTarget::MainApp),
+        "extensions" => Ok(TweakTarget::AllExtensions),
+        _ => Ok(TweakTarget::Bundles(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )),
+    }
+}
+
+fn parse_tweak_settings(value: &str) -> Result<TweakSettings, String> {
+    match value.to_lowercase().as_str() {
+        "in-app" => Ok(TweakSettings::InApp),
+        "settings-bundle" => Ok(TweakSettings::SettingsBundle),
+        "none" => Ok(TweakSettings::None),
+        _ => Err(format!("unknown tweak settings mode: {value}")),
+    }
+}
+
 pub async fn execute(args: SignArgs) -> Result<()> {
     if !args.package.is_dir() && !args.apple_id && args.output.is_none() {
         return Err(anyhow::anyhow!(
@@ -63,11 +143,44 @@ pub async fn execute(args: SignArgs) -> Result<()> {
         ));
     }
 
+    let injection = TweakInjection {
+        target: args.tweak_target,
+        weak: !args.require_tweaks,
+    };
+    let tweak_injection = args
+        .tweaks
+        .iter()
+        .flatten()
+        .map(|tweak| (tweak.clone(), injection.clone()))
+        .collect();
+
+    let defaults = ExtractLimits::default();
+    let extract_limits = ExtractLimits {
+        max_size: args
+            .max_extract_size
+            .map(|mib| mib.saturating_mul(1024 * 1024))
+            .unwrap_or(defaults.max_size),
+        max_entries: args.max_extract_entries.unwrap_or(defaults.max_entries),
+    };
+
     let mut options = SignerOptions {
         custom_identifier: args.bundle_identifier,
         custom_name: args.name,
         custom_version: args.version,
         tweaks: args.tweaks,
+        tweak_injection,
+        runtime: args.runtime,
+        remove_tweaks: args.remove_tweaks,
+        ignore_tweak_filters: args.ignore_tweak_filters,
+        tweak_settings: args.tweak_settings,
+        extract_limits,
+        register_concurrency: args.register_concurrency,
+        rollback_registration: match (args.rollback, args.no_rollback) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        },
+        granted_capabilities: args.granted_capabilities,
         ..Default::default()
     };
 
@@ -80,7 +193,7 @@ pub async fn execute(args: SignArgs) -> Result<()> {
         }
         (Bundle::new(&args.package)?, None)
     } else {
-        let pkg = Package::new(args.package.clone())?;
+        let pkg = Package::new(args.package.clone())?.with_extract_limits(extract_limits);
         let bundle = pkg.get_package_bundle()?;
         (bundle, Some(pkg))
     };
@@ -92,15 +205,24 @@ pub async fn execute(args: SignArgs) -> Result<()> {
         (Signer::new(Some(cert_identity), options), None)
     } else if args.apple_id {
         let session = get_authenticated_account().await?;
-        let team_id = teams(&session).await?;
+        let team = select_team(&session).await?;
+        let team_id = team.team_id.clone();
+        let mut ledger = QuotaLedger::load(&Some(get_quota_ledger_path())).await?;
         let cert_identity =
             CertificateIdentity::new_with_session(&session, get_data_path(), None, &team_id)
                 .await?;
 
+        if let (true, Some(serial_number)) = (cert_identity.created, &cert_identity.serial_number) {
+            ledger
+                .record(&team_id, QuotaKind::Certificate, serial_number)
+                .await?;
+        }
+
         options.mode = SignerMode::Pem;
+        options.app_id_budget = Some(ledger.status(&team_id, QuotaKind::AppId).remaining());
         (
             Signer::new(Some(cert_identity), options),
-            Some((session, team_id)),
+            Some((session, team_id, team.team_type())),
         )
     } else {
         options.mode = SignerMode::Adhoc;
@@ -137,7 +259,7 @@ pub async fn execute(args: SignArgs) -> Result<()> {
         None
     };
 
-    if let Some((session, team_id)) = team_id_opt {
+    if let Some((session, team_id, team_type)) = team_id_opt {
         signer
             .modify_bundle(&bundle, &Some(team_id.clone()))
             .await?;
@@ -149,7 +271,25 @@ pub async fn execute(args: SignArgs) -> Result<()> {
                 .await?;
         }
 
-        signer.register_bundle(&bundle, &session, &team_id).await?;
+        let registered = signer
+            .register_bundle(&bundle, &session, &team_id, team_type)
+            .await;
+        if let Some(report) = &signer.registration_report {
+            log::info!("Registration:\n{report}");
+            let skipped_capabilities = report.skipped_capabilities();
+            if !skipped_capabilities.is_empty() {
+                log::warn!(
+                    "The team can't enable {}, features relying on them won't work",
+                    skipped_capabilities.join(", ")
+                );
+            }
+
+            QuotaLedger::load(&Some(get_quota_ledger_path()))
+                .await?
+                .record_registration(&team_id, report.created_app_ids())
+                .await?;
+        }
+        registered?;
         signer.sign_bundle(&bundle).await?;
 
         if let Some(dev) = device {
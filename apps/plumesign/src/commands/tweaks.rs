@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use plume_utils::{Bundle, Package, Tweak};
+
+#[derive(Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct TweaksArgs {
+    /// Path to the app bundle or package to inspect (.app or .ipa)
+    #[arg(value_name = "PACKAGE")]
+    pub package: PathBuf,
+}
+
+pub async fn execute(args: TweaksArgs) -> Result<()> {
+    let injected = if args.package.is_dir() {
+        Tweak::injected(&Bundle::new(&args.package)?)?
+    } else {
+        let pkg = Package::new(args.package.clone())?;
+        let injected = pkg.injected_tweaks();
+        pkg.remove_package_stage();
+        injected?
+    };
+
+    if injected.is_empty() {
+        log::info!("No injected tweaks found");
+        return Ok(());
+    }
+
+    for tweak in injected {
+        let kind = if tweak.is_runtime { " (runtime)" } else { "" };
+        println!("{}{} -> {}", tweak.name, kind, tweak.load_path);
+    }
+
+    log::info!("Use `plumesign sign --remove-tweak <NAME>` to remove a tweak while re-signing");
+
+    Ok(())
+}
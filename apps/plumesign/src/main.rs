@@ -13,11 +13,19 @@ async fn main() -> anyhow::Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
     let cli = Cli::parse();
 
+    plume_core::set_network_config(cli.network.to_config());
+    plume_utils::set_stage_root(cli.stage_dir.clone());
+    let removed = plume_utils::sweep_stale_stages();
+    if removed > 0 {
+        log::info!("Cleaned up {removed} leftover stage directories");
+    }
+
     match cli.command {
         Commands::Sign(args) => commands::sign::execute(args).await?,
         Commands::MachO(args) => commands::macho::execute(args).await?,
         Commands::Account(args) => commands::account::execute(args).await?,
         Commands::Device(args) => commands::device::execute(args).await?,
+        Commands::Tweaks(args) => commands::tweaks::execute(args).await?,
     }
 
     Ok(())
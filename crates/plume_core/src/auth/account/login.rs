@@ -5,12 +5,12 @@ use sha2::{Digest, Sha256};
 use srp::client::{SrpClient, SrpClientVerifier};
 use srp::groups::G_2048;
 
-use crate::Error;
+use crate::{Error, HttpClient, HttpRequest, Method};
 
 use crate::auth::account::{check_error, parse_response};
 use crate::auth::anisette_data::AnisetteData;
 use crate::auth::{
-    Account, ChallengeRequest, ChallengeRequestBody, GSA_ENDPOINT, InitRequest, InitRequestBody,
+    Account, ChallengeRequest, ChallengeRequestBody, GSA_SERVICE, InitRequest, InitRequestBody,
     LoginState, RequestHeader,
 };
 
@@ -61,7 +61,19 @@ impl Account {
         tfa_closure: G,
         anisette: AnisetteData,
     ) -> Result<Account, Error> {
-        let mut _self = Account::new_with_anisette(anisette)?;
+        Account::login_with_http(appleid_closure, tfa_closure, anisette, HttpClient::new()?).await
+    }
+
+    pub async fn login_with_http<
+        F: Fn() -> Result<(String, String), String>,
+        G: Fn() -> Result<String, String>,
+    >(
+        appleid_closure: F,
+        tfa_closure: G,
+        anisette: AnisetteData,
+        http: HttpClient,
+    ) -> Result<Account, Error> {
+        let mut _self = Account::new_with_http(anisette, http);
         let (username, password) = appleid_closure().map_err(|e| {
             Error::AuthSrpWithMessage(0, format!("Failed to get Apple ID credentials: {}", e))
         })?;
@@ -153,15 +165,14 @@ impl Account {
         let mut buffer = Vec::new();
         plist::to_writer_xml(&mut buffer, &init_packet)?;
 
-        let res = self
-            .client
-            .post(GSA_ENDPOINT)
-            .headers(gsa_headers.clone())
-            .body(buffer)
-            .send()
-            .await;
+        let request = HttpRequest::new(
+            Method::POST,
+            self.http.endpoints().grandslam_url(GSA_SERVICE),
+        )
+        .headers(gsa_headers.clone())
+        .body(buffer);
 
-        let res = parse_response(res).await?;
+        let res = parse_response(self.http.send(request).await?)?;
         check_error(&res)?;
 
         let salt = res.get("s").unwrap().as_data().unwrap();
@@ -199,15 +210,14 @@ impl Account {
         let mut buffer = Vec::new();
         plist::to_writer_xml(&mut buffer, &challenge_packet)?;
 
-        let res = self
-            .client
-            .post(GSA_ENDPOINT)
-            .headers(gsa_headers)
-            .body(buffer)
-            .send()
-            .await;
+        let request = HttpRequest::new(
+            Method::POST,
+            self.http.endpoints().grandslam_url(GSA_SERVICE),
+        )
+        .headers(gsa_headers)
+        .body(buffer);
 
-        let res = parse_response(res).await?;
+        let res = parse_response(self.http.send(request).await?)?;
         check_error(&res)?;
 
         let m2 = res.get("M2").unwrap().as_data().unwrap();
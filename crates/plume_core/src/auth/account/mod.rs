@@ -4,17 +4,13 @@ mod two_factor_auth;
 
 use cbc::cipher::{BlockDecryptMut, KeyIvInit, block_padding::Pkcs7};
 use hmac::{Hmac, Mac};
-use reqwest::Response;
 use sha2::Sha256;
 use srp::client::SrpClientVerifier;
 
-use crate::Error;
+use crate::{Error, HttpResponse};
 
-pub async fn parse_response(
-    res: Result<Response, reqwest::Error>,
-) -> Result<plist::Dictionary, Error> {
-    let res = res?.text().await?;
-    let res: plist::Dictionary = plist::from_bytes(res.as_bytes())?;
+pub fn parse_response(res: HttpResponse) -> Result<plist::Dictionary, Error> {
+    let res: plist::Dictionary = plist::from_bytes(&res.body)?;
     let res: plist::Value = res.get("Response").unwrap().to_owned();
     match res {
         plist::Value::Dictionary(dict) => Ok(dict),
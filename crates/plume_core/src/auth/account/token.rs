@@ -8,12 +8,12 @@ use hmac::digest::KeyInit;
 use hmac::{Hmac, Mac};
 use reqwest::header::{HeaderMap, HeaderValue};
 
-use crate::Error;
+use crate::{Error, HttpRequest, Method};
 use sha2::Sha256;
 
 use crate::auth::account::{check_error, parse_response};
 use crate::auth::{
-    Account, AppToken, AuthTokenRequest, AuthTokenRequestBody, GSA_ENDPOINT, RequestHeader,
+    Account, AppToken, AuthTokenRequest, AuthTokenRequestBody, GSA_SERVICE, RequestHeader,
 };
 
 type Aes256Gcm16 = AesGcm<Aes256, U16>;
@@ -68,14 +68,13 @@ impl Account {
         plist::to_writer_xml(&mut buffer, &packet)?;
         let buffer = String::from_utf8(buffer).unwrap();
 
-        let res = self
-            .client
-            .post(GSA_ENDPOINT)
-            .headers(gsa_headers.clone())
-            .body(buffer)
-            .send()
-            .await;
-        let res = parse_response(res).await?;
+        let request = HttpRequest::new(
+            Method::POST,
+            self.http.endpoints().grandslam_url(GSA_SERVICE),
+        )
+        .headers(gsa_headers.clone())
+        .body(buffer);
+        let res = parse_response(self.http.send(request).await?)?;
         let err_check = check_error(&res);
         if err_check.is_err() {
             return Err(err_check.err().unwrap());
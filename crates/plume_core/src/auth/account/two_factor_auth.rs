@@ -1,6 +1,4 @@
-use std::str::FromStr;
-
-use crate::Error;
+use crate::{Error, HttpRequest, Method};
 use base64::{Engine, engine::general_purpose};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 
@@ -10,18 +8,18 @@ impl Account {
     pub async fn send_2fa_to_devices(&self) -> Result<LoginState, Error> {
         let headers = self.build_2fa_headers(false).await;
 
-        let res = self
-            .client
-            .get("https://gsa.apple.com/auth/verify/trusteddevice")
-            .headers(headers)
-            .send()
-            .await?;
-
-        let status_code = res.status();
+        let request = HttpRequest::new(
+            Method::GET,
+            self.http
+                .endpoints()
+                .grandslam_url("/auth/verify/trusteddevice"),
+        )
+        .headers(headers);
+        let res = self.http.send(request).await?;
 
-        if !status_code.is_success() {
+        if !res.is_success() {
             return Err(Error::AuthSrpWithMessage(
-                status_code.as_u16() as i64,
+                res.status as i64,
                 "Failed to send 2FA to devices".to_string(),
             ));
         }
@@ -38,19 +36,17 @@ impl Account {
             security_code: None,
         };
 
-        let res = self
-            .client
-            .put("https://gsa.apple.com/auth/verify/phone")
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await?;
-
-        let status_code = res.status();
+        let request = HttpRequest::new(
+            Method::PUT,
+            self.http.endpoints().grandslam_url("/auth/verify/phone"),
+        )
+        .headers(headers)
+        .json(&body)?;
+        let res = self.http.send(request).await?;
 
-        if !status_code.is_success() {
+        if !res.is_success() {
             return Err(Error::AuthSrpWithMessage(
-                status_code.as_u16() as i64,
+                res.status as i64,
                 "Failed to send SMS 2FA to devices".to_string(),
             ));
         }
@@ -61,16 +57,12 @@ impl Account {
     pub async fn get_auth_extras(&self) -> Result<AuthenticationExtras, Error> {
         let headers = self.build_2fa_headers(true);
 
-        let req = self
-            .client
-            .get("https://gsa.apple.com/auth")
+        let request = HttpRequest::new(Method::GET, self.http.endpoints().grandslam_url("/auth"))
             .headers(headers.await)
-            .header("Accept", "application/json")
-            .send()
-            .await?;
-        let status = req.status().as_u16();
-        let mut new_state = req.json::<AuthenticationExtras>().await?;
-        if status == 201 {
+            .header("Accept", HeaderValue::from_static("application/json"));
+        let res = self.http.send(request).await?;
+        let mut new_state = res.json::<AuthenticationExtras>()?;
+        if res.status == 201 {
             new_state.new_state = Some(LoginState::NeedsSMS2FAVerification(VerifyBody {
                 phone_number: PhoneNumber {
                     id: new_state.trusted_phone_numbers.first().unwrap().id,
@@ -87,18 +79,17 @@ impl Account {
         log::debug!("Verifying SMS 2FA with code: {}", code);
 
         let headers = self.build_2fa_headers(false);
-        let res = self
-            .client
-            .get("https://gsa.apple.com/grandslam/GsService2/validate")
-            .headers(headers.await)
-            .header(
-                HeaderName::from_str("security-code").unwrap(),
-                HeaderValue::from_str(&code).unwrap(),
-            )
-            .send()
-            .await?;
-
-        let res: plist::Dictionary = plist::from_bytes(res.text().await?.as_bytes())?;
+        let request = HttpRequest::new(
+            Method::GET,
+            self.http
+                .endpoints()
+                .grandslam_url("/grandslam/GsService2/validate"),
+        )
+        .headers(headers.await)
+        .header("security-code", HeaderValue::from_str(&code).unwrap());
+        let res = self.http.send(request).await?;
+
+        let res: plist::Dictionary = plist::from_bytes(&res.body)?;
 
         super::check_error(&res)?;
 
@@ -114,19 +105,19 @@ impl Account {
 
         let headers = self.build_2fa_headers(true).await;
         body.security_code = Some(VerifyCode { code });
-        let res = self
-            .client
-            .post("https://gsa.apple.com/auth/verify/phone/securitycode")
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await?;
-
-        let status_code = res.status();
+        let request = HttpRequest::new(
+            Method::POST,
+            self.http
+                .endpoints()
+                .grandslam_url("/auth/verify/phone/securitycode"),
+        )
+        .headers(headers)
+        .json(&body)?;
+        let res = self.http.send(request).await?;
 
         // TODO: 423 http code may occur, in this case we to ask for sending
         // last code sent (unlikely it would even work), or try again later
-        if !status_code.is_success() {
+        if !res.is_success() {
             return Err(Error::Bad2faCode);
         }
 
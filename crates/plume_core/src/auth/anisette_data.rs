@@ -13,7 +13,12 @@ pub struct AnisetteData {
 }
 
 impl AnisetteData {
+    /// Fetches fresh headers through `omnisette`, which ignores [`crate::NetworkConfig`].
     pub async fn new(config: AnisetteConfiguration) -> Result<Self, Error> {
+        if crate::network_config().proxy.is_some() {
+            log::warn!("Fetching anisette data without the configured proxy");
+        }
+
         let mut b = AnisetteHeaders::get_anisette_headers_provider(config.clone())?;
         let base_headers = b.provider.get_authentication_headers().await?;
 
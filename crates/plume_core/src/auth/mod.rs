@@ -2,21 +2,21 @@ pub mod account;
 pub mod anisette_data;
 
 use omnisette::AnisetteConfiguration;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use crate::auth::anisette_data::AnisetteData;
-use crate::{Error, client};
+use crate::{Error, HttpClient};
 
-const GSA_ENDPOINT: &str = "https://gsa.apple.com/grandslam/GsService2";
+/// GrandSlam service path, relative to [`crate::Endpoints::grandslam`].
+const GSA_SERVICE: &str = "/grandslam/GsService2";
 
 #[derive(Debug, Clone)]
 pub struct Account {
     pub anisette: Arc<Mutex<AnisetteData>>,
     pub spd: Option<plist::Dictionary>,
-    pub client: Client,
+    pub http: HttpClient,
 }
 
 impl Account {
@@ -26,12 +26,16 @@ impl Account {
     }
 
     pub fn new_with_anisette(anisette: AnisetteData) -> Result<Self, Error> {
-        let client = client()?;
-        Ok(Account {
+        Ok(Self::new_with_http(anisette, HttpClient::new()?))
+    }
+
+    /// Talks to the services through `http`, sessions created from the account inherit it.
+    pub fn new_with_http(anisette: AnisetteData, http: HttpClient) -> Self {
+        Account {
             anisette: Arc::new(Mutex::new(anisette)),
             spd: None,
-            client,
-        })
+            http,
+        }
     }
 }
 
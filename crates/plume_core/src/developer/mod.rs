@@ -1,16 +1,11 @@
 pub mod qh;
+mod retry;
 mod session;
 pub mod v1;
 
+pub use retry::RetryPolicy;
 pub use session::{DeveloperSession, RequestType};
 
-#[macro_export]
-macro_rules! developer_endpoint {
-    ($endpoint:expr) => {
-        format!("https://developerservices2.apple.com/services{}", $endpoint)
-    };
-}
-
 // Apple apis restrict certain characters in app names
 pub fn strip_invalid_chars(s: &str) -> String {
     s.chars().filter(|c| c.is_ascii_alphabetic()).collect()
@@ -4,19 +4,18 @@ use serde::Deserialize;
 use crate::Error;
 
 use super::{DeveloperSession, QHResponseMeta};
-use crate::developer_endpoint;
 
 impl DeveloperSession {
     pub async fn qh_get_account_info(
         &self,
         team_id: &String,
     ) -> Result<ViewDeveloperResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/viewDeveloper.action");
+        let endpoint = self.http().endpoints().qh_url("/viewDeveloper.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), true).await?;
         let response_data: ViewDeveloperResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
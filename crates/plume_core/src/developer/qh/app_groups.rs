@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use plist::{Dictionary, Value};
 use serde::Deserialize;
 
@@ -5,16 +7,20 @@ use crate::Error;
 
 use super::{DeveloperSession, QHResponseMeta};
 use crate::developer::strip_invalid_chars;
-use crate::developer_endpoint;
 
 impl DeveloperSession {
     pub async fn qh_list_app_groups(&self, team_id: &String) -> Result<AppGroupsResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/listApplicationGroups.action");
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/listApplicationGroups.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self
+            .qh_send_paged_request(&endpoint, body, "applicationGroupList")
+            .await?;
         let response_data: AppGroupsResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -26,14 +32,65 @@ impl DeveloperSession {
         name: &String,
         identifier: &String,
     ) -> Result<AppGroupResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/addApplicationGroup.action");
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/addApplicationGroup.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
         body.insert("name".to_string(), Value::String(strip_invalid_chars(name)));
         body.insert("identifier".to_string(), Value::String(identifier.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
+        let response_data: AppGroupResponse = plist::from_value(&Value::Dictionary(response))?;
+
+        Ok(response_data)
+    }
+
+    pub async fn qh_delete_app_group(
+        &self,
+        team_id: &String,
+        app_group_id: &String,
+    ) -> Result<QHResponseMeta, Error> {
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/deleteApplicationGroup.action");
+
+        let mut body = Dictionary::new();
+        body.insert("teamId".to_string(), Value::String(team_id.clone()));
+        body.insert(
+            "applicationGroup".to_string(),
+            Value::String(app_group_id.clone()),
+        );
+
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
+        let response_data: QHResponseMeta = plist::from_value(&Value::Dictionary(response))?;
+
+        Ok(response_data)
+    }
+
+    pub async fn qh_update_app_group(
+        &self,
+        team_id: &String,
+        app_group_id: &String,
+        name: &String,
+    ) -> Result<AppGroupResponse, Error> {
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/updateApplicationGroup.action");
+
+        let mut body = Dictionary::new();
+        body.insert("teamId".to_string(), Value::String(team_id.clone()));
+        body.insert(
+            "applicationGroup".to_string(),
+            Value::String(app_group_id.clone()),
+        );
+        body.insert("name".to_string(), Value::String(strip_invalid_chars(name)));
+
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
         let response_data: AppGroupResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -74,7 +131,10 @@ impl DeveloperSession {
         app_id_id: &String,
         app_group_ids: &Vec<String>,
     ) -> Result<QHResponseMeta, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/assignApplicationGroupToAppId.action");
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/assignApplicationGroupToAppId.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
@@ -89,11 +149,75 @@ impl DeveloperSession {
             ),
         );
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
         let response_data: QHResponseMeta = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
     }
+
+    /// Removes a group from an App ID. Assigning sets the App ID's whole group list, so this
+    /// assigns every other group it has again. Returns `None` if the group wasn't assigned.
+    pub async fn qh_unassign_app_group(
+        &self,
+        team_id: &String,
+        app_id_id: &String,
+        app_group_id: &String,
+    ) -> Result<Option<QHResponseMeta>, Error> {
+        // Without the current list, assigning would drop every other group
+        let assigned: Vec<String> = self
+            .qh_get_app_id_detail(team_id, app_id_id)
+            .await?
+            .app_id
+            .associated_application_groups
+            .ok_or(Error::DeveloperSessionRequestFailed)?
+            .into_iter()
+            .map(|group| group.application_group)
+            .collect();
+
+        if !assigned.contains(app_group_id) {
+            return Ok(None);
+        }
+
+        let remaining = assigned
+            .into_iter()
+            .filter(|group| group != app_group_id)
+            .collect();
+
+        self.qh_assign_app_group(team_id, app_id_id, &remaining)
+            .await
+            .map(Some)
+    }
+
+    /// Bundle identifiers of the App IDs each group is assigned to, keyed by the group's portal
+    /// ID. Costs a request per App ID that has groups.
+    pub async fn qh_app_group_assignments(
+        &self,
+        team_id: &String,
+    ) -> Result<HashMap<String, Vec<String>>, Error> {
+        let mut assignments: HashMap<String, Vec<String>> = HashMap::new();
+
+        for app_id in self.qh_list_app_ids(team_id).await?.app_ids {
+            let group_count = app_id
+                .associated_application_groups_count
+                .and_then(|count| count.as_unsigned());
+            if group_count == Some(0) {
+                continue;
+            }
+
+            let detail = self
+                .qh_get_app_id_detail(team_id, &app_id.app_id_id)
+                .await?
+                .app_id;
+            for group in detail.associated_application_groups.unwrap_or_default() {
+                assignments
+                    .entry(group.application_group)
+                    .or_default()
+                    .push(app_id.identifier.clone());
+            }
+        }
+
+        Ok(assignments)
+    }
 }
 
 #[allow(dead_code)]
@@ -115,7 +239,7 @@ pub struct AppGroupResponse {
 }
 
 #[allow(dead_code)]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ApplicationGroup {
     pub application_group: String, // this is the actual identifier
@@ -3,18 +3,19 @@ use serde::Deserialize;
 
 use crate::Error;
 
-use super::{DeveloperSession, QHResponseMeta};
+use super::{DeveloperSession, QHResponseMeta, app_groups::ApplicationGroup};
 use crate::developer::strip_invalid_chars;
-use crate::developer_endpoint;
 
 impl DeveloperSession {
     pub async fn qh_list_app_ids(&self, team_id: &String) -> Result<AppIDsResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/listAppIds.action");
+        let endpoint = self.http().endpoints().qh_url("/ios/listAppIds.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self
+            .qh_send_paged_request(&endpoint, body, "appIds")
+            .await?;
         let response_data: AppIDsResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -26,14 +27,14 @@ impl DeveloperSession {
         name: &String,
         identifier: &String,
     ) -> Result<AppIDResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/addAppId.action");
+        let endpoint = self.http().endpoints().qh_url("/ios/addAppId.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
         body.insert("name".to_string(), Value::String(strip_invalid_chars(name)));
         body.insert("identifier".to_string(), Value::String(identifier.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
         let response_data: AppIDResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -44,25 +45,43 @@ impl DeveloperSession {
         team_id: &String,
         app_id_id: &String,
     ) -> Result<QHResponseMeta, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/deleteAppId.action");
+        let endpoint = self.http().endpoints().qh_url("/ios/deleteAppId.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
         body.insert("appIdId".to_string(), Value::String(app_id_id.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
         let response_data: QHResponseMeta = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
     }
 
+    /// A single App ID, including the app groups assigned to it which listing leaves out.
+    pub async fn qh_get_app_id_detail(
+        &self,
+        team_id: &String,
+        app_id_id: &String,
+    ) -> Result<AppIDResponse, Error> {
+        let endpoint = self.http().endpoints().qh_url("/ios/getAppIdDetail.action");
+
+        let mut body = Dictionary::new();
+        body.insert("teamId".to_string(), Value::String(team_id.clone()));
+        body.insert("appIdId".to_string(), Value::String(app_id_id.clone()));
+
+        let response = self.qh_send_request(&endpoint, Some(body), true).await?;
+        let response_data: AppIDResponse = plist::from_value(&Value::Dictionary(response))?;
+
+        Ok(response_data)
+    }
+
     pub async fn qh_update_app_id(
         &self,
         team_id: &String,
         app_id_id: &String,
         features: Dictionary,
     ) -> Result<AppIDResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/updateAppId.action");
+        let endpoint = self.http().endpoints().qh_url("/ios/updateAppId.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
@@ -72,7 +91,7 @@ impl DeveloperSession {
             body.insert(key, value);
         }
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
         let response_data: AppIDResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -131,17 +150,19 @@ pub struct AppIDResponse {
 #[serde(rename_all = "camelCase")]
 pub struct AppID {
     pub app_id_id: String,
-    name: String,
+    pub name: String,
     app_id_platform: String,
     prefix: String,
     pub identifier: String,
-    is_wild_card: bool,
+    pub is_wild_card: bool,
     is_duplicate: bool,
     features: Features,
     enabled_features: Option<Vec<String>>,
     is_dev_push_enabled: bool,
     is_prod_push_enabled: bool,
-    associated_application_groups_count: Option<Integer>,
+    pub associated_application_groups_count: Option<Integer>,
+    /// Only filled in by [`DeveloperSession::qh_get_app_id_detail`].
+    pub associated_application_groups: Option<Vec<ApplicationGroup>>,
     associated_cloud_containers_count: Option<Integer>,
     associated_identifiers_count: Option<Integer>,
 }
@@ -5,16 +5,20 @@ use uuid::Uuid;
 use crate::Error;
 
 use super::{DeveloperSession, QHResponseMeta};
-use crate::developer_endpoint;
 
 impl DeveloperSession {
     pub async fn qh_list_certs(&self, team_id: &String) -> Result<CertsResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/listAllDevelopmentCerts.action");
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/listAllDevelopmentCerts.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self
+            .qh_send_paged_request(&endpoint, body, "certificates")
+            .await?;
         let response_data: CertsResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -25,7 +29,10 @@ impl DeveloperSession {
         team_id: &String,
         serial_number: &String,
     ) -> Result<QHResponseMeta, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/revokeDevelopmentCert.action");
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/revokeDevelopmentCert.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
@@ -34,7 +41,7 @@ impl DeveloperSession {
             Value::String(serial_number.clone()),
         );
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
         let response_data: QHResponseMeta = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -46,7 +53,10 @@ impl DeveloperSession {
         csr_data: String,
         machine_name: &String,
     ) -> Result<CsrResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/submitDevelopmentCSR.action");
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/submitDevelopmentCSR.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
@@ -60,7 +70,7 @@ impl DeveloperSession {
             Value::String(machine_name.clone()),
         );
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
         let response_data: CsrResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -4,16 +4,17 @@ use serde::Deserialize;
 use crate::Error;
 
 use super::{DeveloperSession, QHResponseMeta};
-use crate::developer_endpoint;
 
 impl DeveloperSession {
     pub async fn qh_list_devices(&self, team_id: &String) -> Result<DevicesResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/listDevices.action");
+        let endpoint = self.http().endpoints().qh_url("/ios/listDevices.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self
+            .qh_send_paged_request(&endpoint, body, "devices")
+            .await?;
         let response_data: DevicesResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -25,7 +26,7 @@ impl DeveloperSession {
         device_name: &String,
         device_udid: &String,
     ) -> Result<DeviceResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/addDevice.action");
+        let endpoint = self.http().endpoints().qh_url("/ios/addDevice.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
@@ -35,7 +36,7 @@ impl DeveloperSession {
             Value::String(device_udid.clone()),
         );
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), false).await?;
         let response_data: DeviceResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
@@ -6,10 +6,15 @@ pub mod devices;
 pub mod profile;
 pub mod teams;
 
-use crate::developer::DeveloperSession;
-use plist::Integer;
+use crate::{Error, developer::DeveloperSession};
+use plist::{Dictionary, Integer, Value};
 use serde::Deserialize;
 
+/// Records asked for per page by list endpoints.
+const QH_PAGE_SIZE: u64 = 500;
+/// Pages fetched at most, far more than any team has records.
+const QH_MAX_PAGES: u64 = 100;
+
 #[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -44,3 +49,110 @@ impl QHResponseMeta {
         }
     }
 }
+
+impl DeveloperSession {
+    /// Sends a list request page by page, returning the first page's response with the array
+    /// under `list_key` holding the records of every page.
+    ///
+    /// Stops as soon as a page comes back short of `pageSize`, `totalRecords` is reached or the
+    /// response doesn't echo `pageNumber`, which means the endpoint ignored paging. A page
+    /// identical to the one before it or [`QH_MAX_PAGES`] pages also stop it, in case the
+    /// endpoint keeps answering with full pages.
+    pub(crate) async fn qh_send_paged_request(
+        &self,
+        url: &str,
+        body: Dictionary,
+        list_key: &str,
+    ) -> Result<Dictionary, Error> {
+        let mut first_page: Option<Dictionary> = None;
+        let mut records = Vec::new();
+        let mut previous_page: Option<Vec<Value>> = None;
+        let mut page_number: u64 = 1;
+
+        loop {
+            let mut page_body = body.clone();
+            page_body.insert("pageNumber".into(), Value::Integer(page_number.into()));
+            page_body.insert("pageSize".into(), Value::Integer(QH_PAGE_SIZE.into()));
+
+            let mut response = self.qh_send_request(url, Some(page_body), true).await?;
+            let page = match response.remove(list_key) {
+                Some(Value::Array(page)) => page,
+                _ => Vec::new(),
+            };
+            let meta: QHResponseMeta = plist::from_value(&Value::Dictionary(response.clone()))?;
+
+            if previous_page.as_ref() == Some(&page) {
+                log::warn!("{url} returned page {page_number} twice, ignoring pageNumber");
+                break;
+            }
+
+            let received = page.len() as u64;
+            records.extend(page.iter().cloned());
+            first_page.get_or_insert(response);
+            previous_page = Some(page);
+
+            if !has_next_page(&meta, received, records.len() as u64) {
+                break;
+            }
+            if page_number >= QH_MAX_PAGES {
+                log::warn!("{url} still has records after {QH_MAX_PAGES} pages, stopping");
+                break;
+            }
+            page_number += 1;
+        }
+
+        let mut response = first_page.unwrap_or_default();
+        response.insert(list_key.to_string(), Value::Array(records));
+        Ok(response)
+    }
+}
+
+fn has_next_page(meta: &QHResponseMeta, received: u64, collected: u64) -> bool {
+    let page_size = meta
+        .page_size
+        .and_then(Integer::as_unsigned)
+        .unwrap_or(QH_PAGE_SIZE);
+    let total_records = meta.total_records.and_then(Integer::as_unsigned);
+
+    meta.page_number.is_some()
+        && received >= page_size
+        && total_records.is_none_or(|total| collected < total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(page_number: Option<u64>, total_records: Option<u64>) -> QHResponseMeta {
+        QHResponseMeta {
+            creation_timestamp: String::new(),
+            user_string: None,
+            result_string: None,
+            result_code: 0.into(),
+            http_code: None,
+            user_locale: String::new(),
+            protocol_version: String::new(),
+            request_id: None,
+            result_url: None,
+            response_id: String::new(),
+            page_number: page_number.map(Into::into),
+            page_size: None,
+            total_records: total_records.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn test_has_next_page() {
+        let size = QH_PAGE_SIZE;
+        assert!(has_next_page(&meta(Some(1), None), size, size));
+        assert!(has_next_page(&meta(Some(1), Some(size + 1)), size, size));
+        assert!(!has_next_page(
+            &meta(Some(2), Some(2 * size)),
+            size,
+            2 * size
+        ));
+        assert!(!has_next_page(&meta(Some(1), None), size - 1, size - 1));
+        // Paging ignored, the whole list came back at once
+        assert!(!has_next_page(&meta(None, None), size, size));
+    }
+}
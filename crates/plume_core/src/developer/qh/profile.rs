@@ -4,7 +4,6 @@ use serde::Deserialize;
 use crate::Error;
 
 use super::{DeveloperSession, QHResponseMeta};
-use crate::developer_endpoint;
 
 impl DeveloperSession {
     pub async fn qh_get_profile(
@@ -12,13 +11,16 @@ impl DeveloperSession {
         team_id: &String,
         app_id_id: &String,
     ) -> Result<ProfilesResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/ios/downloadTeamProvisioningProfile.action");
+        let endpoint = self
+            .http()
+            .endpoints()
+            .qh_url("/ios/downloadTeamProvisioningProfile.action");
 
         let mut body = Dictionary::new();
         body.insert("teamId".to_string(), Value::String(team_id.clone()));
         body.insert("appIdId".to_string(), Value::String(app_id_id.clone()));
 
-        let response = self.qh_send_request(&endpoint, Some(body)).await?;
+        let response = self.qh_send_request(&endpoint, Some(body), true).await?;
         let response_data: ProfilesResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
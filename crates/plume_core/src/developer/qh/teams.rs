@@ -4,19 +4,29 @@ use serde::Deserialize;
 use crate::Error;
 
 use super::{DeveloperSession, QHResponseMeta};
-use crate::developer_endpoint;
 
 impl DeveloperSession {
     pub async fn qh_list_teams(&self) -> Result<TeamsResponse, Error> {
-        let endpoint = developer_endpoint!("/QH65B2/listTeams.action");
+        let endpoint = self.http().endpoints().qh_url("/listTeams.action");
 
-        let response = self.qh_send_request(&endpoint, None).await?;
+        let response = self.qh_send_request(&endpoint, None, true).await?;
         let response_data: TeamsResponse = plist::from_value(&Value::Dictionary(response))?;
 
         Ok(response_data)
     }
 }
 
+/// Membership of a team, deciding what it may create and enable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeamType {
+    /// Apple ID without a paid membership, only usable from Xcode.
+    Free,
+    /// Apple Developer Program, individual or organization.
+    Paid,
+    /// Apple Developer Enterprise Program.
+    Enterprise,
+}
+
 #[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -43,6 +53,18 @@ pub struct Team {
     team_provisioning_settings: TeamProvisionSettings,
 }
 
+impl Team {
+    pub fn team_type(&self) -> TeamType {
+        if self.xcode_free_only {
+            TeamType::Free
+        } else if self._type == "In-House" {
+            TeamType::Enterprise
+        } else {
+            TeamType::Paid
+        }
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
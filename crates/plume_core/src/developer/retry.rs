@@ -0,0 +1,227 @@
+use std::{future::Future, io::ErrorKind, time::Duration};
+
+use crate::{Error, HttpResponse};
+
+/// The portal's catch-all "An unexpected error has occurred", it goes away on its own.
+const TRANSIENT_RESULT_CODES: &[i64] = &[9999];
+
+/// Rate limited, the server turned the request away without acting on it.
+const TOO_MANY_REQUESTS: u16 = 429;
+
+/// When developer API calls are tried again, see [`crate::developer::DeveloperSession::with_retry_policy`].
+///
+/// Requests are resent unchanged, including their `requestId`. Requests that create, change
+/// or delete something are only resent when they never reached the server or were rate
+/// limited, a timeout or 5xx may come after Apple already did the work.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first one, `0` disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every one after it.
+    pub initial_delay: Duration,
+    /// Upper bound of a single delay, `Retry-After` included.
+    pub max_delay: Duration,
+    /// HTTP statuses worth another try, whether they come from the transport or a `DeveloperApi` error.
+    pub retryable_statuses: Vec<u16>,
+    /// `DeveloperApi` result codes worth another try. Any other result code is the portal's
+    /// final answer (7460, too many certificates, is handled by the caller for instance).
+    pub retryable_result_codes: Vec<i64>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            retryable_statuses: vec![429, 500, 502, 503, 504],
+            retryable_result_codes: TRANSIENT_RESULT_CODES.to_vec(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Fails on the first error, like before retrying existed.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Whether `error` is worth another try. With `idempotent` unset only errors proving the
+    /// request was never acted on are, see [`RetryPolicy`].
+    pub fn is_retryable(&self, error: &Error, idempotent: bool) -> bool {
+        if !idempotent {
+            let rate_limited = |status: u16| {
+                status == TOO_MANY_REQUESTS && self.retryable_statuses.contains(&status)
+            };
+            return match error {
+                Error::HttpStatus { status, .. } => rate_limited(*status),
+                Error::DeveloperApi { http_code, .. } => http_code.is_some_and(rate_limited),
+                Error::Reqwest(e) => e.is_connect(),
+                Error::Io(e) => e.kind() == ErrorKind::ConnectionRefused,
+                _ => false,
+            };
+        }
+
+        match error {
+            Error::HttpStatus { status, .. } => self.retryable_statuses.contains(status),
+            Error::DeveloperApi {
+                result_code,
+                http_code,
+                ..
+            } => {
+                self.retryable_result_codes.contains(result_code)
+                    || http_code.is_some_and(|c| self.retryable_statuses.contains(&c))
+            }
+            Error::Reqwest(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            Error::Io(e) => matches!(
+                e.kind(),
+                ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::TimedOut
+                    | ErrorKind::UnexpectedEof
+                    | ErrorKind::Interrupted
+            ),
+            _ => false,
+        }
+    }
+
+    /// Delay before retry number `retry` (counting from zero), `Retry-After` wins when the
+    /// server sent one.
+    pub fn delay(&self, retry: u32, error: &Error) -> Duration {
+        if let Error::HttpStatus {
+            retry_after: Some(retry_after),
+            ..
+        } = error
+        {
+            return (*retry_after).min(self.max_delay);
+        }
+
+        let backoff = self
+            .initial_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        // Up to a quarter of jitter so parallel requests don't come back in lockstep
+        backoff.mul_f64(1.0 + rand::random::<f64>() * 0.25)
+    }
+
+    /// Runs `attempt` until it succeeds or fails for good, `idempotent` tells whether sending
+    /// the request twice is harmless.
+    pub(crate) async fn run<T, F, Fut>(
+        &self,
+        url: &str,
+        idempotent: bool,
+        mut attempt: F,
+    ) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Err(e) if retry < self.max_retries && self.is_retryable(&e, idempotent) => {
+                    let delay = self.delay(retry, &e);
+                    retry += 1;
+                    log::warn!(
+                        "Request to {url} failed ({e}), retry {retry}/{} in {delay:?}",
+                        self.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Turns a rate-limited or failing response into an [`Error::HttpStatus`].
+pub(crate) fn check_status(url: &str, response: &HttpResponse) -> Result<(), Error> {
+    if response.status != 429 && response.status < 500 {
+        return Ok(());
+    }
+
+    // Only the delay-seconds form, an HTTP date falls back to the backoff
+    let retry_after = response
+        .headers
+        .get("Retry-After")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .map(Duration::from_secs);
+
+    Err(Error::HttpStatus {
+        url: url.to_string(),
+        status: response.status,
+        retry_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(result_code: i64, http_code: Option<u16>) -> Error {
+        Error::DeveloperApi {
+            url: String::new(),
+            result_code,
+            http_code,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let policy = RetryPolicy::default();
+        let unavailable = Error::HttpStatus {
+            url: String::new(),
+            status: 503,
+            retry_after: None,
+        };
+        assert!(policy.is_retryable(&unavailable, true));
+        assert!(policy.is_retryable(&api_error(0, Some(429)), true));
+        assert!(policy.is_retryable(&api_error(9999, None), true));
+        assert!(!policy.is_retryable(&api_error(7460, None), true));
+        assert!(!policy.is_retryable(&Error::Parse, true));
+    }
+
+    #[test]
+    fn test_is_retryable_not_idempotent() {
+        let policy = RetryPolicy::default();
+        let unavailable = Error::HttpStatus {
+            url: String::new(),
+            status: 503,
+            retry_after: None,
+        };
+        assert!(!policy.is_retryable(&unavailable, false));
+        assert!(!policy.is_retryable(&api_error(9999, None), false));
+        assert!(!policy.is_retryable(&api_error(9999, Some(503)), false));
+
+        // Rate limited requests were never acted on
+        let rate_limited = Error::HttpStatus {
+            url: String::new(),
+            status: 429,
+            retry_after: Some(Duration::from_secs(5)),
+        };
+        assert!(policy.is_retryable(&rate_limited, false));
+        assert_eq!(policy.delay(0, &rate_limited), Duration::from_secs(5));
+        assert!(policy.is_retryable(&api_error(9999, Some(429)), false));
+        assert!(!policy.is_retryable(&Error::Io(ErrorKind::TimedOut.into()), false));
+        assert!(policy.is_retryable(&Error::Io(ErrorKind::ConnectionRefused.into()), false));
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::default();
+        let retry_after = Error::HttpStatus {
+            url: String::new(),
+            status: 429,
+            retry_after: Some(Duration::from_secs(120)),
+        };
+        assert_eq!(policy.delay(0, &retry_after), policy.max_delay);
+
+        let delay = policy.delay(2, &Error::Parse);
+        assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_millis(2500));
+    }
+}
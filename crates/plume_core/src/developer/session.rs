@@ -4,21 +4,23 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use plist::{Dictionary, Value};
-use reqwest::Client;
+use reqwest::Method;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderValue;
 use uuid::Uuid;
 
-use crate::Error;
+use crate::{Error, HttpClient, HttpRequest};
 
 use crate::auth::Account;
 use crate::auth::anisette_data::AnisetteData;
 use crate::developer::qh::QHResponseMeta;
+use crate::developer::retry::{RetryPolicy, check_status};
 use crate::developer::v1::V1ErrorResponse;
 
 pub struct DeveloperSession {
     anisette: Arc<Mutex<AnisetteData>>,
-    client: Client,
+    http: HttpClient,
+    retry_policy: RetryPolicy,
     adsid: String,          // from grandslam's SPD "adsid"
     xcode_gs_token: String, // requested from spd initially // com.apple.gs.xcode.auth
 }
@@ -40,7 +42,8 @@ impl DeveloperSession {
 
         Ok(DeveloperSession {
             anisette: account.anisette.clone(),
-            client: account.client.clone(),
+            http: account.http.clone(),
+            retry_policy: RetryPolicy::default(),
             adsid: adsid.into(),
             xcode_gs_token,
         })
@@ -60,11 +63,19 @@ impl DeveloperSession {
         xcode_gs_token: String,
         anisette: Arc<Mutex<AnisetteData>>,
     ) -> Result<Self, Error> {
-        let client = crate::client()?;
+        Self::new_with_http(adsid, xcode_gs_token, anisette, HttpClient::new()?).await
+    }
 
+    pub async fn new_with_http(
+        adsid: String,
+        xcode_gs_token: String,
+        anisette: Arc<Mutex<AnisetteData>>,
+        http: HttpClient,
+    ) -> Result<Self, Error> {
         let s = Self {
             anisette,
-            client,
+            http,
+            retry_policy: RetryPolicy::default(),
             adsid,
             xcode_gs_token,
         };
@@ -83,13 +94,29 @@ impl DeveloperSession {
     pub fn xcode_gs_token(&self) -> &String {
         &self.xcode_gs_token
     }
+
+    pub fn http(&self) -> &HttpClient {
+        &self.http
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
 }
 
 impl DeveloperSession {
+    /// Sends a QH request, `idempotent` when sending it twice is harmless (lists and lookups)
+    /// so failures past the connection are retried too.
     pub async fn qh_send_request(
         &self,
         url: &str,
         body: Option<Dictionary>,
+        idempotent: bool,
     ) -> Result<Dictionary, Error> {
         let mut headers = HeaderMap::new();
         headers.insert("Content-Type", HeaderValue::from_static("text/x-xml-plist"));
@@ -103,27 +130,35 @@ impl DeveloperSession {
             Value::String(Uuid::new_v4().to_string().to_uppercase()),
         );
 
-        let request_builder = self.client.post(url).headers(headers);
-
         let mut buffer = Vec::new();
         plist::to_writer_xml(&mut buffer, &body)?;
 
         log::debug!("QH Request to {}: {:?}", url, body);
 
-        let response = request_builder.body(buffer).send().await?;
-        let response_bytes = response.bytes().await?;
-        let response_dict: Dictionary = plist::from_bytes(&response_bytes)?;
+        let request = HttpRequest::new(Method::POST, url)
+            .headers(headers)
+            .body(buffer);
 
-        log::debug!("QH Response from {}: {:?}", url, response_dict);
+        let request = &request;
+        self.retry_policy
+            .run(url, idempotent, || async move {
+                let response = self.http.send(request.clone()).await?;
+                check_status(url, &response)?;
 
-        let response_meta: QHResponseMeta =
-            plist::from_value(&Value::Dictionary(response_dict.clone()))?;
+                let response_dict: Dictionary = plist::from_bytes(&response.body)?;
 
-        if response_meta.result_code.as_signed().unwrap_or(0) != 0 {
-            return Err(response_meta.to_error(url.to_string()));
-        }
+                log::debug!("QH Response from {}: {:?}", url, response_dict);
+
+                let response_meta: QHResponseMeta =
+                    plist::from_value(&Value::Dictionary(response_dict.clone()))?;
 
-        Ok(response_dict)
+                if response_meta.result_code.as_signed().unwrap_or(0) != 0 {
+                    return Err(response_meta.to_error(url.to_string()));
+                }
+
+                Ok(response_dict)
+            })
+            .await
     }
 
     pub async fn v1_send_request(
@@ -146,37 +181,58 @@ impl DeveloperSession {
             HeaderValue::from_static("XMLHttpRequest"),
         );
         self.insert_identity_headers(&mut headers).await;
-        if let Some(RequestType::Get) = request_type {
-            headers.insert("X-HTTP-Method-Override", HeaderValue::from_static("GET"));
+        // The team goes in the body, so reads and deletes are POSTs the server treats as such
+        match request_type {
+            Some(RequestType::Get) => {
+                headers.insert("X-HTTP-Method-Override", HeaderValue::from_static("GET"));
+            }
+            Some(RequestType::Delete) => {
+                headers.insert("X-HTTP-Method-Override", HeaderValue::from_static("DELETE"));
+            }
+            _ => {}
         }
         self.insert_anisette_headers(&mut headers).await;
 
-        let mut request_builder = match request_type {
-            Some(RequestType::Patch) => self.client.patch(url).headers(headers.clone()),
-            Some(RequestType::Post) | _ if body.is_some() => {
-                self.client.post(url).headers(headers.clone())
-            }
-            _ => self.client.get(url).headers(headers.clone()),
+        let method = match request_type {
+            Some(RequestType::Patch) => Method::PATCH,
+            _ if body.is_some() => Method::POST,
+            _ => Method::GET,
         };
 
         log::debug!("V1 Request to {}: {:?}", url, &body);
 
+        let mut request = HttpRequest::new(method, url).headers(headers);
         if let Some(body) = body {
-            request_builder = request_builder.json(&body);
+            request = request.json(&body)?;
         }
 
-        let response = request_builder.send().await?;
-        let response_text = response.text().await?;
+        // Only reads are safe to send twice
+        let idempotent = matches!(request_type, Some(RequestType::Get));
+        let request = &request;
+        self.retry_policy
+            .run(url, idempotent, || async move {
+                let response = self.http.send(request.clone()).await?;
+                check_status(url, &response)?;
 
-        log::debug!("V1 Response from {}: {}", url, response_text);
+                let response_text = response.text();
 
-        let response_json: serde_json::Value = serde_json::from_str(&response_text)?;
+                log::debug!("V1 Response from {}: {}", url, response_text);
 
-        if let Ok(errors) = serde_json::from_value::<V1ErrorResponse>(response_json.clone()) {
-            return Err(errors.errors[0].to_error(url.to_string()));
-        }
+                // Deletes answer 204 without a body
+                if response_text.trim().is_empty() {
+                    return Ok(serde_json::Value::Null);
+                }
+
+                let response_json: serde_json::Value = serde_json::from_str(&response_text)?;
+
+                if let Ok(errors) = serde_json::from_value::<V1ErrorResponse>(response_json.clone())
+                {
+                    return Err(errors.errors[0].to_error(url.to_string()));
+                }
 
-        Ok(response_json)
+                Ok(response_json)
+            })
+            .await
     }
 
     // TODO: this can be deduplicated as well, for reuse in `fn build_2fa_headers`
@@ -221,4 +277,5 @@ pub enum RequestType {
     Get,
     Post,
     Patch,
+    Delete,
 }
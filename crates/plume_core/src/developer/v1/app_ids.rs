@@ -2,22 +2,14 @@ use serde::Deserialize;
 use serde_json::{Value, json};
 
 use super::{DeveloperSession, RequestType};
-use crate::developer_endpoint;
 
 use crate::Error;
 
 impl DeveloperSession {
     pub async fn v1_list_app_ids(&self, team: &String) -> Result<AppIDsResponse, Error> {
-        let endpoint = developer_endpoint!("/v1/bundleIds");
+        let endpoint = self.http().endpoints().v1_url("/bundleIds");
 
-        let body = json!({
-            "teamId": team,
-            "urlEncodedQueryParams": "limit=1000"
-        });
-
-        let response = self
-            .v1_send_request(&endpoint, Some(body), Some(RequestType::Get))
-            .await?;
+        let response = self.v1_send_paged_request(&endpoint, team).await?;
         let response_data: AppIDsResponse = serde_json::from_value(response)?;
 
         Ok(response_data)
@@ -47,7 +39,10 @@ impl DeveloperSession {
         let response_data = self.v1_get_app_id(team, app_id).await?;
         let app_id = response_data.ok_or(Error::DeveloperSessionRequestFailed)?;
 
-        let endpoint = developer_endpoint!(&format!("/v1/bundleIds/{}", app_id.id));
+        let endpoint = self
+            .http()
+            .endpoints()
+            .v1_url(&format!("/bundleIds/{}", app_id.id));
 
         let bundle_id_capabilities: Vec<Value> = capabilities
             .into_iter()
@@ -3,12 +3,13 @@ use serde::Deserialize;
 use serde_json::json;
 
 use super::{DeveloperSession, RequestType};
-use crate::developer_endpoint;
 
 use crate::Error;
+use crate::developer::qh::teams::TeamType;
 use std::collections::HashSet;
 
-const FREE_DEVELOPER_ACCOUNT_UNALLOWED_CAPABILITIES: &[&str] = &[
+/// Capabilities the portal refuses to enable for personal teams.
+const FREE_TEAM_UNALLOWED_CAPABILITIES: &[&str] = &[
     "AUTOFILL_CREDENTIAL_PROVIDER",
     "APPLE_ID_AUTH",
     "NETWORK_SLICING",
@@ -20,9 +21,54 @@ const FREE_DEVELOPER_ACCOUNT_UNALLOWED_CAPABILITIES: &[&str] = &[
     "MDM_MANAGED_ASSOCIATED_DOMAINS",
 ];
 
+/// Managed capabilities, Apple has to grant these to a team on request before it can enable
+/// them, whatever its membership. The portal doesn't say which ones a team was granted, see
+/// [`CapabilityPolicy::with_granted`].
+const MANAGED_CAPABILITIES: &[&str] = &[
+    // 5G network slicing, requested through Apple for each app
+    "NETWORK_SLICING",
+    // Associated domains pushed by an MDM server, requested through Apple as well
+    "MDM_MANAGED_ASSOCIATED_DOMAINS",
+];
+
+/// Capabilities a team can't enable on its App IDs, picked from its [`TeamType`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityPolicy {
+    unallowed: Vec<String>,
+}
+
+impl CapabilityPolicy {
+    pub fn for_team_type(team_type: TeamType) -> Self {
+        let unallowed = match team_type {
+            TeamType::Free => FREE_TEAM_UNALLOWED_CAPABILITIES,
+            TeamType::Paid | TeamType::Enterprise => MANAGED_CAPABILITIES,
+        };
+
+        CapabilityPolicy {
+            unallowed: unallowed.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    /// Allows capabilities Apple granted the team, which [`Self::for_team_type`] can't know about.
+    pub fn with_granted<I, S>(mut self, granted: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for capability in granted {
+            self.unallowed.retain(|id| id != capability.as_ref());
+        }
+        self
+    }
+
+    pub fn allows(&self, capability: &str) -> bool {
+        !self.unallowed.iter().any(|id| id == capability)
+    }
+}
+
 impl DeveloperSession {
     pub async fn v1_list_capabilities(&self, team: &String) -> Result<CapabilitiesResponse, Error> {
-        let endpoint = developer_endpoint!("/v1/capabilities");
+        let endpoint = self.http().endpoints().v1_url("/capabilities");
 
         let body = json!({
             "teamId": team,
@@ -37,19 +83,23 @@ impl DeveloperSession {
         Ok(response_data)
     }
 
+    /// Enables the capabilities `entitlements` need that `policy` allows.
+    ///
+    /// Returns the IDs of the capabilities the entitlements need but `policy` doesn't allow,
+    /// whatever relies on them won't work in the signed app.
     pub async fn v1_request_capabilities_for_entitlements(
         &self,
         team: &String,
         id: &String,
         entitlements: &Dictionary,
-    ) -> Result<(), Error> {
+        policy: &CapabilityPolicy,
+    ) -> Result<Vec<String>, Error> {
         let capabilities = self.v1_list_capabilities(team).await?.data;
         let entitlement_keys: HashSet<&str> = entitlements.keys().map(|k| k.as_str()).collect();
 
-        // Collect capability IDs that match entitlement keys and are allowed for free accounts
-        let capabilities_to_enable: Vec<String> = capabilities
+        // Capability IDs that match entitlement keys, split by whether the team may enable them
+        let (capabilities_to_enable, skipped): (Vec<String>, Vec<String>) = capabilities
             .iter()
-            .filter(|cap| !FREE_DEVELOPER_ACCOUNT_UNALLOWED_CAPABILITIES.contains(&cap.id.as_str()))
             .filter_map(|cap| {
                 cap.attributes
                     .entitlements
@@ -58,12 +108,19 @@ impl DeveloperSession {
                     .find(|e| entitlement_keys.contains(e.profile_key.as_str()))
                     .map(|_| cap.id.clone())
             })
-            .collect();
+            .partition(|cap| policy.allows(cap));
 
-        self.v1_update_app_id(team, id, capabilities_to_enable.clone())
+        if !skipped.is_empty() {
+            log::warn!(
+                "Skipping capabilities not available to team {team} for {id}: {}",
+                skipped.join(", ")
+            );
+        }
+
+        self.v1_update_app_id(team, id, capabilities_to_enable)
             .await?;
 
-        Ok(())
+        Ok(skipped)
     }
 }
 
@@ -96,3 +153,29 @@ pub struct CapabilityAttributes {
 pub struct CapabilityEntitlement {
     pub profile_key: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_for_team_type() {
+        assert!(!CapabilityPolicy::for_team_type(TeamType::Free).allows("ICLOUD"));
+        assert!(CapabilityPolicy::for_team_type(TeamType::Paid).allows("ICLOUD"));
+        assert!(CapabilityPolicy::for_team_type(TeamType::Paid).allows("IN_APP_PURCHASE"));
+        assert!(!CapabilityPolicy::for_team_type(TeamType::Paid).allows("NETWORK_SLICING"));
+        assert!(CapabilityPolicy::for_team_type(TeamType::Enterprise).allows("APPLE_ID_AUTH"));
+        assert!(
+            !CapabilityPolicy::for_team_type(TeamType::Enterprise)
+                .allows("MDM_MANAGED_ASSOCIATED_DOMAINS")
+        );
+    }
+
+    #[test]
+    fn test_policy_with_granted() {
+        let policy =
+            CapabilityPolicy::for_team_type(TeamType::Paid).with_granted(["NETWORK_SLICING"]);
+        assert!(policy.allows("NETWORK_SLICING"));
+        assert!(!policy.allows("MDM_MANAGED_ASSOCIATED_DOMAINS"));
+    }
+}
@@ -0,0 +1,109 @@
+use base64::{Engine, engine::general_purpose};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{DeveloperSession, RequestType};
+
+use crate::Error;
+
+impl DeveloperSession {
+    pub async fn v1_list_certificates(&self, team: &String) -> Result<CertificatesResponse, Error> {
+        let endpoint = self.http().endpoints().v1_url("/certificates");
+
+        let response = self.v1_send_paged_request(&endpoint, team).await?;
+        let response_data: CertificatesResponse = serde_json::from_value(response)?;
+
+        Ok(response_data)
+    }
+
+    pub async fn v1_get_certificate(
+        &self,
+        team: &String,
+        certificate_id: &String,
+    ) -> Result<CertificateResponse, Error> {
+        let endpoint = self
+            .http()
+            .endpoints()
+            .v1_url(&format!("/certificates/{certificate_id}"));
+
+        let body = json!({ "teamId": team });
+
+        let response = self
+            .v1_send_request(&endpoint, Some(body), Some(RequestType::Get))
+            .await?;
+        let response_data: CertificateResponse = serde_json::from_value(response)?;
+
+        Ok(response_data)
+    }
+
+    /// The DER encoded certificate, without its private key.
+    pub async fn v1_download_certificate(
+        &self,
+        team: &String,
+        certificate_id: &String,
+    ) -> Result<Vec<u8>, Error> {
+        let certificate = self.v1_get_certificate(team, certificate_id).await?.data;
+        let content = certificate
+            .attributes
+            .certificate_content
+            .ok_or(Error::DeveloperSessionRequestFailed)?;
+
+        general_purpose::STANDARD
+            .decode(content)
+            .map_err(|_| Error::Parse)
+    }
+
+    pub async fn v1_revoke_certificate(
+        &self,
+        team: &String,
+        certificate_id: &String,
+    ) -> Result<(), Error> {
+        let endpoint = self
+            .http()
+            .endpoints()
+            .v1_url(&format!("/certificates/{certificate_id}"));
+
+        let body = json!({ "teamId": team });
+
+        self.v1_send_request(&endpoint, Some(body), Some(RequestType::Delete))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificatesResponse {
+    pub data: Vec<Certificate>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateResponse {
+    pub data: Certificate,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Certificate {
+    pub id: String,
+    pub attributes: CertificateAttributes,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateAttributes {
+    pub name: Option<String>,
+    pub display_name: Option<String>,
+    pub certificate_type: Option<String>,
+    pub serial_number: String,
+    pub platform: Option<String>,
+    pub expiration_date: Option<String>,
+    /// Base64 encoded DER, see [`DeveloperSession::v1_download_certificate`].
+    pub certificate_content: Option<String>,
+}
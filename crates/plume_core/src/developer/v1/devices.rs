@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use super::{DeveloperSession, RequestType};
+
+use crate::Error;
+
+impl DeveloperSession {
+    pub async fn v1_list_devices(&self, team: &String) -> Result<DevicesResponse, Error> {
+        let endpoint = self.http().endpoints().v1_url("/devices");
+
+        let response = self.v1_send_paged_request(&endpoint, team).await?;
+        let response_data: DevicesResponse = serde_json::from_value(response)?;
+
+        Ok(response_data)
+    }
+
+    pub async fn v1_enable_device(
+        &self,
+        team: &String,
+        device_id: &String,
+    ) -> Result<DeviceResponse, Error> {
+        self.v1_update_device(team, device_id, None, Some(DeviceStatus::Enabled))
+            .await
+    }
+
+    pub async fn v1_disable_device(
+        &self,
+        team: &String,
+        device_id: &String,
+    ) -> Result<DeviceResponse, Error> {
+        self.v1_update_device(team, device_id, None, Some(DeviceStatus::Disabled))
+            .await
+    }
+
+    pub async fn v1_rename_device(
+        &self,
+        team: &String,
+        device_id: &String,
+        name: &String,
+    ) -> Result<DeviceResponse, Error> {
+        self.v1_update_device(team, device_id, Some(name), None)
+            .await
+    }
+
+    async fn v1_update_device(
+        &self,
+        team: &String,
+        device_id: &String,
+        name: Option<&String>,
+        status: Option<DeviceStatus>,
+    ) -> Result<DeviceResponse, Error> {
+        let endpoint = self
+            .http()
+            .endpoints()
+            .v1_url(&format!("/devices/{device_id}"));
+
+        let mut attributes = Map::new();
+        if let Some(name) = name {
+            attributes.insert("name".into(), Value::String(name.clone()));
+        }
+        if let Some(status) = status {
+            attributes.insert("status".into(), serde_json::to_value(status)?);
+        }
+
+        let payload = json!({
+            "teamId": team,
+            "data": {
+                "type": "devices",
+                "id": device_id,
+                "attributes": attributes
+            }
+        });
+
+        let response = self
+            .v1_send_request(&endpoint, Some(payload), Some(RequestType::Patch))
+            .await?;
+        let response_data: DeviceResponse = serde_json::from_value(response)?;
+
+        Ok(response_data)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DevicesResponse {
+    pub data: Vec<Device>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceResponse {
+    pub data: Device,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Device {
+    pub id: String,
+    pub attributes: DeviceAttributes,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceAttributes {
+    pub name: String,
+    pub udid: String,
+    pub platform: Option<String>,
+    pub device_class: Option<String>,
+    pub model: Option<String>,
+    pub status: DeviceStatus,
+    pub added_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeviceStatus {
+    Enabled,
+    Disabled,
+    /// Anything this client doesn't know about yet.
+    #[serde(other)]
+    Unknown,
+}
@@ -1,10 +1,22 @@
 pub mod app_ids;
 pub mod capabilities;
+pub mod certificates;
+pub mod devices;
+pub mod profiles;
+
+use std::collections::HashSet;
 
 use serde::Deserialize;
+use serde_json::{Value, json};
 
+use crate::Error;
 use crate::developer::{DeveloperSession, RequestType};
 
+/// Records asked for per page by list endpoints, the most the API returns at once.
+const V1_PAGE_LIMIT: u64 = 1000;
+/// Pages fetched at most, far more than any team has records.
+const V1_MAX_PAGES: u64 = 100;
+
 #[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -40,3 +52,86 @@ impl V1ErrorDetail {
         }
     }
 }
+
+impl DeveloperSession {
+    /// Lists the records of `team` at `url` page by page, returning the first page's response
+    /// with `data` holding the records of every page.
+    ///
+    /// Follows `links.next` until a page has none, its query (the page cursor) goes out through
+    /// `urlEncodedQueryParams` like the first page's. A `next` link seen before or
+    /// [`V1_MAX_PAGES`] pages also stop it.
+    pub(crate) async fn v1_send_paged_request(
+        &self,
+        url: &str,
+        team: &String,
+    ) -> Result<Value, Error> {
+        let mut first_page: Option<Value> = None;
+        let mut records = Vec::new();
+        let mut seen_queries = HashSet::new();
+        let mut query = format!("limit={V1_PAGE_LIMIT}");
+
+        for page_number in 1.. {
+            let body = json!({
+                "teamId": team,
+                "urlEncodedQueryParams": query
+            });
+
+            let mut response = self
+                .v1_send_request(url, Some(body), Some(RequestType::Get))
+                .await?;
+            if let Some(Value::Array(page)) = response.get_mut("data").map(Value::take) {
+                records.extend(page);
+            }
+            let next = next_page_query(&response);
+            first_page.get_or_insert(response);
+
+            let Some(next) = next else {
+                break;
+            };
+            if !seen_queries.insert(next.clone()) {
+                log::warn!("{url} links to page {next} again, stopping");
+                break;
+            }
+            if page_number >= V1_MAX_PAGES {
+                log::warn!("{url} still has records after {V1_MAX_PAGES} pages, stopping");
+                break;
+            }
+            query = next;
+        }
+
+        let mut response = first_page.unwrap_or_else(|| json!({}));
+        response["data"] = Value::Array(records);
+        Ok(response)
+    }
+}
+
+/// Query of the response's `links.next`, e.g. `cursor=Mg&limit=1000`.
+fn next_page_query(response: &Value) -> Option<String> {
+    let next = response.get("links")?.get("next")?.as_str()?;
+    let (_, query) = next.split_once('?')?;
+    (!query.is_empty()).then(|| query.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_page_query() {
+        let page = json!({
+            "data": [],
+            "links": {
+                "self": "https://api.example.com/v1/devices?limit=1000",
+                "next": "https://api.example.com/v1/devices?cursor=Mg&limit=1000"
+            }
+        });
+        assert_eq!(
+            next_page_query(&page).as_deref(),
+            Some("cursor=Mg&limit=1000")
+        );
+
+        let last_page = json!({ "data": [], "links": { "self": "https://api.example.com" } });
+        assert_eq!(next_page_query(&last_page), None);
+        assert_eq!(next_page_query(&json!({ "data": [] })), None);
+    }
+}
@@ -0,0 +1,106 @@
+use base64::{Engine, engine::general_purpose};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{DeveloperSession, RequestType};
+
+use crate::Error;
+
+impl DeveloperSession {
+    pub async fn v1_list_profiles(&self, team: &String) -> Result<ProfilesResponse, Error> {
+        let endpoint = self.http().endpoints().v1_url("/profiles");
+
+        let response = self.v1_send_paged_request(&endpoint, team).await?;
+        let response_data: ProfilesResponse = serde_json::from_value(response)?;
+
+        Ok(response_data)
+    }
+
+    pub async fn v1_get_profile(
+        &self,
+        team: &String,
+        profile_id: &String,
+    ) -> Result<ProfileResponse, Error> {
+        let endpoint = self
+            .http()
+            .endpoints()
+            .v1_url(&format!("/profiles/{profile_id}"));
+
+        let body = json!({ "teamId": team });
+
+        let response = self
+            .v1_send_request(&endpoint, Some(body), Some(RequestType::Get))
+            .await?;
+        let response_data: ProfileResponse = serde_json::from_value(response)?;
+
+        Ok(response_data)
+    }
+
+    /// The `.mobileprovision` contents of a profile.
+    pub async fn v1_download_profile(
+        &self,
+        team: &String,
+        profile_id: &String,
+    ) -> Result<Vec<u8>, Error> {
+        let profile = self.v1_get_profile(team, profile_id).await?.data;
+        let content = profile
+            .attributes
+            .profile_content
+            .ok_or(Error::DeveloperSessionRequestFailed)?;
+
+        general_purpose::STANDARD
+            .decode(content)
+            .map_err(|_| Error::Parse)
+    }
+
+    pub async fn v1_delete_profile(&self, team: &String, profile_id: &String) -> Result<(), Error> {
+        let endpoint = self
+            .http()
+            .endpoints()
+            .v1_url(&format!("/profiles/{profile_id}"));
+
+        let body = json!({ "teamId": team });
+
+        self.v1_send_request(&endpoint, Some(body), Some(RequestType::Delete))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesResponse {
+    pub data: Vec<Profile>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileResponse {
+    pub data: Profile,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub attributes: ProfileAttributes,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileAttributes {
+    pub name: String,
+    pub platform: Option<String>,
+    pub profile_type: Option<String>,
+    pub profile_state: Option<String>,
+    pub uuid: Option<String>,
+    pub created_date: Option<String>,
+    pub expiration_date: Option<String>,
+    /// Base64 encoded `.mobileprovision`, see [`DeveloperSession::v1_download_profile`].
+    pub profile_content: Option<String>,
+}
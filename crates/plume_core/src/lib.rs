@@ -1,12 +1,29 @@
 pub mod auth;
 pub mod developer;
+mod network;
+mod transport;
 mod utils;
 
 pub use apple_codesign::{AppleCodesignError, SettingsScope, SigningSettings, UnifiedSigner};
 
 pub use omnisette::AnisetteConfiguration;
 
-pub use utils::{CertificateIdentity, MachO, MachOExt, MobileProvision};
+pub use reqwest::{
+    Method,
+    header::{HeaderMap, HeaderValue},
+};
+
+pub use network::{NetworkConfig, network_config, set_network_config};
+
+pub use transport::{
+    Endpoints, HttpClient, HttpRequest, HttpResponse, ReqwestTransport, Transport, TransportFuture,
+};
+
+pub use utils::{
+    BuildVersionInfo, CertificateIdentity, CodeDirectoryInfo, CodeSignatureInfo, EncryptionInfo,
+    LoadCommandField, LoadCommandInfo, MachO, MachOExt, MachOInfo, MobileProvision, SectionInfo,
+    SegmentInfo,
+};
 
 use thiserror::Error as ThisError;
 #[derive(Debug, ThisError)]
@@ -26,6 +43,12 @@ pub enum Error {
         http_code: Option<u16>,
         message: String,
     },
+    #[error("HTTP {status} from {url}")]
+    HttpStatus {
+        url: String,
+        status: u16,
+        retry_after: Option<std::time::Duration>,
+    },
     #[error("Request to developer session failed")]
     DeveloperSessionRequestFailed,
     #[error("Authentication SRP error {0}: {1}")]
@@ -36,6 +59,8 @@ pub enum Error {
     Bad2faCode,
     #[error("Failed to parse")]
     Parse, // TODO: better parsing errors
+    #[error("Not enough free space after the Mach-O load commands")]
+    MachOHeaderSpace,
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Plist error: {0}")]
@@ -50,6 +75,8 @@ pub enum Error {
     X509(#[from] x509_certificate::X509CertificateError),
     #[error("Reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
+    #[error("Invalid network configuration: {0}")]
+    InvalidNetworkConfig(String),
     #[error("Anisette error: {0}")]
     Anisette(#[from] omnisette::AnisetteError),
     #[error("Serde JSON error: {0}")]
@@ -64,15 +91,17 @@ pub enum Error {
     RcGen(#[from] rcgen::RcgenError),
 }
 
+/// Client trusting Apple's root CA, configured with [`network_config`].
 pub fn client() -> Result<reqwest::Client, Error> {
+    client_with_config(&network_config())
+}
+
+pub fn client_with_config(config: &NetworkConfig) -> Result<reqwest::Client, Error> {
     const APPLE_ROOT: &[u8] = include_bytes!("./apple_root.der");
-    let client = reqwest::ClientBuilder::new()
+    let builder = reqwest::ClientBuilder::new()
         .add_root_certificate(reqwest::Certificate::from_der(APPLE_ROOT)?)
-        // uncomment when debugging w/ charles proxy
-        // .danger_accept_invalid_certs(true)
         .http1_title_case_headers()
-        .connection_verbose(true)
-        .build()?;
+        .connection_verbose(true);
 
-    Ok(client)
+    Ok(config.apply(builder)?.build()?)
 }
@@ -0,0 +1,156 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+static NETWORK_CONFIG: RwLock<Option<NetworkConfig>> = RwLock::new(None);
+
+/// Proxy, trust and timeout settings of the client talking to Apple.
+///
+/// Anything left unset falls back to the `PLUME_*` environment variables, see
+/// [`NetworkConfig::with_env_defaults`].
+///
+/// Anisette data is fetched by `omnisette` with its own client, none of these settings reach
+/// it, so logging in still needs a direct connection to the anisette server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` proxy every request goes through.
+    pub proxy: Option<String>,
+    pub proxy_username: Option<String>,
+    pub proxy_password: Option<String>,
+    /// PEM or DER certificates trusted alongside Apple's root, e.g. an intercepting proxy's.
+    pub ca_certificates: Vec<PathBuf>,
+    /// Seconds to wait for a connection.
+    pub connect_timeout: Option<u64>,
+    /// Seconds a whole request may take.
+    pub timeout: Option<u64>,
+    /// Skips certificate validation, only for inspecting traffic while debugging.
+    pub accept_invalid_certs: bool,
+}
+
+impl NetworkConfig {
+    /// Reads `PLUME_PROXY`, `PLUME_PROXY_USERNAME`, `PLUME_PROXY_PASSWORD`, `PLUME_CA_CERTS`
+    /// (a path list), `PLUME_CONNECT_TIMEOUT`, `PLUME_TIMEOUT` and `PLUME_ACCEPT_INVALID_CERTS`.
+    pub fn from_env() -> Self {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let seconds = |name: &str| {
+            var(name).and_then(|v| match v.parse() {
+                Ok(seconds) => Some(seconds),
+                Err(_) => {
+                    log::warn!("Ignoring {name}={v}, expected a number of seconds");
+                    None
+                }
+            })
+        };
+
+        NetworkConfig {
+            proxy: var("PLUME_PROXY"),
+            proxy_username: var("PLUME_PROXY_USERNAME"),
+            proxy_password: var("PLUME_PROXY_PASSWORD"),
+            ca_certificates: env::var_os("PLUME_CA_CERTS")
+                .map(|paths| env::split_paths(&paths).collect())
+                .unwrap_or_default(),
+            connect_timeout: seconds("PLUME_CONNECT_TIMEOUT"),
+            timeout: seconds("PLUME_TIMEOUT"),
+            accept_invalid_certs: var("PLUME_ACCEPT_INVALID_CERTS")
+                .is_some_and(|v| matches!(v.as_str(), "1" | "true" | "yes")),
+        }
+    }
+
+    /// Fills whatever is unset from the environment, so explicit settings win over it.
+    pub fn with_env_defaults(mut self) -> Self {
+        let env = Self::from_env();
+
+        self.proxy = self.proxy.or(env.proxy);
+        self.proxy_username = self.proxy_username.or(env.proxy_username);
+        self.proxy_password = self.proxy_password.or(env.proxy_password);
+        if self.ca_certificates.is_empty() {
+            self.ca_certificates = env.ca_certificates;
+        }
+        self.connect_timeout = self.connect_timeout.or(env.connect_timeout);
+        self.timeout = self.timeout.or(env.timeout);
+        self.accept_invalid_certs |= env.accept_invalid_certs;
+        self
+    }
+
+    pub(crate) fn apply(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder, Error> {
+        if let Some(url) = &self.proxy {
+            let mut proxy = reqwest::Proxy::all(url)
+                .map_err(|e| Error::InvalidNetworkConfig(format!("proxy {url}: {e}")))?;
+            if let Some(username) = &self.proxy_username {
+                proxy = proxy.basic_auth(username, self.proxy_password.as_deref().unwrap_or(""));
+            }
+            builder = builder.proxy(proxy);
+        }
+
+        for path in &self.ca_certificates {
+            for certificate in read_certificates(path)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(seconds) = self.connect_timeout {
+            builder = builder.connect_timeout(Duration::from_secs(seconds));
+        }
+        if let Some(seconds) = self.timeout {
+            builder = builder.timeout(Duration::from_secs(seconds));
+        }
+
+        if self.accept_invalid_certs {
+            log::warn!("Certificate validation is disabled, only use this for debugging");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Replaces the settings clients are built with from now on.
+pub fn set_network_config(config: NetworkConfig) {
+    *NETWORK_CONFIG.write().unwrap() = Some(config);
+}
+
+/// The settings passed to [`set_network_config`], or the environment's.
+pub fn network_config() -> NetworkConfig {
+    NETWORK_CONFIG
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(NetworkConfig::from_env)
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<reqwest::Certificate>, Error> {
+    let bytes = std::fs::read(path)?;
+    let invalid = |e: &dyn std::fmt::Display| {
+        Error::InvalidNetworkConfig(format!("certificate {}: {e}", path.display()))
+    };
+
+    if !bytes.windows(10).any(|w| w == b"-----BEGIN") {
+        return Ok(vec![
+            reqwest::Certificate::from_der(&bytes).map_err(|e| invalid(&e))?,
+        ]);
+    }
+
+    let certificates = pem::parse_many(&bytes)
+        .map_err(|e| invalid(&e))?
+        .into_iter()
+        .filter(|p| p.tag() == "CERTIFICATE")
+        .map(|p| reqwest::Certificate::from_der(p.contents()).map_err(|e| invalid(&e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if certificates.is_empty() {
+        return Err(invalid(&"no certificates found"));
+    }
+
+    Ok(certificates)
+}
@@ -0,0 +1,209 @@
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+
+use reqwest::{
+    Method,
+    header::{CONTENT_TYPE, HeaderMap, HeaderValue},
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{Error, NetworkConfig};
+
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, Error>> + Send + 'a>>;
+
+/// Sends the requests of [`crate::auth::Account`] and [`crate::developer::DeveloperSession`].
+///
+/// Swap it out to route traffic elsewhere, stub it in tests or record and replay it.
+pub trait Transport: fmt::Debug + Send + Sync {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+}
+
+impl HttpRequest {
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    pub fn header(mut self, name: &'static str, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Serializes `value` as the body, defaulting the content type to `application/json`.
+    pub fn json<T: Serialize + ?Sized>(mut self, value: &T) -> Result<Self, Error> {
+        self.body = Some(serde_json::to_vec(value)?);
+        self.headers
+            .entry(CONTENT_TYPE)
+            .or_insert(HeaderValue::from_static("application/json"));
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+/// Default transport, a `reqwest` client trusting Apple's root CA.
+///
+/// [`ReqwestTransport::new`] picks up the process-wide [`crate::network_config`].
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self::with_client(crate::client()?))
+    }
+
+    pub fn with_config(config: &NetworkConfig) -> Result<Self, Error> {
+        Ok(Self::with_client(crate::client_with_config(config)?))
+    }
+
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, &request.url)
+                .headers(request.headers);
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().await?;
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
+            let body = response.bytes().await?.to_vec();
+
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+/// Base URLs of the services, without a trailing slash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// GrandSlam authentication, login and two-factor requests.
+    pub grandslam: String,
+    /// Xcode's plist developer API.
+    pub qh: String,
+    /// JSON developer API.
+    pub v1: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            grandslam: "https://gsa.apple.com".to_string(),
+            qh: "https://developerservices2.apple.com/services/QH65B2".to_string(),
+            v1: "https://developerservices2.apple.com/services/v1".to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// Every service under one base URL, e.g. a local stand-in server.
+    pub fn with_base_url(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Endpoints {
+            grandslam: base_url.to_string(),
+            qh: format!("{base_url}/services/QH65B2"),
+            v1: format!("{base_url}/services/v1"),
+        }
+    }
+
+    pub fn grandslam_url(&self, path: &str) -> String {
+        format!("{}{}", self.grandslam, path)
+    }
+
+    pub fn qh_url(&self, path: &str) -> String {
+        format!("{}{}", self.qh, path)
+    }
+
+    pub fn v1_url(&self, path: &str) -> String {
+        format!("{}{}", self.v1, path)
+    }
+}
+
+/// Transport and endpoints, shared by an account and the sessions made from it.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    transport: Arc<dyn Transport>,
+    endpoints: Endpoints,
+}
+
+impl HttpClient {
+    /// [`ReqwestTransport`] against Apple's servers.
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self::with_transport(Arc::new(ReqwestTransport::new()?)))
+    }
+
+    pub fn with_transport(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            endpoints: Endpoints::default(),
+        }
+    }
+
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    pub async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        self.transport.send(request).await
+    }
+}
@@ -28,6 +28,8 @@ pub struct CertificateIdentity {
     pub machine_id: Option<String>,
     pub serial_number: Option<String>,
     pub p12_data: Option<Vec<u8>>,
+    /// Whether [`Self::new_with_session`] had to submit a new certificate instead of reusing one.
+    pub created: bool,
 }
 
 impl CertificateIdentity {
@@ -39,6 +41,7 @@ impl CertificateIdentity {
             machine_id: None,
             p12_data: None,
             serial_number: None,
+            created: false,
         };
 
         if let Some(paths) = paths {
@@ -67,6 +70,7 @@ impl CertificateIdentity {
             machine_id: None,
             p12_data: None,
             serial_number: None,
+            created: false,
         };
 
         // To same some unnecessary requests, we're going to list our certificates first here
@@ -303,6 +307,7 @@ impl CertificateIdentity {
         }
 
         self.set_serial_number(cert_id.serial_num.clone());
+        self.created = true;
 
         // We request again, and hope this has our new certificate
         // ready.... if not then woops... thats too bad isnt it
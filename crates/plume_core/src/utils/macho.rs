@@ -5,40 +5,58 @@ use apple_codesign::{MachFile, MachOBinary, UniversalBinaryBuilder};
 use goblin::mach::{
     MachO as GoblinMachO,
     cputype::CPU_TYPE_ARM64,
+    fat::FAT_MAGIC,
     load_command::{
-        CommandVariant, LC_LAZY_LOAD_DYLIB, LC_LOAD_DYLIB, LC_LOAD_UPWARD_DYLIB,
+        CommandVariant, LC_ID_DYLIB, LC_LAZY_LOAD_DYLIB, LC_LOAD_DYLIB, LC_LOAD_UPWARD_DYLIB,
         LC_LOAD_WEAK_DYLIB, LC_REEXPORT_DYLIB,
     },
 };
 use plist::{Dictionary, Value};
+use serde::Serialize;
 
+use super::macho_info::{self, MachOInfo};
 use crate::Error;
 
+const DYLIB_COMMANDS: &[u32] = &[
+    LC_LOAD_DYLIB,
+    LC_LOAD_WEAK_DYLIB,
+    LC_REEXPORT_DYLIB,
+    LC_LAZY_LOAD_DYLIB,
+    LC_LOAD_UPWARD_DYLIB,
+];
+
+/// Universal binary with 64-bit slice offsets, goblin only knows the 32-bit `FAT_MAGIC`.
+const FAT_MAGIC_64: u32 = 0xcafe_babf;
+
 /// Represents a Mach-O file and its entitlements.
+///
+/// The file contents are owned by this struct, every edit rebuilds the buffer
+/// and re-parses it, so nothing outlives the `MachO` itself.
 pub struct MachO {
-    #[allow(dead_code)]
-    macho_file: MachFile<'static>,
+    data: Vec<u8>,
     path: std::path::PathBuf,
     entitlements: Option<Dictionary>,
 }
 
 impl MachO {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let macho_data = fs::read(&path)?;
-        // Leak the data for 'static lifetime required by MachFile.
-        let macho_data = Box::leak(macho_data.into_boxed_slice());
-        let macho_file = MachFile::parse(macho_data)?; // macho_file.data is the full file data
-        let entitlements = Self::extract_entitlements(&macho_file)?;
+        let data = fs::read(&path)?;
+        let entitlements = Self::extract_entitlements(&MachFile::parse(&data)?)?;
 
         Ok(MachO {
-            macho_file,
+            data,
             path: path.as_ref().to_path_buf(),
             entitlements,
         })
     }
 
-    pub fn macho_file(&self) -> &MachFile<'_> {
-        &self.macho_file
+    /// Parses the owned buffer, the returned file borrows from `self`.
+    pub fn macho_file(&self) -> Result<MachFile<'_>, Error> {
+        Ok(MachFile::parse(&self.data)?)
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
     }
 
     pub fn entitlements(&self) -> &Option<Dictionary> {
@@ -49,6 +67,51 @@ impl MachO {
         macho_file.nth_macho(0)?.embedded_entitlements()
     }
 
+    /// Returns true if any slice still carries a non-zero `cryptid` (FairPlay encrypted).
+    pub fn is_encrypted(&self) -> Result<bool, Error> {
+        Self::is_encrypted_bytes(&self.data)
+    }
+
+    /// Same as [`MachO::is_encrypted`], but for raw file contents that were never written to disk.
+    pub fn is_encrypted_bytes(data: &[u8]) -> Result<bool, Error> {
+        let macho_file = MachFile::parse(data)?;
+        Ok(macho_file.iter_macho().any(|macho| {
+            macho
+                .encryption_info()
+                .is_some_and(|info| info.cryptid != 0)
+        }))
+    }
+
+    /// Dylib load paths of the first slice.
+    pub fn dylib_load_paths(&self) -> Result<Vec<String>, Error> {
+        Self::dylib_load_paths_bytes(&self.data)
+    }
+
+    /// Same as [`MachO::dylib_load_paths`], but for raw file contents.
+    pub fn dylib_load_paths_bytes(data: &[u8]) -> Result<Vec<String>, Error> {
+        MachFile::parse(data)?.nth_macho(0)?.dylib_load_paths()
+    }
+
+    /// Weakly linked dylib load paths of the first slice, a missing one doesn't stop the load.
+    pub fn weak_dylib_load_paths(&self) -> Result<Vec<String>, Error> {
+        MachFile::parse(&self.data)?
+            .nth_macho(0)?
+            .weak_dylib_load_paths()
+    }
+
+    /// Install name from `LC_ID_DYLIB` of the first slice, `None` for executables.
+    pub fn id_dylib(&self) -> Result<Option<String>, Error> {
+        Ok(MachFile::parse(&self.data)?.nth_macho(0)?.id_dylib())
+    }
+
+    /// Decoded header, load commands and signature of every slice.
+    pub fn info(&self) -> Result<Vec<MachOInfo>, Error> {
+        self.macho_file()?
+            .iter_macho()
+            .map(|macho| macho.info())
+            .collect()
+    }
+
     pub fn app_groups_for_entitlements(&self) -> Option<Vec<String>> {
         self.entitlements
             .as_ref()
@@ -60,64 +123,97 @@ impl MachO {
             })
     }
 
-    // TODO: why is this here again
     pub fn write_changes(&self) -> Result<(), Error> {
-        let mut builder = UniversalBinaryBuilder::default();
-        for binary in self.macho_file.iter_macho() {
-            let _ = builder.add_binary(binary.data);
-        }
+        fs::write(&self.path, &self.data)?;
+        Ok(())
+    }
 
-        let writer = &mut fs::File::create(self.path.clone()).map_err(Error::from)?;
-        builder.write(writer)?;
+    /// Runs `edit` on every slice, reassembles the file from the edited slices and writes it back.
+    ///
+    /// A thin binary is written back thin, a universal one stays universal.
+    fn edit_slices<F>(&mut self, edit: F) -> Result<(), Error>
+    where
+        F: Fn(&MachOBinary<'_>) -> Result<Vec<u8>, Error>,
+    {
+        let mut slices = MachFile::parse(&self.data)?
+            .iter_macho()
+            .map(&edit)
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let data = if is_universal(&self.data) {
+            let mut builder = UniversalBinaryBuilder::default();
+            for slice in &slices {
+                builder.add_binary(slice.as_slice())?;
+            }
 
-        Ok(())
+            let mut data = Vec::new();
+            builder.write(&mut data)?;
+            data
+        } else {
+            slices.pop().ok_or(Error::Parse)?
+        };
+
+        self.entitlements = Self::extract_entitlements(&MachFile::parse(&data)?)?;
+        self.data = data;
+
+        self.write_changes()
     }
 
+    /// Adds a weak load command, the binary still loads if `path` is missing.
     pub fn add_dylib(&mut self, path: &str) -> Result<(), Error> {
-        let machos = self.macho_file.iter_macho_mut();
-        for macho in machos {
-            macho.add_dylib_load_path(path)?;
-        }
-        self.write_changes()?;
-        Ok(())
+        self.edit_slices(|macho| macho.add_dylib_load_path(path, true))
+    }
+
+    /// Adds an `LC_LOAD_DYLIB`, the binary fails to load without `path`.
+    pub fn add_required_dylib(&mut self, path: &str) -> Result<(), Error> {
+        self.edit_slices(|macho| macho.add_dylib_load_path(path, false))
     }
 
     pub fn replace_dylib(&mut self, old_path: &str, new_path: &str) -> Result<(), Error> {
-        let machos = self.macho_file.iter_macho_mut();
-        for macho in machos {
-            macho.replace_dylib_load_path(old_path, new_path)?;
-        }
-        self.write_changes()?;
-        Ok(())
+        self.edit_slices(|macho| macho.replace_dylib_load_path(old_path, new_path))
     }
 
     pub fn remove_dylib(&mut self, path: &str) -> Result<(), Error> {
-        let machos = self.macho_file.iter_macho_mut();
-        for macho in machos {
-            macho.remove_dylib_load_path(path)?;
-        }
-        self.write_changes()?;
-        Ok(())
+        self.edit_slices(|macho| macho.remove_dylib_load_path(path))
+    }
+
+    pub fn set_id_dylib(&mut self, id: &str) -> Result<(), Error> {
+        self.edit_slices(|macho| macho.set_id_dylib(id))
     }
 
     pub fn replace_sdk_version(&mut self, new_version: &str) -> Result<(), Error> {
-        let machos = self.macho_file.iter_macho_mut();
-        for macho in machos {
-            macho.replace_sdk_version(new_version)?;
-        }
-        self.write_changes()?;
-        Ok(())
+        self.edit_slices(|macho| macho.replace_sdk_version(new_version))
     }
+
+    /// Drops `LC_CODE_SIGNATURE` and the signature blob from every slice.
+    pub fn remove_code_signature(&mut self) -> Result<(), Error> {
+        self.edit_slices(|macho| macho.remove_code_signature())
+    }
+}
+
+/// Contents of `LC_ENCRYPTION_INFO` / `LC_ENCRYPTION_INFO_64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EncryptionInfo {
+    pub cryptoff: u32,
+    pub cryptsize: u32,
+    pub cryptid: u32,
 }
 
 #[allow(dead_code)]
 pub trait MachOExt {
     fn embedded_entitlements(&self) -> Result<Option<Dictionary>, Error>;
+    fn encryption_info(&self) -> Option<EncryptionInfo>;
     fn dylib_load_paths(&self) -> Result<Vec<String>, Error>;
-    fn add_dylib_load_path(&mut self, path: &str) -> Result<(), Error>;
-    fn remove_dylib_load_path(&mut self, path: &str) -> Result<(), Error>;
-    fn replace_dylib_load_path(&mut self, old_path: &str, new_path: &str) -> Result<(), Error>;
-    fn replace_sdk_version(&mut self, new_version: &str) -> Result<(), Error>;
+    fn weak_dylib_load_paths(&self) -> Result<Vec<String>, Error>;
+    fn id_dylib(&self) -> Option<String>;
+    fn info(&self) -> Result<MachOInfo, Error>;
+    // editing functions return the modified copy of the slice
+    fn add_dylib_load_path(&self, path: &str, weak: bool) -> Result<Vec<u8>, Error>;
+    fn remove_dylib_load_path(&self, path: &str) -> Result<Vec<u8>, Error>;
+    fn replace_dylib_load_path(&self, old_path: &str, new_path: &str) -> Result<Vec<u8>, Error>;
+    fn set_id_dylib(&self, id: &str) -> Result<Vec<u8>, Error>;
+    fn replace_sdk_version(&self, new_version: &str) -> Result<Vec<u8>, Error>;
+    fn remove_code_signature(&self) -> Result<Vec<u8>, Error>;
 }
 
 // theres multiple binaries in MachFile, being Vec<MachOBinary>
@@ -135,36 +231,43 @@ impl<'a> MachOExt for MachOBinary<'a> {
         Ok(None)
     }
 
+    fn encryption_info(&self) -> Option<EncryptionInfo> {
+        self.macho
+            .load_commands
+            .iter()
+            .find_map(|load_cmd| match &load_cmd.command {
+                CommandVariant::EncryptionInfo32(info) => Some(EncryptionInfo {
+                    cryptoff: info.cryptoff,
+                    cryptsize: info.cryptsize,
+                    cryptid: info.cryptid,
+                }),
+                CommandVariant::EncryptionInfo64(info) => Some(EncryptionInfo {
+                    cryptoff: info.cryptoff,
+                    cryptsize: info.cryptsize,
+                    cryptid: info.cryptid,
+                }),
+                _ => None,
+            })
+    }
+
     fn dylib_load_paths(&self) -> Result<Vec<String>, Error> {
-        const DYLIB_COMMANDS: &[u32] = &[
-            LC_LOAD_DYLIB,
-            LC_LOAD_WEAK_DYLIB,
-            LC_REEXPORT_DYLIB,
-            LC_LAZY_LOAD_DYLIB,
-            LC_LOAD_UPWARD_DYLIB,
-        ];
+        Ok(load_paths_for(self, DYLIB_COMMANDS))
+    }
 
-        let mut paths = Vec::new();
+    fn weak_dylib_load_paths(&self) -> Result<Vec<String>, Error> {
+        Ok(load_paths_for(self, &[LC_LOAD_WEAK_DYLIB]))
+    }
 
-        for load_cmd in &self.macho.load_commands {
-            if DYLIB_COMMANDS.contains(&load_cmd.command.cmd()) {
-                let path = match &load_cmd.command {
-                    CommandVariant::LoadDylib(dylib) => {
-                        extract_dylib_path(self.data, load_cmd.offset, dylib.dylib.name)
-                    }
-                    _ => manually_parse_dylib(self.data, load_cmd.offset),
-                };
-                if let Some(p) = path {
-                    paths.push(p);
-                }
-            }
-        }
+    fn id_dylib(&self) -> Option<String> {
+        load_paths_for(self, &[LC_ID_DYLIB]).into_iter().next()
+    }
 
-        Ok(paths)
+    fn info(&self) -> Result<MachOInfo, Error> {
+        macho_info::collect(&self.macho, self.data)
     }
 
     // these require rewriting the Mach-O
-    fn add_dylib_load_path(&mut self, path: &str) -> Result<(), Error> {
+    fn add_dylib_load_path(&self, path: &str, weak: bool) -> Result<Vec<u8>, Error> {
         let macho = &self.macho;
 
         let read_u32_le = |data: &[u8], offset: usize| -> u32 {
@@ -201,7 +304,7 @@ impl<'a> MachOExt for MachOBinary<'a> {
 
         if dylib_exists {
             log::warn!("Dylib already exists in binary: {}", path);
-            return Ok(());
+            return Ok(data);
         }
 
         let header_size = if is_64 { 32 } else { 28 };
@@ -217,40 +320,23 @@ impl<'a> MachOExt for MachOBinary<'a> {
         let sizeofcmds_offset = header_offset + 20;
         let ncmds_offset = header_offset + 16;
 
-        // Find the minimum non-zero file offset from segments
-        let min_fileoff = macho
-            .load_commands
-            .iter()
-            .filter_map(|load_cmd| match &load_cmd.command {
-                CommandVariant::Segment64(seg) if seg.filesize > 0 && seg.fileoff > 0 => {
-                    Some(seg.fileoff)
-                }
-                CommandVariant::Segment32(seg) if seg.filesize > 0 && seg.fileoff > 0 => {
-                    Some(seg.fileoff as u64)
-                }
-                _ => None,
-            })
-            .min()
-            .unwrap_or(u64::MAX);
-
-        // Calculate available space
         let load_commands_end = load_commands_offset + current_sizeofcmds as usize;
-        let data_start = if min_fileoff < u64::MAX {
-            min_fileoff as usize
-        } else {
-            data.len()
-        };
-
-        let available_space = data_start.saturating_sub(load_commands_end);
+        let available_space =
+            load_commands_limit(macho, data.len()).saturating_sub(load_commands_end);
 
         if dylib_command_size > available_space {
-            return Err(Error::Parse);
+            return Err(Error::MachOHeaderSpace);
         }
 
         // Write the new load command into the available space (no splice needed!)
         let insert_offset = load_commands_end;
         let mut new_command = Vec::new();
-        new_command.extend_from_slice(&(LC_LOAD_WEAK_DYLIB as u32).to_le_bytes()); // cmd
+        let cmd = if weak {
+            LC_LOAD_WEAK_DYLIB
+        } else {
+            LC_LOAD_DYLIB
+        };
+        new_command.extend_from_slice(&cmd.to_le_bytes()); // cmd
         new_command.extend_from_slice(&(dylib_command_size as u32).to_le_bytes()); // cmdsize
 
         // dylib_command structure:
@@ -279,166 +365,82 @@ impl<'a> MachOExt for MachOBinary<'a> {
             .copy_from_slice(&new_sizeofcmds.to_le_bytes());
         data[ncmds_offset..ncmds_offset + 4].copy_from_slice(&new_ncmds.to_le_bytes());
 
-        self.data = Box::leak(data.into_boxed_slice());
-
-        Ok(())
+        Ok(data)
     }
 
-    fn remove_dylib_load_path(&mut self, path: &str) -> Result<(), Error> {
+    fn remove_dylib_load_path(&self, path: &str) -> Result<Vec<u8>, Error> {
         let macho = &self.macho;
         let mut data = self.data.to_vec();
 
-        let read_u32_le = |data: &[u8], offset: usize| -> u32 {
-            u32::from_le_bytes([
-                data[offset],
-                data[offset + 1],
-                data[offset + 2],
-                data[offset + 3],
-            ])
-        };
-
-        let replacements: Vec<(usize, usize, usize)> = macho
+        let removals: Vec<(usize, usize)> = macho
             .load_commands
             .iter()
-            .filter_map(|load_cmd| {
-                match &load_cmd.command {
-                    CommandVariant::LoadDylib(dylib) => {
-                        extract_dylib_path(self.data, load_cmd.offset, dylib.dylib.name)
-                    }
-                    _ => manually_parse_dylib(self.data, load_cmd.offset),
-                }
-                .and_then(|name| {
-                    if name == path {
-                        let cmdsize = read_u32_le(&self.data, load_cmd.offset + 4) as usize;
-                        Some((0, load_cmd.offset, cmdsize))
-                    } else {
-                        None
-                    }
-                })
+            .filter(|load_cmd| DYLIB_COMMANDS.contains(&load_cmd.command.cmd()))
+            .filter(|load_cmd| {
+                manually_parse_dylib(self.data, load_cmd.offset).is_some_and(|name| name == path)
             })
+            .map(|load_cmd| (load_cmd.offset, load_cmd.command.cmdsize()))
             .collect();
 
-        if replacements.is_empty() {
+        if removals.is_empty() {
             log::warn!("No matching dylib load commands found for path: {}", path);
-            return Ok(());
+            return Ok(data);
         }
 
-        let current_sizeofcmds = read_u32_le(&self.data, 20);
-        let current_ncmds = read_u32_le(&self.data, 16);
-        let mut total_removed_size = 0;
-        for (arch_offset, cmd_offset, cmdsize) in &replacements {
-            let absolute_cmd_offset = arch_offset + cmd_offset - total_removed_size;
-            // Shift subsequent data to overwrite the removed command
-            data.copy_within(absolute_cmd_offset + cmdsize.., absolute_cmd_offset);
-            total_removed_size += cmdsize;
-        }
-        // Update header fields
-        let new_sizeofcmds = current_sizeofcmds - total_removed_size as u32;
-        let new_ncmds = current_ncmds - replacements.len() as u32;
-        data[20..24].copy_from_slice(&new_sizeofcmds.to_le_bytes());
-        data[16..20].copy_from_slice(&new_ncmds.to_le_bytes());
-        data.truncate(20 + new_sizeofcmds as usize);
+        remove_load_commands(&mut data, macho.is_64, &removals);
 
-        self.data = Box::leak(data.into_boxed_slice());
-
-        Ok(())
+        Ok(data)
     }
 
-    fn replace_dylib_load_path(&mut self, old_path: &str, new_path: &str) -> Result<(), Error> {
-        let macho = &self.macho;
+    fn replace_dylib_load_path(&self, old_path: &str, new_path: &str) -> Result<Vec<u8>, Error> {
         let mut data = self.data.to_vec();
 
-        const DYLIB_COMMANDS: &[u32] = &[
-            LC_LOAD_DYLIB,
-            LC_LOAD_WEAK_DYLIB,
-            LC_REEXPORT_DYLIB,
-            LC_LAZY_LOAD_DYLIB,
-            LC_LOAD_UPWARD_DYLIB,
-        ];
-
-        let read_u32_le = |data: &[u8], offset: usize| -> u32 {
-            u32::from_le_bytes([
-                data[offset],
-                data[offset + 1],
-                data[offset + 2],
-                data[offset + 3],
-            ])
-        };
-
-        let find_dylib_matches = |macho: &GoblinMachO, base_offset: usize| -> Vec<(usize, usize)> {
-            macho
-                .load_commands
-                .iter()
-                .filter(|load_cmd| DYLIB_COMMANDS.contains(&load_cmd.command.cmd()))
-                .filter_map(|load_cmd| {
-                    // Try to extract the path, first from LoadDylib variant, then manually
-                    let path = match &load_cmd.command {
-                        goblin::mach::load_command::CommandVariant::LoadDylib(dylib) => {
-                            extract_dylib_path(
-                                &self.data,
-                                base_offset + load_cmd.offset,
-                                dylib.dylib.name,
-                            )
-                        }
-                        _ => manually_parse_dylib(&self.data, base_offset + load_cmd.offset),
-                    }?;
-
-                    if path == old_path {
-                        let cmdsize =
-                            read_u32_le(&self.data, base_offset + load_cmd.offset + 4) as usize;
-                        return Some((load_cmd.offset, cmdsize));
-                    }
-                    None
-                })
-                .collect()
-        };
-
-        let replacements: Vec<(usize, usize, usize)> = find_dylib_matches(macho, 0)
-            .into_iter()
-            .map(|(offset, size)| (0, offset, size))
+        let matches: Vec<usize> = self
+            .macho
+            .load_commands
+            .iter()
+            .filter(|load_cmd| DYLIB_COMMANDS.contains(&load_cmd.command.cmd()))
+            .filter(|load_cmd| {
+                manually_parse_dylib(self.data, load_cmd.offset).is_some_and(|p| p == old_path)
+            })
+            .map(|load_cmd| load_cmd.offset)
             .collect();
 
-        if replacements.is_empty() {
+        if matches.is_empty() {
             log::warn!(
                 "No matching dylib load commands found for path: {}",
                 old_path
             );
-            return Ok(());
+            return Ok(data);
         }
 
-        for (arch_offset, cmd_offset, cmdsize) in &replacements {
-            let absolute_cmd_offset = arch_offset + cmd_offset;
-            let dylib_name_offset = absolute_cmd_offset + 24; // sizeof(dylib_command)
-            let available_space = cmdsize - 24;
-
-            let new_path_len = new_path.len();
-            let old_path_len = old_path.len();
-            let new_padding = (8 - ((new_path_len + 1) % 8)) % 8;
-            let required_space = new_path_len + 1 + new_padding;
+        // back to front, growing a command moves the ones after it
+        for offset in matches.into_iter().rev() {
+            write_dylib_name(&mut data, &self.macho, offset, new_path)?;
+        }
 
-            if required_space > available_space {
-                return Err(Error::Parse);
-            }
+        Ok(data)
+    }
 
-            // Only zero out the space we're actually using (old path + its null terminator + old padding)
-            let old_padding = (8 - ((old_path_len + 1) % 8)) % 8;
-            let old_total_size = old_path_len + 1 + old_padding;
-            for i in 0..old_total_size.min(available_space) {
-                data[dylib_name_offset + i] = 0;
-            }
+    fn set_id_dylib(&self, id: &str) -> Result<Vec<u8>, Error> {
+        let mut data = self.data.to_vec();
 
-            // Write new path and null terminator
-            data[dylib_name_offset..dylib_name_offset + new_path_len]
-                .copy_from_slice(new_path.as_bytes());
-            // Null terminator is already written by the zeroing above, padding bytes are also zeros
-        }
+        let Some(load_cmd) = self
+            .macho
+            .load_commands
+            .iter()
+            .find(|load_cmd| load_cmd.command.cmd() == LC_ID_DYLIB)
+        else {
+            log::warn!("Binary has no LC_ID_DYLIB to set to {}", id);
+            return Ok(data);
+        };
 
-        self.data = Box::leak(data.into_boxed_slice());
+        write_dylib_name(&mut data, &self.macho, load_cmd.offset, id)?;
 
-        Ok(())
+        Ok(data)
     }
 
-    fn replace_sdk_version(&mut self, new_version: &str) -> Result<(), Error> {
+    fn replace_sdk_version(&self, new_version: &str) -> Result<Vec<u8>, Error> {
         let macho = &self.macho;
         let mut data = self.data.to_vec();
 
@@ -464,13 +466,180 @@ impl<'a> MachOExt for MachOBinary<'a> {
             }
         }
 
-        self.data = Box::leak(data.into_boxed_slice());
+        Ok(data)
+    }
 
-        Ok(())
+    fn remove_code_signature(&self) -> Result<Vec<u8>, Error> {
+        let macho = &self.macho;
+        let mut data = self.data.to_vec();
+
+        let Some((cmd_offset, cmdsize, dataoff)) =
+            macho
+                .load_commands
+                .iter()
+                .find_map(|load_cmd| match &load_cmd.command {
+                    CommandVariant::CodeSignature(sig) => {
+                        Some((load_cmd.offset, sig.cmdsize as usize, sig.dataoff as usize))
+                    }
+                    _ => None,
+                })
+        else {
+            log::warn!("Binary has no code signature");
+            return Ok(data);
+        };
+
+        // The signature is the last thing in __LINKEDIT, the segment now ends where it started
+        let page_size: u64 = if macho.header.cputype == CPU_TYPE_ARM64 {
+            0x4000
+        } else {
+            0x1000
+        };
+
+        for load_cmd in &macho.load_commands {
+            match &load_cmd.command {
+                CommandVariant::Segment64(seg) if seg.segname.starts_with(b"__LINKEDIT\0") => {
+                    let filesize = (dataoff as u64)
+                        .checked_sub(seg.fileoff)
+                        .ok_or(Error::Parse)?;
+                    let vmsize = filesize.div_ceil(page_size) * page_size;
+                    data[load_cmd.offset + 32..load_cmd.offset + 40]
+                        .copy_from_slice(&vmsize.to_le_bytes());
+                    data[load_cmd.offset + 48..load_cmd.offset + 56]
+                        .copy_from_slice(&filesize.to_le_bytes());
+                }
+                CommandVariant::Segment32(seg) if seg.segname.starts_with(b"__LINKEDIT\0") => {
+                    let filesize = (dataoff as u32)
+                        .checked_sub(seg.fileoff)
+                        .ok_or(Error::Parse)?;
+                    let vmsize = filesize.div_ceil(page_size as u32) * page_size as u32;
+                    data[load_cmd.offset + 28..load_cmd.offset + 32]
+                        .copy_from_slice(&vmsize.to_le_bytes());
+                    data[load_cmd.offset + 36..load_cmd.offset + 40]
+                        .copy_from_slice(&filesize.to_le_bytes());
+                }
+                _ => {}
+            }
+        }
+
+        remove_load_commands(&mut data, macho.is_64, &[(cmd_offset, cmdsize)]);
+        data.truncate(dataoff);
+
+        Ok(data)
     }
 }
 
-fn extract_dylib_path(
+/// Whether `data` is a universal (fat) binary rather than a single slice.
+fn is_universal(data: &[u8]) -> bool {
+    data.first_chunk::<4>()
+        .is_some_and(|magic| [FAT_MAGIC, FAT_MAGIC_64].contains(&u32::from_be_bytes(*magic)))
+}
+
+fn load_paths_for(binary: &MachOBinary<'_>, commands: &[u32]) -> Vec<String> {
+    binary
+        .macho
+        .load_commands
+        .iter()
+        .filter(|load_cmd| commands.contains(&load_cmd.command.cmd()))
+        .filter_map(|load_cmd| match &load_cmd.command {
+            CommandVariant::LoadDylib(dylib) | CommandVariant::IdDylib(dylib) => {
+                extract_dylib_path(binary.data, load_cmd.offset, dylib.dylib.name)
+            }
+            _ => manually_parse_dylib(binary.data, load_cmd.offset),
+        })
+        .collect()
+}
+
+/// File offset the load commands can grow up to, the start of the first section's data.
+fn load_commands_limit(macho: &GoblinMachO, data_len: usize) -> usize {
+    let section_offsets = macho.segments.iter().flat_map(|segment| {
+        segment
+            .sections()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(section, _)| section.offset as usize)
+    });
+
+    let segment_offsets = macho
+        .segments
+        .iter()
+        .filter(|segment| segment.filesize > 0)
+        .map(|segment| segment.fileoff as usize);
+
+    // zerofill sections have no file offset
+    section_offsets
+        .chain(segment_offsets)
+        .filter(|offset| *offset > 0)
+        .min()
+        .unwrap_or(data_len)
+}
+
+/// Writes `name` into the dylib command at `offset`, growing the command and moving
+/// the ones after it when the new name doesn't fit.
+fn write_dylib_name(
+    data: &mut [u8],
+    macho: &GoblinMachO,
+    offset: usize,
+    name: &str,
+) -> Result<(), Error> {
+    let read_u32_le = |data: &[u8], offset: usize| -> usize {
+        u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ]) as usize
+    };
+
+    let header_size = if macho.is_64 { 32 } else { 28 };
+    let mut cmdsize = read_u32_le(data, offset + 4);
+    let name_offset = read_u32_le(data, offset + 8);
+    let required_size = (name_offset + name.len() + 1).next_multiple_of(8);
+
+    if required_size > cmdsize {
+        let sizeofcmds = read_u32_le(data, 20);
+        let cmds_end = header_size + sizeofcmds;
+        let grow = required_size - cmdsize;
+
+        if cmds_end + grow > load_commands_limit(macho, data.len()) {
+            return Err(Error::MachOHeaderSpace);
+        }
+
+        data.copy_within(offset + cmdsize..cmds_end, offset + required_size);
+        data[4 + offset..8 + offset].copy_from_slice(&(required_size as u32).to_le_bytes());
+        data[20..24].copy_from_slice(&((sizeofcmds + grow) as u32).to_le_bytes());
+        cmdsize = required_size;
+    }
+
+    data[offset + name_offset..offset + cmdsize].fill(0);
+    data[offset + name_offset..offset + name_offset + name.len()].copy_from_slice(name.as_bytes());
+
+    Ok(())
+}
+
+/// Removes the given `(offset, cmdsize)` load commands, moving the ones after them
+/// down and zeroing the freed space at the end of the load command area.
+fn remove_load_commands(data: &mut [u8], is_64: bool, commands: &[(usize, usize)]) {
+    let header_size = if is_64 { 32 } else { 28 };
+    let ncmds = u32::from_le_bytes([data[16], data[17], data[18], data[19]]);
+    let sizeofcmds = u32::from_le_bytes([data[20], data[21], data[22], data[23]]);
+    let mut cmds_end = header_size + sizeofcmds as usize;
+
+    let mut commands = commands.to_vec();
+    // remove from the back so earlier offsets stay valid
+    commands.sort_by(|a, b| b.0.cmp(&a.0));
+
+    for (offset, cmdsize) in &commands {
+        data.copy_within(offset + cmdsize..cmds_end, *offset);
+        cmds_end -= cmdsize;
+        data[cmds_end..cmds_end + cmdsize].fill(0);
+    }
+
+    let removed: usize = commands.iter().map(|(_, cmdsize)| cmdsize).sum();
+    data[16..20].copy_from_slice(&(ncmds - commands.len() as u32).to_le_bytes());
+    data[20..24].copy_from_slice(&(sizeofcmds - removed as u32).to_le_bytes());
+}
+
+pub(super) fn extract_dylib_path(
     file_data: &[u8],
     load_cmd_offset: usize,
     name_offset_rel: u32,
@@ -505,3 +674,309 @@ fn manually_parse_dylib(file_data: &[u8], load_cmd_offset: usize) -> Option<Stri
 
     extract_dylib_path(file_data, load_cmd_offset, name_offset_field)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goblin::mach::{
+        header::{MH_EXECUTE, MH_MAGIC_64},
+        load_command::{LC_CODE_SIGNATURE, LC_SEGMENT_64},
+    };
+
+    const PAGE: usize = 0x4000;
+    /// `__LINKEDIT` starts at [`PAGE`] with 0x100 bytes before the signature.
+    const SIGNATURE_OFFSET: usize = PAGE + 0x100;
+    const SIGNATURE_SIZE: usize = 0x200;
+
+    fn segment(name: &str, vmaddr: u64, vmsize: u64, fileoff: u64, filesize: u64) -> Vec<u8> {
+        let mut segname = [0u8; 16];
+        segname[..name.len()].copy_from_slice(name.as_bytes());
+
+        let mut cmd = Vec::new();
+        cmd.extend_from_slice(&LC_SEGMENT_64.to_le_bytes());
+        cmd.extend_from_slice(&72u32.to_le_bytes());
+        cmd.extend_from_slice(&segname);
+        for value in [vmaddr, vmsize, fileoff, filesize] {
+            cmd.extend_from_slice(&value.to_le_bytes());
+        }
+        // maxprot, initprot, nsects, flags
+        for value in [5u32, 5, 0, 0] {
+            cmd.extend_from_slice(&value.to_le_bytes());
+        }
+        cmd
+    }
+
+    /// Thin arm64 executable with `__TEXT`, `__LINKEDIT` and an empty signature at its end,
+    /// `extra` load commands go after those.
+    fn signed_binary(extra: &[Vec<u8>]) -> Vec<u8> {
+        let linkedit_size = (SIGNATURE_OFFSET + SIGNATURE_SIZE - PAGE) as u64;
+
+        let mut commands = vec![
+            segment("__TEXT", 0, PAGE as u64, 0, PAGE as u64),
+            segment(
+                "__LINKEDIT",
+                PAGE as u64,
+                2 * PAGE as u64,
+                PAGE as u64,
+                linkedit_size,
+            ),
+            [
+                LC_CODE_SIGNATURE,
+                16,
+                SIGNATURE_OFFSET as u32,
+                SIGNATURE_SIZE as u32,
+            ]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect(),
+        ];
+        commands.extend_from_slice(extra);
+        let sizeofcmds: usize = commands.iter().map(Vec::len).sum();
+
+        let mut data = Vec::new();
+        for value in [
+            MH_MAGIC_64,
+            CPU_TYPE_ARM64,
+            0,
+            MH_EXECUTE,
+            commands.len() as u32,
+            sizeofcmds as u32,
+            0,
+            0,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend(commands.concat());
+        data.resize(SIGNATURE_OFFSET, 0);
+        // Empty SuperBlob, big endian: magic, length, count
+        data.extend_from_slice(&[0xfa, 0xde, 0x0c, 0xc0, 0, 0, 0, 12, 0, 0, 0, 0]);
+        data.resize(SIGNATURE_OFFSET + SIGNATURE_SIZE, 0);
+        data
+    }
+
+    /// Dylib command naming `name`, padded to 8 bytes like the linker does.
+    fn dylib_command(cmd: u32, name: &str) -> Vec<u8> {
+        let cmdsize = (24 + name.len() + 1).next_multiple_of(8);
+
+        let mut command = Vec::new();
+        for value in [cmd, cmdsize as u32, 24, 2, 0x10000, 0x10000] {
+            command.extend_from_slice(&value.to_le_bytes());
+        }
+        command.extend_from_slice(name.as_bytes());
+        command.resize(cmdsize, 0);
+        command
+    }
+
+    /// Runs a slice edit on the first slice of `data`.
+    fn edit<F>(data: &[u8], apply: F) -> Vec<u8>
+    where
+        F: Fn(&MachOBinary<'_>) -> Result<Vec<u8>, Error>,
+    {
+        apply(&MachFile::parse(data).unwrap().nth_macho(0).unwrap()).unwrap()
+    }
+
+    fn temp_macho(data: Vec<u8>) -> MachO {
+        MachO {
+            data,
+            path: std::env::temp_dir().join(format!("plume_macho_{}", uuid::Uuid::new_v4())),
+            entitlements: None,
+        }
+    }
+
+    #[test]
+    fn test_remove_code_signature() {
+        let data = signed_binary(&[]);
+        let stripped = MachFile::parse(&data)
+            .unwrap()
+            .nth_macho(0)
+            .unwrap()
+            .remove_code_signature()
+            .unwrap();
+
+        // Everything from the signature on is gone
+        assert_eq!(stripped.len(), SIGNATURE_OFFSET);
+
+        let macho = GoblinMachO::parse(&stripped, 0).unwrap();
+        assert_eq!(macho.header.ncmds, 2);
+        assert_eq!(macho.header.sizeofcmds, 2 * 72);
+        assert!(
+            !macho
+                .load_commands
+                .iter()
+                .any(|c| matches!(c.command, CommandVariant::CodeSignature(_)))
+        );
+        // The freed command space is zeroed
+        assert!(stripped[32 + 2 * 72..32 + 3 * 72].iter().all(|b| *b == 0));
+
+        let linkedit = macho
+            .segments
+            .iter()
+            .find(|s| s.name().is_ok_and(|n| n == "__LINKEDIT"))
+            .unwrap();
+        assert_eq!(linkedit.fileoff, PAGE as u64);
+        assert_eq!(linkedit.filesize, 0x100);
+        // Rounded up to a whole arm64 page
+        assert_eq!(linkedit.vmsize, PAGE as u64);
+    }
+
+    #[test]
+    fn test_remove_code_signature_twice() {
+        let data = signed_binary(&[]);
+        let macho = MachFile::parse(&data).unwrap();
+        let stripped = macho.nth_macho(0).unwrap().remove_code_signature().unwrap();
+
+        let again = MachFile::parse(&stripped)
+            .unwrap()
+            .nth_macho(0)
+            .unwrap()
+            .remove_code_signature()
+            .unwrap();
+        assert_eq!(again, stripped);
+    }
+
+    #[test]
+    fn test_edit_keeps_layout() {
+        let mut thin = temp_macho(signed_binary(&[]));
+        thin.remove_code_signature().unwrap();
+        fs::remove_file(&thin.path).ok();
+        assert!(!is_universal(thin.data()));
+        assert_eq!(thin.data().len(), SIGNATURE_OFFSET);
+
+        let mut builder = UniversalBinaryBuilder::default();
+        builder.add_binary(signed_binary(&[]).as_slice()).unwrap();
+        let mut fat = Vec::new();
+        builder.write(&mut fat).unwrap();
+
+        let mut fat = temp_macho(fat);
+        fat.remove_code_signature().unwrap();
+        fs::remove_file(&fat.path).ok();
+        assert!(is_universal(fat.data()));
+        assert_eq!(fat.info().unwrap().len(), 1);
+
+        assert!(is_universal(&FAT_MAGIC_64.to_be_bytes()));
+    }
+
+    #[test]
+    fn test_add_dylib() {
+        let data = signed_binary(&[]);
+        let data = edit(&data, |m| m.add_dylib_load_path("@rpath/Weak.dylib", true));
+        let data = edit(&data, |m| {
+            m.add_dylib_load_path("@rpath/Required.dylib", false)
+        });
+        // Adding a path twice keeps one command
+        let data = edit(&data, |m| m.add_dylib_load_path("@rpath/Weak.dylib", false));
+
+        assert_eq!(
+            MachO::dylib_load_paths_bytes(&data).unwrap(),
+            ["@rpath/Weak.dylib", "@rpath/Required.dylib"]
+        );
+        assert_eq!(
+            temp_macho(data.clone()).weak_dylib_load_paths().unwrap(),
+            ["@rpath/Weak.dylib"]
+        );
+
+        let macho = GoblinMachO::parse(&data, 0).unwrap();
+        assert_eq!(macho.header.ncmds, 5);
+        // The signature didn't move
+        assert_eq!(data.len(), SIGNATURE_OFFSET + SIGNATURE_SIZE);
+    }
+
+    #[test]
+    fn test_add_dylib_without_space() {
+        let name = format!("@rpath/{}.dylib", "a".repeat(PAGE));
+        let data = signed_binary(&[]);
+        let macho = MachFile::parse(&data).unwrap();
+
+        assert!(matches!(
+            macho.nth_macho(0).unwrap().add_dylib_load_path(&name, true),
+            Err(Error::MachOHeaderSpace)
+        ));
+    }
+
+    #[test]
+    fn test_remove_dylib() {
+        let data = signed_binary(&[
+            dylib_command(LC_LOAD_DYLIB, "/usr/lib/libc++.1.dylib"),
+            dylib_command(LC_LOAD_WEAK_DYLIB, "@rpath/Tweak.dylib"),
+            dylib_command(LC_LOAD_DYLIB, "/usr/lib/libSystem.B.dylib"),
+        ]);
+        let sizeofcmds = GoblinMachO::parse(&data, 0).unwrap().header.sizeofcmds as usize;
+        let removed_size = dylib_command(LC_LOAD_WEAK_DYLIB, "@rpath/Tweak.dylib").len();
+
+        let data = edit(&data, |m| m.remove_dylib_load_path("@rpath/Tweak.dylib"));
+        assert_eq!(
+            MachO::dylib_load_paths_bytes(&data).unwrap(),
+            ["/usr/lib/libc++.1.dylib", "/usr/lib/libSystem.B.dylib"]
+        );
+
+        let macho = GoblinMachO::parse(&data, 0).unwrap();
+        assert_eq!(macho.header.ncmds, 5);
+        assert_eq!(macho.header.sizeofcmds as usize, sizeofcmds - removed_size);
+        let cmds_end = 32 + sizeofcmds;
+        assert!(
+            data[cmds_end - removed_size..cmds_end]
+                .iter()
+                .all(|b| *b == 0)
+        );
+
+        // Removing a path that isn't there leaves the binary alone
+        assert_eq!(
+            edit(&data, |m| m.remove_dylib_load_path("@rpath/Tweak.dylib")),
+            data
+        );
+    }
+
+    #[test]
+    fn test_replace_dylib() {
+        let data = signed_binary(&[
+            dylib_command(LC_LOAD_DYLIB, "/usr/lib/libsubstrate.dylib"),
+            dylib_command(LC_LOAD_DYLIB, "/usr/lib/libSystem.B.dylib"),
+        ]);
+
+        // Longer than the command it replaces, so the commands after it move
+        let new_path = "@rpath/CydiaSubstrate.framework/CydiaSubstrate";
+        let data = edit(&data, |m| {
+            m.replace_dylib_load_path("/usr/lib/libsubstrate.dylib", new_path)
+        });
+        assert_eq!(
+            MachO::dylib_load_paths_bytes(&data).unwrap(),
+            [new_path, "/usr/lib/libSystem.B.dylib"]
+        );
+
+        let data = edit(&data, |m| {
+            m.replace_dylib_load_path(new_path, "@rpath/libellekit.dylib")
+        });
+        assert_eq!(
+            MachO::dylib_load_paths_bytes(&data).unwrap(),
+            ["@rpath/libellekit.dylib", "/usr/lib/libSystem.B.dylib"]
+        );
+    }
+
+    #[test]
+    fn test_set_id_dylib() {
+        let data = signed_binary(&[
+            dylib_command(
+                LC_ID_DYLIB,
+                "/Library/MobileSubstrate/DynamicLibraries/Tweak.dylib",
+            ),
+            dylib_command(LC_LOAD_DYLIB, "/usr/lib/libSystem.B.dylib"),
+        ]);
+
+        let data = edit(&data, |m| m.set_id_dylib("@rpath/Tweak.dylib"));
+        assert_eq!(
+            temp_macho(data.clone()).id_dylib().unwrap().as_deref(),
+            Some("@rpath/Tweak.dylib")
+        );
+        assert_eq!(
+            MachO::dylib_load_paths_bytes(&data).unwrap(),
+            ["/usr/lib/libSystem.B.dylib"]
+        );
+
+        // Executables have no install name to set
+        let executable = signed_binary(&[]);
+        assert_eq!(
+            edit(&executable, |m| m.set_id_dylib("@rpath/Tweak.dylib")),
+            executable
+        );
+    }
+}
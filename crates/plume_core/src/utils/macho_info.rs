@@ -0,0 +1,676 @@
+use goblin::mach::{
+    MachO as GoblinMachO,
+    cputype::get_arch_name_from_types,
+    header::filetype_to_str,
+    load_command::{CommandVariant, cmd_to_str},
+};
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha384};
+
+use super::macho::{EncryptionInfo, extract_dylib_path};
+use crate::Error;
+
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade0cc0;
+const CSMAGIC_CODEDIRECTORY: u32 = 0xfade0c02;
+const CSSLOT_CODEDIRECTORY: u32 = 0;
+const CSSLOT_ENTITLEMENTS: u32 = 5;
+const CSSLOT_DER_ENTITLEMENTS: u32 = 7;
+const CSSLOT_ALTERNATE_CODEDIRECTORIES: u32 = 0x1000;
+const CSSLOT_ALTERNATE_CODEDIRECTORY_MAX: u32 = 5;
+const CSSLOT_SIGNATURESLOT: u32 = 0x10000;
+
+/// Decoded view of a single Mach-O slice.
+#[derive(Debug, Clone, Serialize)]
+pub struct MachOInfo {
+    pub arch: String,
+    pub cputype: u32,
+    pub cpusubtype: u32,
+    pub filetype: String,
+    pub flags: u32,
+    pub is_64: bool,
+    pub ncmds: usize,
+    pub sizeofcmds: u32,
+    pub uuid: Option<String>,
+    pub load_commands: Vec<LoadCommandInfo>,
+    pub segments: Vec<SegmentInfo>,
+    pub build_version: Option<BuildVersionInfo>,
+    pub encryption_info: Option<EncryptionInfo>,
+    pub rpaths: Vec<String>,
+    pub id_dylib: Option<String>,
+    pub code_signature: Option<CodeSignatureInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadCommandInfo {
+    pub name: String,
+    pub cmd: u32,
+    pub offset: usize,
+    pub cmdsize: u32,
+    /// Command specific fields, in the order they appear in the command.
+    pub fields: Vec<LoadCommandField>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadCommandField {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentInfo {
+    pub name: String,
+    pub vmaddr: u64,
+    pub vmsize: u64,
+    pub fileoff: u64,
+    pub filesize: u64,
+    pub maxprot: u32,
+    pub initprot: u32,
+    pub flags: u32,
+    pub sections: Vec<SectionInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionInfo {
+    pub name: String,
+    pub addr: u64,
+    pub size: u64,
+    pub offset: u32,
+    pub align: u32,
+    pub flags: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildVersionInfo {
+    pub platform: String,
+    pub minos: String,
+    pub sdk: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CodeSignatureInfo {
+    pub dataoff: u32,
+    pub datasize: u32,
+    /// False when the blob is truncated or in a format this doesn't know, only its location
+    /// is filled in then.
+    pub parsed: bool,
+    pub has_entitlements: bool,
+    /// Ad-hoc signatures carry an empty CMS blob, so this is only true for certificate signatures.
+    pub has_cms_signature: bool,
+    pub code_directories: Vec<CodeDirectoryInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CodeDirectoryInfo {
+    pub slot: u32,
+    pub version: u32,
+    pub flags: u32,
+    pub hash_type: String,
+    pub identifier: Option<String>,
+    pub team_id: Option<String>,
+    pub cdhash: String,
+    pub code_slots: u32,
+    pub special_slots: u32,
+    pub code_limit: u32,
+    pub page_size: u32,
+}
+
+pub(super) fn collect(macho: &GoblinMachO<'_>, data: &[u8]) -> Result<MachOInfo, Error> {
+    let header = &macho.header;
+
+    let mut uuid = None;
+    let mut build_version = None;
+    let mut encryption_info = None;
+    let mut code_signature = None;
+    let mut load_commands = Vec::with_capacity(macho.load_commands.len());
+
+    for load_cmd in &macho.load_commands {
+        let fields = decode_fields(&load_cmd.command, data, load_cmd.offset);
+
+        match &load_cmd.command {
+            CommandVariant::Uuid(cmd) => uuid = Some(format_uuid(&cmd.uuid)),
+            CommandVariant::BuildVersion(cmd) => {
+                build_version = Some(BuildVersionInfo {
+                    platform: platform_to_str(cmd.platform).to_string(),
+                    minos: format_version(cmd.minos),
+                    sdk: format_version(cmd.sdk),
+                })
+            }
+            CommandVariant::EncryptionInfo32(cmd) => {
+                encryption_info = Some(EncryptionInfo {
+                    cryptoff: cmd.cryptoff,
+                    cryptsize: cmd.cryptsize,
+                    cryptid: cmd.cryptid,
+                })
+            }
+            CommandVariant::EncryptionInfo64(cmd) => {
+                encryption_info = Some(EncryptionInfo {
+                    cryptoff: cmd.cryptoff,
+                    cryptsize: cmd.cryptsize,
+                    cryptid: cmd.cryptid,
+                })
+            }
+            CommandVariant::CodeSignature(cmd) => {
+                // A signature we can't read shouldn't hide the rest of the binary
+                code_signature = Some(
+                    parse_code_signature(data, cmd.dataoff, cmd.datasize).unwrap_or(
+                        CodeSignatureInfo {
+                            dataoff: cmd.dataoff,
+                            datasize: cmd.datasize,
+                            parsed: false,
+                            has_entitlements: false,
+                            has_cms_signature: false,
+                            code_directories: Vec::new(),
+                        },
+                    ),
+                )
+            }
+            _ => {}
+        }
+
+        load_commands.push(LoadCommandInfo {
+            name: cmd_to_str(load_cmd.command.cmd()).to_string(),
+            cmd: load_cmd.command.cmd(),
+            offset: load_cmd.offset,
+            cmdsize: load_cmd.command.cmdsize() as u32,
+            fields,
+        });
+    }
+
+    let mut segments = Vec::new();
+    for segment in &macho.segments {
+        let sections = segment
+            .sections()
+            .map_err(|_| Error::Parse)?
+            .into_iter()
+            .map(|(section, _)| SectionInfo {
+                name: section.name().unwrap_or_default().to_string(),
+                addr: section.addr,
+                size: section.size,
+                offset: section.offset,
+                align: section.align,
+                flags: section.flags,
+            })
+            .collect();
+
+        segments.push(SegmentInfo {
+            name: segment.name().unwrap_or_default().to_string(),
+            vmaddr: segment.vmaddr,
+            vmsize: segment.vmsize,
+            fileoff: segment.fileoff,
+            filesize: segment.filesize,
+            maxprot: segment.maxprot,
+            initprot: segment.initprot,
+            flags: segment.flags,
+            sections,
+        });
+    }
+
+    Ok(MachOInfo {
+        arch: get_arch_name_from_types(header.cputype, header.cpusubtype)
+            .unwrap_or("unknown")
+            .to_string(),
+        cputype: header.cputype,
+        cpusubtype: header.cpusubtype,
+        filetype: filetype_to_str(header.filetype).to_string(),
+        flags: header.flags,
+        is_64: macho.is_64,
+        ncmds: header.ncmds,
+        sizeofcmds: header.sizeofcmds,
+        uuid,
+        load_commands,
+        segments,
+        build_version,
+        encryption_info,
+        rpaths: macho.rpaths.iter().map(|p| p.to_string()).collect(),
+        id_dylib: macho.name.map(|n| n.to_string()),
+        code_signature,
+    })
+}
+
+fn decode_fields(command: &CommandVariant, data: &[u8], offset: usize) -> Vec<LoadCommandField> {
+    let mut fields = Vec::new();
+    let mut push = |name: &str, value: String| {
+        fields.push(LoadCommandField {
+            name: name.to_string(),
+            value,
+        })
+    };
+
+    match command {
+        CommandVariant::Segment32(cmd) => {
+            push("segname", segname_to_string(&cmd.segname));
+            push("vmaddr", format!("{:#x}", cmd.vmaddr));
+            push("vmsize", format!("{:#x}", cmd.vmsize));
+            push("fileoff", cmd.fileoff.to_string());
+            push("filesize", cmd.filesize.to_string());
+            push("nsects", cmd.nsects.to_string());
+        }
+        CommandVariant::Segment64(cmd) => {
+            push("segname", segname_to_string(&cmd.segname));
+            push("vmaddr", format!("{:#x}", cmd.vmaddr));
+            push("vmsize", format!("{:#x}", cmd.vmsize));
+            push("fileoff", cmd.fileoff.to_string());
+            push("filesize", cmd.filesize.to_string());
+            push("nsects", cmd.nsects.to_string());
+        }
+        CommandVariant::LoadDylib(cmd)
+        | CommandVariant::LoadWeakDylib(cmd)
+        | CommandVariant::ReexportDylib(cmd)
+        | CommandVariant::LazyLoadDylib(cmd)
+        | CommandVariant::LoadUpwardDylib(cmd)
+        | CommandVariant::IdDylib(cmd) => {
+            push(
+                "name",
+                extract_dylib_path(data, offset, cmd.dylib.name).unwrap_or_default(),
+            );
+            push("current_version", format_version(cmd.dylib.current_version));
+            push(
+                "compatibility_version",
+                format_version(cmd.dylib.compatibility_version),
+            );
+        }
+        CommandVariant::LoadDylinker(cmd)
+        | CommandVariant::IdDylinker(cmd)
+        | CommandVariant::DyldEnvironment(cmd) => {
+            push(
+                "name",
+                extract_dylib_path(data, offset, cmd.name).unwrap_or_default(),
+            );
+        }
+        CommandVariant::Rpath(cmd) => {
+            push(
+                "path",
+                extract_dylib_path(data, offset, cmd.path).unwrap_or_default(),
+            );
+        }
+        CommandVariant::Uuid(cmd) => push("uuid", format_uuid(&cmd.uuid)),
+        CommandVariant::BuildVersion(cmd) => {
+            push("platform", platform_to_str(cmd.platform).to_string());
+            push("minos", format_version(cmd.minos));
+            push("sdk", format_version(cmd.sdk));
+            push("ntools", cmd.ntools.to_string());
+        }
+        CommandVariant::VersionMinMacosx(cmd)
+        | CommandVariant::VersionMinIphoneos(cmd)
+        | CommandVariant::VersionMinTvos(cmd)
+        | CommandVariant::VersionMinWatchos(cmd) => {
+            push("version", format_version(cmd.version));
+            push("sdk", format_version(cmd.sdk));
+        }
+        CommandVariant::SourceVersion(cmd) => {
+            let v = cmd.version;
+            push(
+                "version",
+                format!(
+                    "{}.{}.{}.{}.{}",
+                    v >> 40,
+                    (v >> 30) & 0x3ff,
+                    (v >> 20) & 0x3ff,
+                    (v >> 10) & 0x3ff,
+                    v & 0x3ff
+                ),
+            );
+        }
+        CommandVariant::Main(cmd) => {
+            push("entryoff", format!("{:#x}", cmd.entryoff));
+            push("stacksize", cmd.stacksize.to_string());
+        }
+        CommandVariant::EncryptionInfo32(cmd) => {
+            push("cryptoff", cmd.cryptoff.to_string());
+            push("cryptsize", cmd.cryptsize.to_string());
+            push("cryptid", cmd.cryptid.to_string());
+        }
+        CommandVariant::EncryptionInfo64(cmd) => {
+            push("cryptoff", cmd.cryptoff.to_string());
+            push("cryptsize", cmd.cryptsize.to_string());
+            push("cryptid", cmd.cryptid.to_string());
+        }
+        CommandVariant::Symtab(cmd) => {
+            push("symoff", cmd.symoff.to_string());
+            push("nsyms", cmd.nsyms.to_string());
+            push("stroff", cmd.stroff.to_string());
+            push("strsize", cmd.strsize.to_string());
+        }
+        CommandVariant::Dysymtab(cmd) => {
+            push("nlocalsym", cmd.nlocalsym.to_string());
+            push("nextdefsym", cmd.nextdefsym.to_string());
+            push("nundefsym", cmd.nundefsym.to_string());
+            push("nindirectsyms", cmd.nindirectsyms.to_string());
+        }
+        CommandVariant::DyldInfo(cmd) | CommandVariant::DyldInfoOnly(cmd) => {
+            push("rebase", format!("{}+{}", cmd.rebase_off, cmd.rebase_size));
+            push("bind", format!("{}+{}", cmd.bind_off, cmd.bind_size));
+            push(
+                "weak_bind",
+                format!("{}+{}", cmd.weak_bind_off, cmd.weak_bind_size),
+            );
+            push(
+                "lazy_bind",
+                format!("{}+{}", cmd.lazy_bind_off, cmd.lazy_bind_size),
+            );
+            push("export", format!("{}+{}", cmd.export_off, cmd.export_size));
+        }
+        CommandVariant::CodeSignature(cmd)
+        | CommandVariant::SegmentSplitInfo(cmd)
+        | CommandVariant::FunctionStarts(cmd)
+        | CommandVariant::DataInCode(cmd)
+        | CommandVariant::DylibCodeSignDrs(cmd)
+        | CommandVariant::LinkerOptimizationHint(cmd)
+        | CommandVariant::DyldExportsTrie(cmd)
+        | CommandVariant::DyldChainedFixups(cmd) => {
+            push("dataoff", cmd.dataoff.to_string());
+            push("datasize", cmd.datasize.to_string());
+        }
+        _ => {}
+    }
+
+    fields
+}
+
+fn parse_code_signature(
+    data: &[u8],
+    dataoff: u32,
+    datasize: u32,
+) -> Result<CodeSignatureInfo, Error> {
+    let start = dataoff as usize;
+    let blob = data
+        .get(start..start + datasize as usize)
+        .ok_or(Error::Parse)?;
+
+    if read_u32_be(blob, 0)? != CSMAGIC_EMBEDDED_SIGNATURE {
+        return Err(Error::Parse);
+    }
+
+    let count = read_u32_be(blob, 8)? as usize;
+
+    let mut info = CodeSignatureInfo {
+        dataoff,
+        datasize,
+        parsed: true,
+        has_entitlements: false,
+        has_cms_signature: false,
+        code_directories: Vec::new(),
+    };
+
+    for i in 0..count {
+        let slot = read_u32_be(blob, 12 + i * 8)?;
+        let offset = read_u32_be(blob, 16 + i * 8)? as usize;
+
+        match slot {
+            CSSLOT_ENTITLEMENTS | CSSLOT_DER_ENTITLEMENTS => info.has_entitlements = true,
+            // an empty CMS wrapper is just the 8 byte blob header
+            CSSLOT_SIGNATURESLOT => info.has_cms_signature = read_u32_be(blob, offset + 4)? > 8,
+            CSSLOT_CODEDIRECTORY => info
+                .code_directories
+                .push(parse_code_directory(blob, slot, offset)?),
+            s if (CSSLOT_ALTERNATE_CODEDIRECTORIES
+                ..CSSLOT_ALTERNATE_CODEDIRECTORIES + CSSLOT_ALTERNATE_CODEDIRECTORY_MAX)
+                .contains(&s) =>
+            {
+                info.code_directories
+                    .push(parse_code_directory(blob, slot, offset)?)
+            }
+            _ => {}
+        }
+    }
+
+    Ok(info)
+}
+
+fn parse_code_directory(blob: &[u8], slot: u32, offset: usize) -> Result<CodeDirectoryInfo, Error> {
+    let cd = blob.get(offset..).ok_or(Error::Parse)?;
+
+    if read_u32_be(cd, 0)? != CSMAGIC_CODEDIRECTORY {
+        return Err(Error::Parse);
+    }
+
+    let length = read_u32_be(cd, 4)? as usize;
+    let cd = cd.get(..length).ok_or(Error::Parse)?;
+
+    let version = read_u32_be(cd, 8)?;
+    let ident_offset = read_u32_be(cd, 20)? as usize;
+    let hash_type = *cd.get(37).ok_or(Error::Parse)?;
+    let page_size = *cd.get(39).ok_or(Error::Parse)?;
+
+    // teamOffset only exists from version 0x20200 onwards
+    let team_id = if version >= 0x20200 {
+        match read_u32_be(cd, 48)? as usize {
+            0 => None,
+            team_offset => read_cstr(cd, team_offset),
+        }
+    } else {
+        None
+    };
+
+    let digest = match hash_type {
+        1 => Sha1::digest(cd).to_vec(),
+        2 | 3 => Sha256::digest(cd).to_vec(),
+        4 => Sha384::digest(cd).to_vec(),
+        _ => return Err(Error::Parse),
+    };
+
+    Ok(CodeDirectoryInfo {
+        slot,
+        version,
+        flags: read_u32_be(cd, 12)?,
+        hash_type: hash_type_to_str(hash_type).to_string(),
+        identifier: read_cstr(cd, ident_offset),
+        team_id,
+        // the CDHash is always truncated to 20 bytes, regardless of hash type
+        cdhash: hex::encode(&digest[..20]),
+        code_slots: read_u32_be(cd, 28)?,
+        special_slots: read_u32_be(cd, 24)?,
+        code_limit: read_u32_be(cd, 32)?,
+        page_size: if page_size == 0 { 0 } else { 1 << page_size },
+    })
+}
+
+fn read_u32_be(data: &[u8], offset: usize) -> Result<u32, Error> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(Error::Parse)
+}
+
+fn read_cstr(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..)?;
+    let end = bytes.iter().position(|&b| b == 0)?;
+    std::str::from_utf8(&bytes[..end])
+        .ok()
+        .map(|s| s.to_string())
+}
+
+fn segname_to_string(segname: &[u8; 16]) -> String {
+    let end = segname.iter().position(|&b| b == 0).unwrap_or(16);
+    String::from_utf8_lossy(&segname[..end]).into_owned()
+}
+
+fn format_uuid(uuid: &[u8; 16]) -> String {
+    let hex = hex::encode_upper(uuid);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Decodes the `xxxx.yy.zz` nibble encoding used by dylib and build versions.
+fn format_version(version: u32) -> String {
+    format!(
+        "{}.{}.{}",
+        version >> 16,
+        (version >> 8) & 0xff,
+        version & 0xff
+    )
+}
+
+fn platform_to_str(platform: u32) -> &'static str {
+    match platform {
+        1 => "macOS",
+        2 => "iOS",
+        3 => "tvOS",
+        4 => "watchOS",
+        5 => "bridgeOS",
+        6 => "Mac Catalyst",
+        7 => "iOS Simulator",
+        8 => "tvOS Simulator",
+        9 => "watchOS Simulator",
+        10 => "DriverKit",
+        11 => "visionOS",
+        12 => "visionOS Simulator",
+        _ => "unknown",
+    }
+}
+
+fn hash_type_to_str(hash_type: u8) -> &'static str {
+    match hash_type {
+        1 => "SHA-1",
+        2 => "SHA-256",
+        3 => "SHA-256 (truncated)",
+        4 => "SHA-384",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goblin::mach::{
+        cputype::CPU_TYPE_ARM64,
+        header::{MH_EXECUTE, MH_MAGIC_64},
+        load_command::LC_CODE_SIGNATURE,
+    };
+
+    const SIGNATURE_OFFSET: usize = 0x100;
+
+    /// Thin arm64 executable, signed with `signature` when there is one.
+    fn binary(signature: Option<&[u8]>) -> Vec<u8> {
+        let ncmds = u32::from(signature.is_some());
+        let mut data = Vec::new();
+        for value in [
+            MH_MAGIC_64,
+            CPU_TYPE_ARM64,
+            0,
+            MH_EXECUTE,
+            ncmds,
+            16 * ncmds,
+            0,
+            0,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        if let Some(signature) = signature {
+            let command = [
+                LC_CODE_SIGNATURE,
+                16,
+                SIGNATURE_OFFSET as u32,
+                signature.len() as u32,
+            ];
+            for value in command {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            data.resize(SIGNATURE_OFFSET, 0);
+            data.extend_from_slice(signature);
+        }
+        data
+    }
+
+    /// Version 0x20200 CodeDirectory without any hashes.
+    fn code_directory(hash_type: u8) -> Vec<u8> {
+        let identifier = b"com.example.test\0";
+        let team = b"TEAMID1234\0";
+        let ident_offset = 52;
+        let team_offset = ident_offset + identifier.len();
+        let length = team_offset + team.len();
+
+        let mut cd = Vec::new();
+        for value in [
+            CSMAGIC_CODEDIRECTORY,
+            length as u32,
+            0x20200,
+            0x2,
+            length as u32,
+            ident_offset as u32,
+            0,
+            0,
+            0x4000,
+        ] {
+            cd.extend_from_slice(&value.to_be_bytes());
+        }
+        // hashSize, hashType, platform, pageSize
+        cd.extend_from_slice(&[32, hash_type, 0, 12]);
+        // spare2, scatterOffset, teamOffset
+        for value in [0, 0, team_offset as u32] {
+            cd.extend_from_slice(&value.to_be_bytes());
+        }
+        cd.extend_from_slice(identifier);
+        cd.extend_from_slice(team);
+        cd
+    }
+
+    /// SuperBlob holding just `cd` in the CodeDirectory slot.
+    fn signature(cd: &[u8]) -> Vec<u8> {
+        let mut blob = Vec::new();
+        for value in [
+            CSMAGIC_EMBEDDED_SIGNATURE,
+            20 + cd.len() as u32,
+            1,
+            CSSLOT_CODEDIRECTORY,
+            20,
+        ] {
+            blob.extend_from_slice(&value.to_be_bytes());
+        }
+        blob.extend_from_slice(cd);
+        blob
+    }
+
+    fn info(data: &[u8]) -> MachOInfo {
+        collect(&GoblinMachO::parse(data, 0).unwrap(), data).unwrap()
+    }
+
+    #[test]
+    fn test_code_signature() {
+        let cd = code_directory(2);
+        let info = info(&binary(Some(&signature(&cd))));
+
+        let signature = info.code_signature.unwrap();
+        assert!(signature.parsed);
+        assert!(!signature.has_entitlements);
+        assert!(!signature.has_cms_signature);
+
+        let [directory] = signature.code_directories.as_slice() else {
+            panic!("expected one CodeDirectory");
+        };
+        assert_eq!(directory.hash_type, "SHA-256");
+        assert_eq!(directory.identifier.as_deref(), Some("com.example.test"));
+        assert_eq!(directory.team_id.as_deref(), Some("TEAMID1234"));
+        assert_eq!(directory.page_size, 0x1000);
+        assert_eq!(directory.cdhash, hex::encode(&Sha256::digest(&cd)[..20]));
+    }
+
+    #[test]
+    fn test_unsigned() {
+        let info = info(&binary(None));
+        assert_eq!(info.filetype, "EXECUTE");
+        assert!(info.code_signature.is_none());
+    }
+
+    #[test]
+    fn test_unparsed_code_signature() {
+        let unknown_hash = signature(&code_directory(9));
+        let mut truncated = signature(&code_directory(2));
+        truncated.truncate(24);
+
+        for blob in [unknown_hash, truncated] {
+            let signature = info(&binary(Some(&blob))).code_signature.unwrap();
+            assert!(!signature.parsed);
+            assert_eq!(signature.dataoff, SIGNATURE_OFFSET as u32);
+            assert_eq!(signature.datasize, blob.len() as u32);
+            assert!(signature.code_directories.is_empty());
+        }
+    }
+}
@@ -3,11 +3,18 @@ use plist::Value;
 mod certificate;
 #[cfg(feature = "tweaks")]
 mod macho;
+#[cfg(feature = "tweaks")]
+mod macho_info;
 mod provision;
 
 pub use certificate::CertificateIdentity;
 #[cfg(feature = "tweaks")]
-pub use macho::{MachO, MachOExt};
+pub use macho::{EncryptionInfo, MachO, MachOExt};
+#[cfg(feature = "tweaks")]
+pub use macho_info::{
+    BuildVersionInfo, CodeDirectoryInfo, CodeSignatureInfo, LoadCommandField, LoadCommandInfo,
+    MachOInfo, SectionInfo, SegmentInfo,
+};
 pub use provision::MobileProvision;
 
 pub const TEAM_ID_REGEX: &str = r"^[A-Z0-9]{10}\.";
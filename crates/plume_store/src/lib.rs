@@ -1,4 +1,11 @@
 mod gsa_account;
+mod network;
+mod quota;
 mod store;
 pub use gsa_account::{GsaAccount, account_from_session};
+pub use network::NetworkStore;
+pub use quota::{
+    FREE_APP_ID_LIMIT, FREE_CERTIFICATE_LIMIT, QUOTA_WINDOW, QuotaEntry, QuotaKind, QuotaLedger,
+    QuotaStatus, TeamQuota, UsedSlot,
+};
 pub use store::AccountStore;
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use plume_core::{Error, NetworkConfig};
+
+/// Network settings chosen in the GUI, applied with [`plume_core::set_network_config`].
+///
+/// The proxy password is never written to disk, it lasts until the app quits.
+#[derive(Debug, Default, Clone)]
+pub struct NetworkStore {
+    config: NetworkConfig,
+    path: Option<PathBuf>,
+}
+
+impl NetworkStore {
+    pub fn load_sync(path: &Option<PathBuf>) -> Result<Self, Error> {
+        let config = match path {
+            Some(path) if path.exists() => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            _ => NetworkConfig::default(),
+        };
+
+        Ok(Self {
+            config,
+            path: path.clone(),
+        })
+    }
+
+    pub fn save_sync(&self) -> Result<(), Error> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            let config = NetworkConfig {
+                proxy_password: None,
+                ..self.config.clone()
+            };
+            std::fs::write(path, serde_json::to_string_pretty(&config)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn config(&self) -> &NetworkConfig {
+        &self.config
+    }
+
+    pub fn set_config_sync(&mut self, config: NetworkConfig) -> Result<(), Error> {
+        self.config = config;
+        self.save_sync()
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use plume_core::Error;
+
+/// App IDs a free team can create within [`QUOTA_WINDOW`], deleting them doesn't give any back.
+pub const FREE_APP_ID_LIMIT: usize = 10;
+/// Development certificates a free team can hold, each one expires after [`QUOTA_WINDOW`].
+pub const FREE_CERTIFICATE_LIMIT: usize = 2;
+pub const QUOTA_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind {
+    AppId,
+    Certificate,
+}
+
+impl QuotaKind {
+    pub fn limit(self) -> usize {
+        match self {
+            QuotaKind::AppId => FREE_APP_ID_LIMIT,
+            QuotaKind::Certificate => FREE_CERTIFICATE_LIMIT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaEntry {
+    /// Portal ID of the App ID, or the certificate's serial number.
+    pub identifier: String,
+    /// Unix seconds.
+    pub created_at: u64,
+}
+
+impl QuotaEntry {
+    /// Unix seconds at which the slot this entry takes frees up.
+    pub fn frees_at(&self) -> u64 {
+        self.created_at.saturating_add(QUOTA_WINDOW.as_secs())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamQuota {
+    pub app_ids: Vec<QuotaEntry>,
+    pub certificates: Vec<QuotaEntry>,
+}
+
+impl TeamQuota {
+    pub fn entries(&self, kind: QuotaKind) -> &[QuotaEntry] {
+        match kind {
+            QuotaKind::AppId => &self.app_ids,
+            QuotaKind::Certificate => &self.certificates,
+        }
+    }
+
+    fn entries_mut(&mut self, kind: QuotaKind) -> &mut Vec<QuotaEntry> {
+        match kind {
+            QuotaKind::AppId => &mut self.app_ids,
+            QuotaKind::Certificate => &mut self.certificates,
+        }
+    }
+
+    pub fn status(&self, kind: QuotaKind, now: u64) -> QuotaStatus {
+        let mut in_use = self
+            .entries(kind)
+            .iter()
+            .filter(|e| e.frees_at() > now)
+            .map(|e| UsedSlot {
+                identifier: e.identifier.clone(),
+                frees_in: Duration::from_secs(e.frees_at() - now),
+            })
+            .collect::<Vec<_>>();
+        in_use.sort_by_key(|slot| slot.frees_in);
+
+        QuotaStatus {
+            kind,
+            limit: kind.limit(),
+            in_use,
+        }
+    }
+}
+
+/// How much of a team's quota the ledger has seen used.
+///
+/// Only counts what was created from this machine, the portal may know about more.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaStatus {
+    pub kind: QuotaKind,
+    pub limit: usize,
+    /// Slots taken, the one freeing up soonest first.
+    pub in_use: Vec<UsedSlot>,
+}
+
+impl QuotaStatus {
+    pub fn used(&self) -> usize {
+        self.in_use.len()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.limit.saturating_sub(self.used())
+    }
+
+    /// When the next slot frees up, `None` when nothing is in use.
+    pub fn next_free_in(&self) -> Option<Duration> {
+        self.in_use.first().map(|slot| slot.frees_in)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsedSlot {
+    pub identifier: String,
+    pub frees_in: Duration,
+}
+
+/// App IDs and certificates created per team, for staying within free account limits.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QuotaLedger {
+    teams: HashMap<String, TeamQuota>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl QuotaLedger {
+    pub async fn load(path: &Option<PathBuf>) -> Result<Self, Error> {
+        let mut ledger = match path {
+            Some(path) if path.exists() => {
+                let contents = tokio::fs::read_to_string(path).await?;
+                serde_json::from_str(&contents)?
+            }
+            _ => Self::default(),
+        };
+        ledger.path = path.clone();
+        Ok(ledger)
+    }
+
+    pub async fn save(&self) -> Result<(), Error> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+
+            tokio::fs::write(path, serde_json::to_string_pretty(self)?).await?;
+        }
+        Ok(())
+    }
+
+    pub fn teams(&self) -> &HashMap<String, TeamQuota> {
+        &self.teams
+    }
+
+    pub fn status(&self, team_id: &str, kind: QuotaKind) -> QuotaStatus {
+        self.teams
+            .get(team_id)
+            .cloned()
+            .unwrap_or_default()
+            .status(kind, unix_now())
+    }
+
+    /// Records a creation at `created_at`, dropping entries whose slot has freed up.
+    pub fn record_at(&mut self, team_id: &str, kind: QuotaKind, identifier: &str, created_at: u64) {
+        let entries = self
+            .teams
+            .entry(team_id.to_string())
+            .or_default()
+            .entries_mut(kind);
+
+        entries.retain(|e| e.frees_at() > created_at);
+        entries.push(QuotaEntry {
+            identifier: identifier.to_string(),
+            created_at,
+        });
+    }
+
+    pub async fn record(
+        &mut self,
+        team_id: &str,
+        kind: QuotaKind,
+        identifier: &str,
+    ) -> Result<(), Error> {
+        self.record_at(team_id, kind, identifier, unix_now());
+        self.save().await
+    }
+
+    /// Records the App IDs a registration created, see `RegistrationReport::created_app_ids`.
+    ///
+    /// Deleted App IDs still count against the quota, rolled back ones included.
+    pub async fn record_registration<'a>(
+        &mut self,
+        team_id: &str,
+        created_app_ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), Error> {
+        let now = unix_now();
+        for app_id in created_app_ids {
+            self.record_at(team_id, QuotaKind::AppId, app_id, now);
+        }
+        self.save().await
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_status() {
+        let mut ledger = QuotaLedger::default();
+        ledger.record_at("TEAM", QuotaKind::AppId, "com.example.a", 0);
+        ledger.record_at("TEAM", QuotaKind::AppId, "com.example.b", 2 * DAY);
+        ledger.record_at("TEAM", QuotaKind::Certificate, "SERIAL", DAY);
+
+        let status = ledger.teams()["TEAM"].status(QuotaKind::AppId, 3 * DAY);
+        assert_eq!(status.used(), 2);
+        assert_eq!(status.remaining(), FREE_APP_ID_LIMIT - 2);
+        assert_eq!(status.next_free_in(), Some(Duration::from_secs(4 * DAY)));
+
+        let status = ledger.teams()["TEAM"].status(QuotaKind::AppId, 8 * DAY);
+        assert_eq!(
+            status.in_use,
+            [UsedSlot {
+                identifier: "com.example.b".to_string(),
+                frees_in: Duration::from_secs(DAY),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_record_prunes_freed_entries() {
+        let mut ledger = QuotaLedger::default();
+        ledger.record_at("TEAM", QuotaKind::AppId, "com.example.a", 0);
+        ledger.record_at("TEAM", QuotaKind::AppId, "com.example.b", 8 * DAY);
+
+        assert_eq!(ledger.teams()["TEAM"].app_ids.len(), 1);
+        assert!(ledger.teams()["TEAM"].certificates.is_empty());
+    }
+}
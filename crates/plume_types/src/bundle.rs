@@ -5,6 +5,7 @@ use goblin::mach::{
     header::{MH_MAGIC, MH_MAGIC_64},
 };
 use plist::Value;
+use plume_core::MachO;
 use std::{fs, path::PathBuf};
 
 #[derive(Debug, Clone)]
@@ -56,6 +57,33 @@ impl Bundle {
 
         Ok(bundles)
     }
+
+    /// Path to the Mach-O of this bundle, dylibs point to themselves.
+    pub fn executable_path(&self) -> Option<PathBuf> {
+        if self.bundle_type == BundleType::Dylib {
+            return Some(self.bundle_dir.clone());
+        }
+
+        let executable_path = self.bundle_dir.join(self.get_executable()?);
+        executable_path.exists().then_some(executable_path)
+    }
+
+    /// Collects every binary in this bundle (and nested bundles) that is still FairPlay encrypted.
+    pub fn encrypted_binaries(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut encrypted = Vec::new();
+
+        for bundle in self.collect_bundles_sorted()? {
+            let Some(executable_path) = bundle.executable_path() else {
+                continue;
+            };
+
+            if MachO::new(&executable_path)?.is_encrypted()? {
+                encrypted.push(executable_path);
+            }
+        }
+
+        Ok(encrypted)
+    }
 }
 
 impl Bundle {
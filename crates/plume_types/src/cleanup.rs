@@ -0,0 +1,37 @@
+use plume_core::developer::qh::app_ids::AppID;
+
+/// App IDs no installed app or app extension is signed with, wildcard ones are never returned.
+///
+/// `installed` holds the bundle identifiers of the apps on the device, App IDs of their
+/// extensions count as used through the `<app>.` prefix. Apps installed on other devices
+/// aren't known here, so their App IDs are returned as unused.
+pub fn unused_app_ids<'a>(app_ids: &'a [AppID], installed: &[String]) -> Vec<&'a AppID> {
+    app_ids
+        .iter()
+        .filter(|app_id| !app_id.is_wild_card && !app_id_in_use(&app_id.identifier, installed))
+        .collect()
+}
+
+fn app_id_in_use(identifier: &str, installed: &[String]) -> bool {
+    installed.iter().any(|app| {
+        identifier == app
+            || identifier
+                .strip_prefix(app.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_id_in_use() {
+        let installed = vec!["com.example.app.TEAM".to_string()];
+
+        assert!(app_id_in_use("com.example.app.TEAM", &installed));
+        assert!(app_id_in_use("com.example.app.TEAM.widget", &installed));
+        assert!(!app_id_in_use("com.example.app.TEAMS", &installed));
+        assert!(!app_id_in_use("com.example.other.TEAM", &installed));
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Error, StageDir,
+    extract::{ExtractLimits, extract_deb},
+};
+
+/// Dependencies every injected app gets anyway, or that only make sense on a jailbroken device.
+const IMPLICIT_DEPENDENCIES: &[&str] = &[
+    "firmware",
+    "mobilesubstrate",
+    "ellekit",
+    "com.ex.substitute",
+    "com.ex.libsubstitute",
+    "org.coolstar.libhooker",
+    "coreutils",
+];
+const IMPLICIT_DEPENDENCY_PREFIXES: &[&str] = &["cy+", "gsc."];
+
+/// Fields from a `.deb`'s `control` file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DebControl {
+    pub package: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// `iphoneos-arm` for rootful, `iphoneos-arm64` for rootless packages.
+    pub architecture: Option<String>,
+    /// Raw `Depends` clauses, alternatives are still separated by `|`.
+    pub depends: Vec<String>,
+    pub provides: Vec<String>,
+    pub description: Option<String>,
+}
+
+impl DebControl {
+    pub fn parse(contents: &str) -> Option<Self> {
+        let mut control = DebControl::default();
+        let mut current_key: Option<String> = None;
+
+        for line in contents.lines() {
+            // continuation lines only extend the description
+            if line.starts_with(' ') || line.starts_with('\t') {
+                if let (Some("description"), Some(description)) =
+                    (current_key.as_deref(), control.description.as_mut())
+                {
+                    description.push('\n');
+                    description.push_str(line.trim());
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_string();
+
+            match key.as_str() {
+                "package" => control.package = value,
+                "name" => control.name = Some(value),
+                "version" => control.version = Some(value),
+                "architecture" => control.architecture = Some(value),
+                "depends" | "pre-depends" => control.depends.extend(split_list(&value)),
+                "provides" => control.provides.extend(split_list(&value)),
+                "description" => control.description = Some(value),
+                _ => {}
+            }
+
+            current_key = Some(key);
+        }
+
+        (!control.package.is_empty()).then_some(control)
+    }
+
+    /// Reads the `control` file out of a `.deb` without extracting its data.
+    pub fn from_deb(deb_path: &Path, limits: &ExtractLimits) -> Result<Self, Error> {
+        let stage_dir = StageDir::new("plume_control")?;
+        Self::extract_control(deb_path, stage_dir.path(), limits)
+    }
+
+    fn extract_control(
+        deb_path: &Path,
+        stage_dir: &Path,
+        limits: &ExtractLimits,
+    ) -> Result<Self, Error> {
+        let failed = |msg: &str| Error::TweakExtractionFailed(msg.to_string());
+
+        let control_dir = stage_dir.join("control_contents");
+        if !extract_deb(deb_path, "control.tar", &control_dir, limits)? {
+            return Err(failed("control.tar not found"));
+        }
+
+        let contents = fs::read_to_string(control_dir.join("control"))?;
+
+        Self::parse(&contents).ok_or_else(|| failed("control has no Package field"))
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.package)
+    }
+
+    pub fn is_rootless(&self) -> bool {
+        matches!(
+            self.architecture.as_deref(),
+            Some("iphoneos-arm64") | Some("iphoneos-arm64e")
+        )
+    }
+
+    /// Package names per `Depends` clause, each clause is satisfied by any of its alternatives.
+    pub fn dependency_names(&self) -> Vec<Vec<String>> {
+        self.depends
+            .iter()
+            .map(|clause| clause.split('|').map(package_name).collect())
+            .collect()
+    }
+
+    /// `dependency` comes from [`package_name`], so it's already lowercase.
+    fn satisfies(&self, dependency: &str) -> bool {
+        self.package.to_lowercase() == dependency
+            || self.provides.iter().any(|p| package_name(p) == dependency)
+    }
+}
+
+/// Returns, for each package, the dependencies not satisfied by the others in the set.
+pub fn missing_dependencies(controls: &[&DebControl]) -> Vec<(String, String)> {
+    let mut missing = Vec::new();
+
+    for control in controls {
+        for clause in control.dependency_names() {
+            let satisfied = clause.iter().any(|dependency| {
+                is_implicit(dependency) || controls.iter().any(|c| c.satisfies(dependency))
+            });
+
+            if !satisfied {
+                missing.push((control.package.clone(), clause.join(" | ")));
+            }
+        }
+    }
+
+    missing
+}
+
+/// Orders tweak files so `.deb` dependencies install before their dependents.
+///
+/// Non-`.deb` files keep their relative order and go last, missing dependencies are logged.
+pub fn sort_tweaks(tweak_files: &[PathBuf], limits: &ExtractLimits) -> Vec<PathBuf> {
+    let (debs, others): (Vec<_>, Vec<_>) = tweak_files
+        .iter()
+        .partition(|p| p.extension().and_then(|e| e.to_str()) == Some("deb"));
+
+    let mut controls = Vec::new();
+    let mut unreadable = Vec::new();
+    for deb in debs {
+        match DebControl::from_deb(deb, limits) {
+            Ok(control) => controls.push((deb.clone(), control)),
+            Err(e) => {
+                log::warn!("Could not read control of {}: {}", deb.display(), e);
+                unreadable.push(deb.clone());
+            }
+        }
+    }
+
+    let control_refs = controls.iter().map(|(_, c)| c).collect::<Vec<_>>();
+    for (package, dependency) in missing_dependencies(&control_refs) {
+        log::warn!("{package} depends on {dependency}, which was not supplied");
+    }
+
+    let mut ordered = Vec::new();
+    let mut visiting = Vec::new();
+
+    fn visit(
+        index: usize,
+        controls: &[(PathBuf, DebControl)],
+        visiting: &mut Vec<usize>,
+        ordered: &mut Vec<usize>,
+    ) {
+        // cycles fall back to input order
+        if ordered.contains(&index) || visiting.contains(&index) {
+            return;
+        }
+        visiting.push(index);
+
+        for clause in controls[index].1.dependency_names() {
+            if let Some(dependency) = controls
+                .iter()
+                .position(|(_, c)| clause.iter().any(|d| c.satisfies(d)))
+            {
+                visit(dependency, controls, visiting, ordered);
+            }
+        }
+
+        visiting.pop();
+        ordered.push(index);
+    }
+
+    for index in 0..controls.len() {
+        visit(index, &controls, &mut visiting, &mut ordered);
+    }
+
+    ordered
+        .into_iter()
+        .map(|index| controls[index].0.clone())
+        .chain(unreadable)
+        .chain(others.into_iter().cloned())
+        .collect()
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Strips version constraints and architecture qualifiers, `foo:any (>= 1.0)` becomes `foo`.
+fn package_name(entry: &str) -> String {
+    let name = entry.trim();
+    let name = name.split(['(', ' ']).next().unwrap_or(name);
+    let name = name.split(':').next().unwrap_or(name);
+    name.to_lowercase()
+}
+
+fn is_implicit(dependency: &str) -> bool {
+    IMPLICIT_DEPENDENCIES.contains(&dependency)
+        || IMPLICIT_DEPENDENCY_PREFIXES
+            .iter()
+            .any(|prefix| dependency.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTROL: &str = "Package: com.example.tweak
+Name: Example
+Version: 1.2.0
+Architecture: iphoneos-arm64
+Depends: firmware (>= 14.0), mobilesubstrate | ellekit, preferenceloader, com.example.lib:any
+Description: Does things
+ across several lines
+";
+
+    #[test]
+    fn test_parse_control() {
+        let control = DebControl::parse(CONTROL).unwrap();
+        assert_eq!(control.package, "com.example.tweak");
+        assert_eq!(control.display_name(), "Example");
+        assert_eq!(control.version.as_deref(), Some("1.2.0"));
+        assert!(control.is_rootless());
+        assert_eq!(
+            control.description.as_deref(),
+            Some("Does things\nacross several lines")
+        );
+        assert_eq!(
+            control.dependency_names(),
+            vec![
+                vec!["firmware".to_string()],
+                vec!["mobilesubstrate".to_string(), "ellekit".to_string()],
+                vec!["preferenceloader".to_string()],
+                vec!["com.example.lib".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_dependencies() {
+        let tweak = DebControl::parse(CONTROL).unwrap();
+        // Package names are case-insensitive
+        let lib = DebControl::parse("Package: com.Example.Lib\n").unwrap();
+
+        assert_eq!(
+            missing_dependencies(&[&tweak, &lib]),
+            vec![(
+                "com.example.tweak".to_string(),
+                "preferenceloader".to_string()
+            )]
+        );
+    }
+}
@@ -63,6 +63,21 @@ impl Device {
     }
 
     pub async fn installed_apps(&self) -> Result<Vec<SignerAppReal>, Error> {
+        let mut found_apps = Vec::new();
+
+        for bundle_id in self.installed_bundle_identifiers().await? {
+            let signer_app = SignerAppReal::from_bundle_identifier(Some(bundle_id.as_str()));
+
+            if signer_app.app.supports_pairing_file_alt() {
+                found_apps.push(signer_app);
+            }
+        }
+
+        Ok(found_apps)
+    }
+
+    /// Bundle identifiers of every user installed app.
+    pub async fn installed_bundle_identifiers(&self) -> Result<Vec<String>, Error> {
         let device = match &self.usbmuxd_device {
             Some(dev) => dev,
             None => return Err(Error::Other("Device is not connected via USB".to_string())),
@@ -76,17 +91,7 @@ impl Device {
         let mut ic = InstallationProxyClient::connect(&provider).await?;
         let apps = ic.get_apps(Some("User"), None).await?;
 
-        let mut found_apps = Vec::new();
-
-        for (bundle_id, _) in apps {
-            let signer_app = SignerAppReal::from_bundle_identifier(Some(bundle_id.as_str()));
-
-            if signer_app.app.supports_pairing_file_alt() {
-                found_apps.push(signer_app);
-            }
-        }
-
-        Ok(found_apps)
+        Ok(apps.into_iter().map(|(bundle_id, _)| bundle_id).collect())
     }
 
     pub async fn pair(&self) -> Result<(), Error> {
@@ -236,15 +241,10 @@ pub async fn get_device_for_id(device_id: &str) -> Result<Device, Error> {
 
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 pub async fn install_app_mac(app_path: &PathBuf) -> Result<(), Error> {
-    use crate::copy_dir_recursively;
-    use std::env;
+    use crate::{StageDir, copy_dir_recursively};
     use tokio::fs;
-    use uuid::Uuid;
 
-    let stage_dir = env::temp_dir().join(format!(
-        "plume_mac_stage_{}",
-        Uuid::new_v4().to_string().to_uppercase()
-    ));
+    let stage_dir = StageDir::new("plume_mac_stage")?;
     let app_name = app_path
         .file_name()
         .ok_or(Error::Other("Invalid app path".to_string()))?;
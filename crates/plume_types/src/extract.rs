@@ -0,0 +1,425 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs,
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use zip::ZipArchive;
+
+use crate::Error;
+
+/// Longest symlink target read from an archive, `PATH_MAX` on Linux.
+const MAX_LINK_TARGET: u64 = 4096;
+
+/// Bounds on what a single archive may unpack to, packages and tweaks come from anywhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractLimits {
+    /// Total uncompressed size in bytes.
+    pub max_size: u64,
+    pub max_entries: usize,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        ExtractLimits {
+            max_size: 16 * 1024 * 1024 * 1024,
+            max_entries: 250_000,
+        }
+    }
+}
+
+/// Unpacks an IPA or zipped tweak into `dest`.
+///
+/// Links are created after every file is written, so nothing can be written through them.
+pub(crate) fn extract_zip(
+    archive_path: &Path,
+    dest: &Path,
+    limits: &ExtractLimits,
+) -> Result<(), Error> {
+    let mut archive = ZipArchive::new(fs::File::open(archive_path)?)?;
+    if archive.len() > limits.max_entries {
+        return Err(Error::ArchiveTooManyEntries(limits.max_entries));
+    }
+
+    fs::create_dir_all(dest)?;
+
+    let mut size = 0u64;
+    let mut links = Vec::new();
+    #[cfg(unix)]
+    let mut modes = Vec::new();
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let relative = enclosed_path(file.name())?;
+        let out_path = dest.join(&relative);
+
+        if file.is_dir() {
+            fs::create_dir_all(&out_path)?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if file.is_symlink() {
+            let mut target = String::new();
+            file.by_ref()
+                .take(MAX_LINK_TARGET + 1)
+                .read_to_string(&mut target)?;
+            if target.len() as u64 > MAX_LINK_TARGET {
+                return Err(Error::ArchiveLinkTooLong(relative.display().to_string()));
+            }
+
+            size += target.len() as u64;
+            if size > limits.max_size {
+                return Err(Error::ArchiveTooLarge(limits.max_size));
+            }
+
+            links.push((relative, PathBuf::from(target)));
+            continue;
+        }
+
+        write_limited(&mut file, &out_path, &mut size, limits)?;
+
+        #[cfg(unix)]
+        if let Some(mode) = file.unix_mode() {
+            modes.push((out_path, mode));
+        }
+    }
+
+    create_links(dest, links)?;
+
+    #[cfg(unix)]
+    set_modes(modes)?;
+
+    Ok(())
+}
+
+/// Unpacks the first member of a `.deb` whose name starts with `member`, e.g. `data.tar`, into
+/// `dest`, returning `false` when there is none.
+///
+/// The tar is read straight out of the `ar` archive and decompressed on the fly, every entry
+/// is bounded by `limits` as it's written.
+pub(crate) fn extract_deb(
+    deb_path: &Path,
+    member: &str,
+    dest: &Path,
+    limits: &ExtractLimits,
+) -> Result<bool, Error> {
+    let failed = |e: io::Error| Error::TweakExtractionFailed(e.to_string());
+    let mut archive = ar::Archive::new(fs::File::open(deb_path)?);
+
+    while let Some(entry) = archive.next_entry() {
+        let entry = entry.map_err(failed)?;
+        let name = String::from_utf8_lossy(entry.header().identifier())
+            .trim_end_matches('/')
+            .to_string();
+        if !name.starts_with(member) {
+            continue;
+        }
+
+        let extension = Path::new(&name).extension().and_then(|e| e.to_str());
+        let reader: Box<dyn Read + '_> = match extension {
+            Some("gz") => Box::new(flate2::read::GzDecoder::new(entry)),
+            Some("xz") => Box::new(xz2::read::XzDecoder::new(entry)),
+            Some("lzma") => {
+                let stream = xz2::stream::Stream::new_lzma_decoder(u64::MAX)
+                    .map_err(|e| failed(e.into()))?;
+                Box::new(xz2::read::XzDecoder::new_stream(entry, stream))
+            }
+            Some("bz2") => Box::new(bzip2::read::BzDecoder::new(entry)),
+            Some("zst") => Box::new(zstd::stream::read::Decoder::new(entry).map_err(failed)?),
+            Some("tar") => Box::new(entry),
+            _ => return Err(Error::TweakExtractionFailed(format!("Unsupported {name}"))),
+        };
+
+        extract_tar(reader, dest, limits)?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// Unpacks a tar stream into `dest`, links are created once every file is written.
+fn extract_tar(reader: impl Read, dest: &Path, limits: &ExtractLimits) -> Result<(), Error> {
+    fs::create_dir_all(dest)?;
+
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = 0;
+    let mut size = 0u64;
+    let mut links = Vec::new();
+    let mut hard_links = Vec::new();
+    #[cfg(unix)]
+    let mut modes = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        entries += 1;
+        if entries > limits.max_entries {
+            return Err(Error::ArchiveTooManyEntries(limits.max_entries));
+        }
+
+        let relative = enclosed_path(&entry.path()?.to_string_lossy())?;
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let out_path = dest.join(&relative);
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            fs::create_dir_all(&out_path)?;
+            continue;
+        }
+        if !(entry_type.is_file() || entry_type.is_symlink() || entry_type.is_hard_link()) {
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let target = entry
+                .link_name()?
+                .map(|t| t.into_owned())
+                .ok_or_else(|| Error::ArchiveLinkEscapes(relative.display().to_string()))?;
+            if entry_type.is_symlink() {
+                links.push((relative, target));
+            } else {
+                hard_links.push((relative, enclosed_path(&target.to_string_lossy())?));
+            }
+            continue;
+        }
+
+        write_limited(&mut entry, &out_path, &mut size, limits)?;
+
+        #[cfg(unix)]
+        modes.push((out_path, entry.header().mode()?));
+    }
+
+    // Hard links name an earlier entry relative to the archive root, copies are enough
+    for (relative, target) in hard_links {
+        let target_path = dest.join(&target);
+        if !fs::symlink_metadata(&target_path).is_ok_and(|m| m.is_file()) {
+            return Err(Error::ArchiveLinkEscapes(relative.display().to_string()));
+        }
+
+        size += fs::metadata(&target_path)?.len();
+        if size > limits.max_size {
+            return Err(Error::ArchiveTooLarge(limits.max_size));
+        }
+        fs::copy(&target_path, dest.join(&relative))?;
+    }
+
+    create_links(dest, links)?;
+
+    #[cfg(unix)]
+    set_modes(modes)?;
+
+    Ok(())
+}
+
+/// Copies `reader` to a new file at `path`, failing as soon as `size` goes past the limit.
+fn write_limited(
+    reader: &mut impl Read,
+    path: &Path,
+    size: &mut u64,
+    limits: &ExtractLimits,
+) -> Result<(), Error> {
+    // Declared sizes can lie, count what actually comes out
+    let remaining = limits.max_size.saturating_sub(*size);
+    let mut out_file = fs::File::create(path)?;
+    *size += io::copy(&mut reader.take(remaining.saturating_add(1)), &mut out_file)?;
+    if *size > limits.max_size {
+        return Err(Error::ArchiveTooLarge(limits.max_size));
+    }
+    Ok(())
+}
+
+/// Creates the archive's symlinks once none of them, followed through the others, leaves `dest`.
+fn create_links(dest: &Path, links: Vec<(PathBuf, PathBuf)>) -> Result<(), Error> {
+    let link_map: HashMap<PathBuf, PathBuf> = links.iter().cloned().collect();
+    for (relative, target) in &links {
+        if link_escapes(&link_map, relative, target) {
+            return Err(Error::ArchiveLinkEscapes(relative.display().to_string()));
+        }
+    }
+
+    for (relative, target) in links {
+        let link_path = dest.join(&relative);
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&target, &link_path)?;
+        #[cfg(windows)]
+        {
+            let resolved = link_path.parent().unwrap_or(dest).join(&target);
+            if resolved.is_dir() {
+                std::os::windows::fs::symlink_dir(&target, &link_path)?;
+            } else {
+                std::os::windows::fs::symlink_file(&target, &link_path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_modes(modes: Vec<(PathBuf, u32)>) -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt;
+    for (path, mode) in modes {
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o777))?;
+    }
+    Ok(())
+}
+
+/// Path of an archive entry relative to the extraction directory, rejecting anything that
+/// leaves it.
+fn enclosed_path(name: &str) -> Result<PathBuf, Error> {
+    let mut relative = PathBuf::new();
+
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(Error::ArchivePathTraversal(name.to_string()));
+            }
+        }
+    }
+
+    Ok(relative)
+}
+
+/// Whether a link at `relative` pointing to `target` resolves outside the extraction directory.
+///
+/// Resolves the way the OS will, following the other `links` of the archive (by their path
+/// relative to the extraction directory) wherever the target goes through one.
+fn link_escapes(links: &HashMap<PathBuf, PathBuf>, relative: &Path, target: &Path) -> bool {
+    // Same bound as the OS puts on link chains, anything longer can't be followed anyway
+    const MAX_LINKS_FOLLOWED: usize = 40;
+
+    let mut resolved: Vec<&OsStr> = relative
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .map(Component::as_os_str)
+        .collect();
+    let mut pending: Vec<Component> = target.components().rev().collect();
+    let mut followed = 0;
+
+    while let Some(component) = pending.pop() {
+        match component {
+            Component::Normal(part) => {
+                resolved.push(part);
+
+                let path: PathBuf = resolved.iter().collect();
+                if let Some(next) = links.get(&path) {
+                    followed += 1;
+                    if followed > MAX_LINKS_FOLLOWED {
+                        return true;
+                    }
+
+                    // The link's own target is relative to the directory holding it
+                    resolved.pop();
+                    pending.extend(next.components().rev());
+                }
+            }
+            Component::ParentDir => {
+                if resolved.pop().is_none() {
+                    return true;
+                }
+            }
+            Component::CurDir => {}
+            Component::RootDir | Component::Prefix(_) => return true,
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enclosed_path() {
+        assert_eq!(
+            enclosed_path("./Library/Tweak.dylib").unwrap(),
+            PathBuf::from("Library/Tweak.dylib")
+        );
+        assert!(enclosed_path("../evil").is_err());
+        assert!(enclosed_path("Payload/../../evil").is_err());
+        assert!(enclosed_path("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_link_escapes() {
+        let links = HashMap::new();
+        let link = Path::new("Foo.framework/Current");
+        assert!(!link_escapes(&links, link, Path::new("Versions/A")));
+        assert!(!link_escapes(&links, link, Path::new("../Bar.framework")));
+        assert!(link_escapes(&links, link, Path::new("../../outside")));
+        assert!(link_escapes(&links, link, Path::new("/etc")));
+    }
+
+    #[test]
+    fn test_link_escapes_through_other_links() {
+        let links = HashMap::from([
+            (PathBuf::from("a/b"), PathBuf::from("../z")),
+            (PathBuf::from("x"), PathBuf::from("a/b/../..")),
+            (PathBuf::from("loop"), PathBuf::from("loop")),
+        ]);
+
+        // `a/b` is `z`, so `a/b/..` is the extraction directory and one more `..` leaves it
+        assert!(!link_escapes(&links, Path::new("a/b"), Path::new("../z")));
+        assert!(link_escapes(&links, Path::new("x"), Path::new("a/b/../..")));
+        assert!(!link_escapes(&links, Path::new("y"), Path::new("a/b/../z")));
+        assert!(link_escapes(&links, Path::new("y"), Path::new("loop")));
+    }
+
+    #[test]
+    fn test_extract_zip_link_target_too_long() {
+        let stage = crate::StageDir::new("plume_stage").unwrap();
+        let archive_path = stage.join("links.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer.add_symlink("Short", "Versions/A", options).unwrap();
+        writer
+            .add_symlink("Long", "a/".repeat(MAX_LINK_TARGET as usize), options)
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert!(matches!(
+            extract_zip(&archive_path, &stage.join("out"), &ExtractLimits::default()),
+            Err(Error::ArchiveLinkTooLong(name)) if name == "Long"
+        ));
+    }
+
+    #[test]
+    fn test_extract_tar_stops_while_writing() {
+        let data = vec![0u8; 4096];
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        let mut builder = tar::Builder::new(Vec::new());
+        builder
+            .append_data(&mut header, "Library/big.bin", data.as_slice())
+            .unwrap();
+        let tar = builder.into_inner().unwrap();
+
+        let stage = crate::StageDir::new("plume_stage").unwrap();
+        let limits = ExtractLimits {
+            max_size: 1024,
+            ..Default::default()
+        };
+        assert!(matches!(
+            extract_tar(tar.as_slice(), stage.path(), &limits),
+            Err(Error::ArchiveTooLarge(1024))
+        ));
+        assert!(fs::metadata(stage.join("Library/big.bin")).unwrap().len() <= 1025);
+    }
+}
@@ -0,0 +1,345 @@
+use std::{collections::HashSet, fmt, path::Path};
+
+use goblin::mach::{Mach, MachO as GoblinMachO, SingleArch};
+use plist::Value;
+use plume_core::MachO;
+
+use crate::{Bundle, BundleType, Error, PlistInfoTrait};
+
+/// Bundles every UIKit app has loaded by the time tweaks are, whether it links them or not.
+const IMPLICIT_BUNDLES: &[&str] = &[
+    "com.apple.UIKit",
+    "com.apple.Foundation",
+    "com.apple.CoreFoundation",
+    "com.apple.CoreGraphics",
+    "com.apple.QuartzCore",
+];
+
+/// `kCFCoreFoundationVersionNumber` of the first release of each iOS version.
+const CORE_FOUNDATION_VERSIONS: &[(u32, f64)] = &[
+    (8, 1140.10),
+    (9, 1240.10),
+    (10, 1348.00),
+    (11, 1443.00),
+    (12, 1556.00),
+    (13, 1665.15),
+    (14, 1740.00),
+    (15, 1854.00),
+];
+
+/// MobileSubstrate filter read from the `.plist` next to a tweak dylib.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TweakFilter {
+    pub bundles: Vec<String>,
+    pub executables: Vec<String>,
+    pub classes: Vec<String>,
+    /// `[min]` or `[min, max]` of `kCFCoreFoundationVersionNumber`.
+    pub core_foundation_version: Option<(f64, Option<f64>)>,
+    /// `Mode = Any` loads when any list matches, otherwise every list present has to.
+    pub match_any: bool,
+}
+
+impl TweakFilter {
+    /// Reads the filter for `dylib_path`, `None` when the dylib has no filter plist.
+    pub fn for_dylib(dylib_path: &Path) -> Result<Option<Self>, Error> {
+        let plist_path = dylib_path.with_extension("plist");
+        if !plist_path.exists() {
+            return Ok(None);
+        }
+
+        // Filters are usually old-style ASCII plists, which `from_file` detects
+        let value = Value::from_file(&plist_path)?;
+        Ok(Self::from_value(&value))
+    }
+
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let filter = value.as_dictionary()?.get("Filter")?.as_dictionary()?;
+
+        let strings = |key: &str| -> Vec<String> {
+            filter
+                .get(key)
+                .and_then(Value::as_array)
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(Value::as_string)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let core_foundation_version = filter
+            .get("CoreFoundationVersion")
+            .and_then(Value::as_array)
+            .and_then(|range| {
+                let min = range.first().and_then(as_number)?;
+                Some((min, range.get(1).and_then(as_number)))
+            });
+
+        Some(Self {
+            bundles: strings("Bundles"),
+            executables: strings("Executables"),
+            classes: strings("Classes"),
+            core_foundation_version,
+            match_any: filter.get("Mode").and_then(Value::as_string) == Some("Any"),
+        })
+    }
+
+    pub fn matches(&self, target: &FilterTarget) -> bool {
+        // The CoreFoundation range always has to hold, but the device is unknown at this point,
+        // so only filters that can't load on the app's minimum OS are ruled out.
+        let max = self.core_foundation_version.and_then(|(_, max)| max);
+        if max
+            .zip(target.minimum_core_foundation_version)
+            .is_some_and(|(max, min_version)| max < min_version)
+        {
+            return false;
+        }
+
+        let lists = [
+            (!self.bundles.is_empty()).then(|| {
+                self.bundles
+                    .iter()
+                    .any(|b| target.bundles.contains(&b.to_lowercase()))
+            }),
+            (!self.executables.is_empty()).then(|| {
+                target
+                    .executable
+                    .as_ref()
+                    .is_some_and(|e| self.executables.contains(e))
+            }),
+            (!self.classes.is_empty())
+                .then(|| self.classes.iter().any(|c| target.classes.contains(c))),
+        ];
+
+        let mut results = lists.into_iter().flatten().peekable();
+        if results.peek().is_none() {
+            // only a CoreFoundation range, which loads everywhere
+            return true;
+        }
+
+        if self.match_any {
+            results.any(|matched| matched)
+        } else {
+            results.all(|matched| matched)
+        }
+    }
+}
+
+impl fmt::Display for TweakFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        for (key, values) in [
+            ("Bundles", &self.bundles),
+            ("Executables", &self.executables),
+            ("Classes", &self.classes),
+        ] {
+            if !values.is_empty() {
+                parts.push(format!("{key}: {}", values.join(", ")));
+            }
+        }
+
+        if let Some((min, max)) = self.core_foundation_version {
+            match max {
+                Some(max) => parts.push(format!("CoreFoundationVersion: {min}-{max}")),
+                None => parts.push(format!("CoreFoundationVersion: >= {min}")),
+            }
+        }
+
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// What a filter is matched against, collected from the app before anything is renamed.
+#[derive(Debug, Clone, Default)]
+pub struct FilterTarget {
+    /// Lowercased identifiers of the app, its frameworks and the system frameworks it links.
+    bundles: HashSet<String>,
+    executable: Option<String>,
+    /// Objective-C classes defined by the app and its embedded frameworks.
+    classes: HashSet<String>,
+    minimum_core_foundation_version: Option<f64>,
+}
+
+impl FilterTarget {
+    /// `bundle_identifier` is passed in since the signer may already have changed the one on disk.
+    pub fn new(app_bundle: &Bundle, bundle_identifier: Option<String>) -> Result<Self, Error> {
+        let mut target = FilterTarget {
+            executable: app_bundle.get_executable(),
+            ..Default::default()
+        };
+
+        target.bundles.extend(bundle_identifier);
+        target
+            .bundles
+            .extend(IMPLICIT_BUNDLES.iter().map(|b| b.to_string()));
+
+        if let Some(executable_path) = app_bundle.executable_path() {
+            let macho = MachO::new(&executable_path)?;
+
+            // `/System/Library/Frameworks/UIKit.framework/UIKit` is `com.apple.UIKit`
+            for load_path in macho.dylib_load_paths()? {
+                if let Some(name) = load_path
+                    .strip_prefix("/System/Library/")
+                    .and_then(|p| p.rsplit('/').next())
+                {
+                    target.bundles.insert(format!("com.apple.{name}"));
+                }
+            }
+
+            target.classes.extend(objc_class_names(macho.data()));
+        }
+
+        let frameworks_dir = app_bundle.bundle_dir().join("Frameworks");
+        for bundle in app_bundle.collect_nested_bundles()? {
+            if *bundle.bundle_type() != BundleType::Framework
+                || bundle.bundle_dir().parent() != Some(frameworks_dir.as_path())
+            {
+                continue;
+            }
+
+            target.bundles.extend(bundle.get_bundle_identifier());
+            if let Some(executable_path) = bundle.executable_path() {
+                target
+                    .classes
+                    .extend(objc_class_names(&std::fs::read(executable_path)?));
+            }
+        }
+
+        target.bundles = target.bundles.iter().map(|b| b.to_lowercase()).collect();
+
+        let info_plist = Value::from_file(app_bundle.bundle_dir().join("Info.plist"))?;
+        target.minimum_core_foundation_version = info_plist
+            .as_dictionary()
+            .and_then(|dict| dict.get("MinimumOSVersion"))
+            .and_then(Value::as_string)
+            .and_then(|v| v.split('.').next()?.parse::<u32>().ok())
+            .and_then(|major| {
+                CORE_FOUNDATION_VERSIONS
+                    .iter()
+                    .rev()
+                    .find(|(version, _)| *version <= major)
+                    .map(|(_, cf)| *cf)
+            });
+
+        Ok(target)
+    }
+}
+
+/// A filtered dylib that wasn't injected because it targets other processes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedTweak {
+    /// File name of the dylib.
+    pub name: String,
+    pub filter: TweakFilter,
+}
+
+impl fmt::Display for SkippedTweak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.filter)
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Real(real) => Some(*real),
+        Value::Integer(integer) => integer.as_signed().map(|i| i as f64),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Names in `__objc_classname` of the first slice.
+fn objc_class_names(data: &[u8]) -> Vec<String> {
+    let macho = match Mach::parse(data) {
+        Ok(Mach::Binary(macho)) => macho,
+        Ok(Mach::Fat(fat)) => match fat.get(0) {
+            Ok(SingleArch::MachO(macho)) => macho,
+            _ => return Vec::new(),
+        },
+        Err(_) => return Vec::new(),
+    };
+
+    section_strings(&macho, "__objc_classname")
+}
+
+fn section_strings(macho: &GoblinMachO, section_name: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+
+    for segment in &macho.segments {
+        let Ok(sections) = segment.sections() else {
+            continue;
+        };
+
+        for (section, data) in sections {
+            if section.name().ok() != Some(section_name) {
+                continue;
+            }
+
+            strings.extend(
+                data.split(|b| *b == 0)
+                    .filter(|s| !s.is_empty())
+                    .filter_map(|s| std::str::from_utf8(s).ok())
+                    .map(str::to_string),
+            );
+        }
+    }
+
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(plist: &str) -> TweakFilter {
+        let value = Value::from_reader(std::io::Cursor::new(plist)).unwrap();
+        TweakFilter::from_value(&value).unwrap()
+    }
+
+    fn target() -> FilterTarget {
+        FilterTarget {
+            bundles: ["com.google.ios.youtube", "com.apple.uikit"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            executable: Some("YouTube".to_string()),
+            classes: ["YTPlayerView".to_string()].into_iter().collect(),
+            minimum_core_foundation_version: Some(1740.00),
+        }
+    }
+
+    #[test]
+    fn test_filter_bundles() {
+        let springboard = filter(r#"{ Filter = { Bundles = ( "com.apple.springboard" ); }; }"#);
+        assert_eq!(springboard.bundles, vec!["com.apple.springboard"]);
+        assert!(!springboard.matches(&target()));
+
+        let uikit = filter(r#"{ Filter = { Bundles = ( "com.apple.UIKit" ); }; }"#);
+        assert!(uikit.matches(&target()));
+    }
+
+    #[test]
+    fn test_filter_mode() {
+        let all = filter(
+            r#"{ Filter = { Bundles = ( "com.google.ios.youtube" ); Executables = ( "SpringBoard" ); }; }"#,
+        );
+        assert!(!all.matches(&target()));
+
+        let any = filter(
+            r#"{ Filter = { Bundles = ( "com.google.ios.youtube" ); Executables = ( "SpringBoard" ); Mode = "Any"; }; }"#,
+        );
+        assert!(any.matches(&target()));
+    }
+
+    #[test]
+    fn test_filter_core_foundation_version() {
+        let old = filter(r#"{ Filter = { CoreFoundationVersion = ( "1140.10", "1556.00" ); }; }"#);
+        assert_eq!(old.core_foundation_version, Some((1140.10, Some(1556.00))));
+        assert!(!old.matches(&target()));
+
+        let open = filter(r#"{ Filter = { CoreFoundationVersion = ( "1140.10" ); }; }"#);
+        assert!(open.matches(&target()));
+    }
+}
@@ -82,11 +82,21 @@ pub mod keys {
     pub const REMOVE_TWEAK: &str = "remove_tweak";
     pub const ADD_BUNDLE: &str = "add_bundle";
     pub const NO_TWEAKS_ADDED: &str = "no_tweaks_added";
+    pub const INJECTED_TWEAKS: &str = "injected_tweaks";
+    pub const MISSING_DEPENDENCY: &str = "missing_dependency";
+    pub const ROOTLESS: &str = "rootless";
+    pub const ROOTFUL: &str = "rootful";
+    pub const TWEAK_RUNTIME: &str = "tweak_runtime";
+    pub const IGNORE_TWEAK_FILTERS: &str = "ignore_tweak_filters";
+    pub const TWEAK_SETTINGS: &str = "tweak_settings";
+    pub const TWEAK_REQUIRED: &str = "tweak_required";
+    pub const CHOOSE_RUNTIME: &str = "choose_runtime";
     pub const REMOVE: &str = "remove";
 
     // Package Screen
     pub const NO_PACKAGE_SELECTED: &str = "no_package_selected";
     pub const GO_BACK_SELECT_FILE: &str = "go_back_select_file";
+    pub const PACKAGE_ENCRYPTED: &str = "package_encrypted";
     pub const MODE: &str = "mode";
     pub const SELECT_MODE: &str = "select_mode";
     pub const SIGNING_METHOD: &str = "signing_method";
@@ -97,6 +107,7 @@ pub mod keys {
     // Tweak File Dialog
     pub const TWEAK_FILES: &str = "tweak_files";
     pub const SELECT_TWEAK_FILE: &str = "select_tweak_file";
+    pub const SELECT_RUNTIME_FILE: &str = "select_runtime_file";
     pub const SELECT_BUNDLE_FOLDER: &str = "select_bundle_folder";
 
     // Settings Screen
@@ -106,6 +117,32 @@ pub mod keys {
     pub const EXPORT_P12: &str = "export_p12";
     pub const LANGUAGE: &str = "language";
     pub const SELECT_LANGUAGE: &str = "select_language";
+    pub const NETWORK: &str = "network";
+    pub const CONFIGURE: &str = "configure";
+    pub const PROXY: &str = "proxy";
+    pub const PROXY_USERNAME: &str = "proxy_username";
+    pub const PROXY_PASSWORD: &str = "proxy_password";
+    pub const PROXY_PASSWORD_NOT_SAVED: &str = "proxy_password_not_saved";
+    pub const CA_CERTIFICATES: &str = "ca_certificates";
+    pub const ACCEPT_INVALID_CERTS: &str = "accept_invalid_certs";
+    pub const ADD_CERTIFICATE: &str = "add_certificate";
+    pub const CERTIFICATE_FILES: &str = "certificate_files";
+    pub const CONNECT_TIMEOUT: &str = "connect_timeout";
+    pub const REQUEST_TIMEOUT: &str = "request_timeout";
+    pub const SAVE: &str = "save";
+    pub const ERROR_INVALID_TIMEOUT: &str = "error_invalid_timeout";
+    pub const ERROR_SAVING_NETWORK: &str = "error_saving_network";
+    pub const APP_IDS: &str = "app_ids";
+    pub const MANAGE: &str = "manage";
+    pub const LOADING_APP_IDS: &str = "loading_app_ids";
+    pub const NO_APP_IDS: &str = "no_app_ids";
+    pub const DELETE_SELECTED: &str = "delete_selected";
+    pub const CONFIRM_DELETE_APP_IDS: &str = "confirm_delete_app_ids";
+    pub const DELETE: &str = "delete";
+    pub const APP_GROUPS_LIST: &str = "app_groups_list";
+    pub const CREATED_DAYS_AGO: &str = "created_days_ago";
+    pub const ERROR_LOADING_APP_IDS: &str = "error_loading_app_ids";
+    pub const ERROR_DELETING_APP_IDS: &str = "error_deleting_app_ids";
     pub const LOADING_ACCOUNTS: &str = "loading_accounts";
     pub const NO_ACCOUNTS_ADDED: &str = "no_accounts_added";
 
@@ -275,11 +312,21 @@ impl I18n {
             keys::REMOVE_TWEAK => "Remove Tweak",
             keys::ADD_BUNDLE => "Add Bundle",
             keys::NO_TWEAKS_ADDED => "No tweaks added",
+            keys::INJECTED_TWEAKS => "Injected tweaks (checked ones are removed)",
+            keys::MISSING_DEPENDENCY => "{0} requires {1}, which is not in the tweak list",
+            keys::ROOTLESS => "rootless",
+            keys::ROOTFUL => "rootful",
+            keys::TWEAK_RUNTIME => "Runtime",
+            keys::IGNORE_TWEAK_FILTERS => "Inject tweaks that target other apps",
+            keys::TWEAK_SETTINGS => "Tweak settings",
+            keys::TWEAK_REQUIRED => "Required",
+            keys::CHOOSE_RUNTIME => "Choose...",
             keys::REMOVE => "Remove",
 
             // Package Screen
             keys::NO_PACKAGE_SELECTED => "No package selected",
             keys::GO_BACK_SELECT_FILE => "Go back and select a file",
+            keys::PACKAGE_ENCRYPTED => "App is encrypted, decrypt it before signing",
             keys::MODE => "Mode:",
             keys::SELECT_MODE => "Select mode",
             keys::SIGNING_METHOD => "Signing:",
@@ -290,6 +337,7 @@ impl I18n {
             // Tweak File Dialog
             keys::TWEAK_FILES => "Tweak files",
             keys::SELECT_TWEAK_FILE => "Select Tweak File",
+            keys::SELECT_RUNTIME_FILE => "Select ElleKit, Substitute or libhooker build",
             keys::SELECT_BUNDLE_FOLDER => "Select Bundle Folder",
 
             // Settings Screen
@@ -299,6 +347,34 @@ impl I18n {
             keys::EXPORT_P12 => "Export P12",
             keys::LANGUAGE => "Language",
             keys::SELECT_LANGUAGE => "Select Language",
+            keys::NETWORK => "Network",
+            keys::CONFIGURE => "Configure",
+            keys::PROXY => "Proxy URL",
+            keys::PROXY_USERNAME => "Proxy username",
+            keys::PROXY_PASSWORD => "Proxy password",
+            keys::PROXY_PASSWORD_NOT_SAVED => "Not saved to disk, or use PLUME_PROXY_PASSWORD",
+            keys::CA_CERTIFICATES => "Extra CA certificates",
+            keys::ACCEPT_INVALID_CERTS => "Skip certificate validation (debugging only)",
+            keys::ADD_CERTIFICATE => "Add Certificate",
+            keys::CERTIFICATE_FILES => "Certificates",
+            keys::CONNECT_TIMEOUT => "Connect timeout (s)",
+            keys::REQUEST_TIMEOUT => "Request timeout (s)",
+            keys::SAVE => "Save",
+            keys::ERROR_INVALID_TIMEOUT => "Timeouts must be a whole number of seconds",
+            keys::ERROR_SAVING_NETWORK => "Failed to save network settings: {0}",
+            keys::APP_IDS => "App IDs",
+            keys::MANAGE => "Manage",
+            keys::LOADING_APP_IDS => "Loading App IDs...",
+            keys::NO_APP_IDS => "No App IDs registered",
+            keys::DELETE_SELECTED => "Delete Selected",
+            keys::CONFIRM_DELETE_APP_IDS => {
+                "Delete {0} App IDs? Apps signed with them can't be refreshed afterwards."
+            }
+            keys::DELETE => "Delete",
+            keys::APP_GROUPS_LIST => "app groups: {0}",
+            keys::CREATED_DAYS_AGO => "created {0} days ago",
+            keys::ERROR_LOADING_APP_IDS => "Failed to load App IDs: {0}",
+            keys::ERROR_DELETING_APP_IDS => "Failed to delete App IDs: {0}",
             keys::LOADING_ACCOUNTS => "Loading accounts...",
             keys::NO_ACCOUNTS_ADDED => "No accounts added yet",
 
@@ -389,11 +465,21 @@ impl I18n {
             keys::REMOVE_TWEAK => "移除 Tweak",
             keys::ADD_BUNDLE => "添加 Bundle",
             keys::NO_TWEAKS_ADDED => "未添加 Tweak",
+            keys::INJECTED_TWEAKS => "已注入的 Tweak（勾选的将被移除）",
+            keys::MISSING_DEPENDENCY => "{0} 依赖 {1}，但它不在插件列表中",
+            keys::ROOTLESS => "无根",
+            keys::ROOTFUL => "有根",
+            keys::TWEAK_RUNTIME => "注入运行时",
+            keys::IGNORE_TWEAK_FILTERS => "注入针对其他应用的插件",
+            keys::TWEAK_SETTINGS => "插件设置",
+            keys::TWEAK_REQUIRED => "必需",
+            keys::CHOOSE_RUNTIME => "选择...",
             keys::REMOVE => "移除",
 
             // Package Screen
             keys::NO_PACKAGE_SELECTED => "未选择包",
             keys::GO_BACK_SELECT_FILE => "返回并选择文件",
+            keys::PACKAGE_ENCRYPTED => "此应用已加密，启动时会闪退，请先解密",
             keys::MODE => "模式：",
             keys::SELECT_MODE => "选择模式",
             keys::SIGNING_METHOD => "签名：",
@@ -404,6 +490,7 @@ impl I18n {
             // Tweak File Dialog
             keys::TWEAK_FILES => "Tweak 文件",
             keys::SELECT_TWEAK_FILE => "选择 Tweak 文件",
+            keys::SELECT_RUNTIME_FILE => "选择 ElleKit、Substitute 或 libhooker",
             keys::SELECT_BUNDLE_FOLDER => "选择 Bundle 文件夹",
 
             // Settings Screen
@@ -413,6 +500,34 @@ impl I18n {
             keys::EXPORT_P12 => "导出 P12",
             keys::LANGUAGE => "语言",
             keys::SELECT_LANGUAGE => "选择语言",
+            keys::NETWORK => "网络",
+            keys::CONFIGURE => "配置",
+            keys::PROXY => "代理地址",
+            keys::PROXY_USERNAME => "代理用户名",
+            keys::PROXY_PASSWORD => "代理密码",
+            keys::PROXY_PASSWORD_NOT_SAVED => "不会保存到磁盘，也可使用 PLUME_PROXY_PASSWORD",
+            keys::CA_CERTIFICATES => "额外的 CA 证书",
+            keys::ACCEPT_INVALID_CERTS => "跳过证书验证（仅用于调试）",
+            keys::ADD_CERTIFICATE => "添加证书",
+            keys::CERTIFICATE_FILES => "证书",
+            keys::CONNECT_TIMEOUT => "连接超时（秒）",
+            keys::REQUEST_TIMEOUT => "请求超时（秒）",
+            keys::SAVE => "保存",
+            keys::ERROR_INVALID_TIMEOUT => "超时必须为整数秒",
+            keys::ERROR_SAVING_NETWORK => "保存网络设置失败：{0}",
+            keys::APP_IDS => "App ID",
+            keys::MANAGE => "管理",
+            keys::LOADING_APP_IDS => "正在加载 App ID...",
+            keys::NO_APP_IDS => "没有已注册的 App ID",
+            keys::DELETE_SELECTED => "删除所选",
+            keys::CONFIRM_DELETE_APP_IDS => {
+                "删除 {0} 个 App ID？使用它们签名的应用之后将无法续签。"
+            }
+            keys::DELETE => "删除",
+            keys::APP_GROUPS_LIST => "应用组：{0}",
+            keys::CREATED_DAYS_AGO => "{0} 天前创建",
+            keys::ERROR_LOADING_APP_IDS => "加载 App ID 失败：{0}",
+            keys::ERROR_DELETING_APP_IDS => "删除 App ID 失败：{0}",
             keys::LOADING_ACCOUNTS => "正在加载账户...",
             keys::NO_ACCOUNTS_ADDED => "尚未添加账户",
 
@@ -1,15 +1,26 @@
 mod bundle;
+mod cleanup;
+mod deb;
 mod device;
+mod extract;
+mod filter;
 mod i18n;
 mod options;
 mod package;
+mod preferences;
+mod registration;
 mod signer;
+mod stage;
 mod tweak;
 
 use std::path::Path;
 
 pub use bundle::{Bundle, BundleType}; // Bundle helper
+pub use cleanup::unused_app_ids; // Developer portal cleanup
+pub use deb::{DebControl, missing_dependencies}; // Tweak package metadata
 pub use device::{Device, get_device_for_id, install_app_mac}; // Device helper
+pub use extract::ExtractLimits; // Archive extraction bounds
+pub use filter::{FilterTarget, SkippedTweak, TweakFilter}; // MobileSubstrate filters
 pub use i18n::{Language, get_language, set_language, t, tf}; // i18n support
 pub use options::{
     SignerApp, // Supported app types
@@ -19,10 +30,16 @@ pub use options::{
     SignerInstallMode, // Installation mode
     SignerMode,        // Signing mode
     SignerOptions,     // Main
+    SignerRuntime,     // Tweak injection runtime
+    TweakInjection,    // Per tweak load targets
+    TweakSettings,     // Tweak PreferenceLoader handling
+    TweakTarget,
 };
 pub use package::Package; // Package helper
+pub use registration::{BundleRegistration, RegistrationOutcome, RegistrationReport}; // Developer portal registration
 pub use signer::Signer; // Signer
-pub use tweak::Tweak; // Tweak helper
+pub use stage::{StageDir, set_stage_root, stage_root, sweep_stale_stages}; // Scratch directories
+pub use tweak::{InjectedTweak, InstalledRuntime, Tweak}; // Tweak helper
 
 use thiserror::Error as ThisError;
 #[derive(Debug, ThisError)]
@@ -32,6 +49,8 @@ pub enum Error {
     // Device
     #[error("Bundle failed to rename, make sure its available: {0}")]
     BundleFailedToCopy(String),
+    #[error("Bundle contains encrypted binaries, decrypt the app first: {}", .0.join(", "))]
+    BundleEncrypted(Vec<String>),
     // Tweak
     #[error("Invalid tweak file path")]
     TweakInvalidPath,
@@ -39,6 +58,21 @@ pub enum Error {
     TweakExtractionFailed(String),
     #[error("Unsupported file type: {0}")]
     UnsupportedFileType(String),
+    #[error("{0} links {}, which the app doesn't provide", .1.join(", "))]
+    TweakUnresolvedDependencies(String, Vec<String>),
+    #[error("No bundle in the app matches tweak target: {0}")]
+    TweakTargetNotFound(String),
+    // Archives
+    #[error("Archive entry escapes the extraction directory: {0}")]
+    ArchivePathTraversal(String),
+    #[error("Archive link points outside the extraction directory: {0}")]
+    ArchiveLinkEscapes(String),
+    #[error("Archive link target is too long: {0}")]
+    ArchiveLinkTooLong(String),
+    #[error("Archive unpacks to more than {0} bytes")]
+    ArchiveTooLarge(u64),
+    #[error("Archive has more than {0} entries")]
+    ArchiveTooManyEntries(usize),
 
     #[error("Zip error: {0}")]
     Zip(#[from] zip::result::ZipError),
@@ -1,3 +1,7 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::ExtractLimits;
+
 /// Settings for the signer process.
 #[derive(Clone, Debug)]
 pub struct SignerOptions {
@@ -16,7 +20,29 @@ pub struct SignerOptions {
     /// Installation mode.
     pub install_mode: SignerInstallMode,
     /// Tweaks to apply before signing.
-    pub tweaks: Option<Vec<std::path::PathBuf>>,
+    pub tweaks: Option<Vec<PathBuf>>,
+    /// Where each tweak is loaded from, tweaks without an entry use [`TweakInjection::default`].
+    pub tweak_injection: HashMap<PathBuf, TweakInjection>,
+    /// Hooking runtime injected alongside tweaks.
+    pub runtime: SignerRuntime,
+    /// Inject tweak dylibs even when their filter plist targets other apps.
+    pub ignore_tweak_filters: bool,
+    /// Where the settings of tweaks shipping PreferenceLoader entries end up.
+    pub tweak_settings: TweakSettings,
+    /// Bounds for unpacking tweak archives.
+    pub extract_limits: ExtractLimits,
+    /// Previously injected tweaks to remove before signing, by name (see [`crate::InjectedTweak`]).
+    pub remove_tweaks: Option<Vec<String>>,
+    /// Bundles registered with the developer portal at the same time.
+    pub register_concurrency: usize,
+    /// Delete the App IDs and app groups a failed registration created. `None` only rolls
+    /// back for paid teams, deleting App IDs doesn't give a free team its quota back.
+    pub rollback_registration: Option<bool>,
+    /// Managed capabilities Apple granted the team, enabled like any other capability.
+    pub granted_capabilities: Vec<String>,
+    /// App IDs the team can still create, [`crate::Signer::register_bundle`] warns when it
+    /// needs more. Only checked for free teams, `None` skips the check.
+    pub app_id_budget: Option<usize>,
     /// App type.
     pub app: SignerApp,
 }
@@ -32,6 +58,16 @@ impl Default for SignerOptions {
             mode: SignerMode::default(),
             install_mode: SignerInstallMode::default(),
             tweaks: None,
+            tweak_injection: HashMap::new(),
+            runtime: SignerRuntime::default(),
+            ignore_tweak_filters: false,
+            tweak_settings: TweakSettings::default(),
+            extract_limits: ExtractLimits::default(),
+            remove_tweaks: None,
+            register_concurrency: 3,
+            rollback_registration: None,
+            granted_capabilities: Vec::new(),
+            app_id_budget: None,
             app: SignerApp::Default,
         }
     }
@@ -116,6 +152,113 @@ impl std::fmt::Display for SignerMode {
     }
 }
 
+/// Hooking runtime injected alongside tweaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerRuntime {
+    /// ElleKit shipped with Impactor.
+    ElleKit,
+    /// User supplied ElleKit, Substitute or libhooker build (`.deb`, `.dylib` or `.framework`).
+    Custom(PathBuf),
+    /// No runtime, for self-contained dylibs.
+    None,
+}
+
+impl Default for SignerRuntime {
+    fn default() -> Self {
+        SignerRuntime::ElleKit
+    }
+}
+
+impl std::fmt::Display for SignerRuntime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignerRuntime::ElleKit => write!(f, "ElleKit"),
+            SignerRuntime::Custom(path) => write!(
+                f,
+                "{}",
+                path.file_name()
+                    .map(|n| n.to_string_lossy())
+                    .unwrap_or_default()
+            ),
+            SignerRuntime::None => write!(f, "None"),
+        }
+    }
+}
+
+/// What happens to a tweak's `PreferenceLoader` entries and `PreferenceBundles`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TweakSettings {
+    /// Copied to `PreferenceLoader/` and `PreferenceBundles/` in the app, for tweaks
+    /// and loaders that show them from inside the app.
+    InApp,
+    /// Also added as child panes of the app's `Settings.bundle`, shown in the Settings app.
+    ///
+    /// Only standard specifiers carry over and values are stored in the app's own defaults.
+    SettingsBundle,
+    /// Left out, the tweak runs with its default settings.
+    None,
+}
+
+impl Default for TweakSettings {
+    fn default() -> Self {
+        TweakSettings::InApp
+    }
+}
+
+impl std::fmt::Display for TweakSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TweakSettings::InApp => write!(f, "In app"),
+            TweakSettings::SettingsBundle => write!(f, "Settings.bundle"),
+            TweakSettings::None => write!(f, "None"),
+        }
+    }
+}
+
+/// Which binaries load a tweak, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TweakInjection {
+    pub target: TweakTarget,
+    /// `LC_LOAD_WEAK_DYLIB` lets the app launch without the tweak, `LC_LOAD_DYLIB` requires it.
+    pub weak: bool,
+}
+
+impl Default for TweakInjection {
+    fn default() -> Self {
+        TweakInjection {
+            target: TweakTarget::default(),
+            weak: true,
+        }
+    }
+}
+
+/// Binaries a tweak gets a load command in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TweakTarget {
+    /// The app's main executable.
+    MainApp,
+    /// Every app extension in `PlugIns/`, but not the app itself.
+    AllExtensions,
+    /// Bundles by directory name (`Share.appex`, `Core.framework`) or bundle identifier.
+    Bundles(Vec<String>),
+}
+
+impl Default for TweakTarget {
+    fn default() -> Self {
+        TweakTarget::MainApp
+    }
+}
+
+impl std::fmt::Display for TweakTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TweakTarget::MainApp => write!(f, "Main app"),
+            TweakTarget::AllExtensions => write!(f, "All extensions"),
+            TweakTarget::Bundles(bundles) => write!(f, "{}", bundles.join(", ")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SignerAppReal {
     pub app: SignerApp,
@@ -1,30 +1,31 @@
 use super::{Bundle, PlistInfoTrait};
-use crate::{Error, SignerApp, SignerOptions};
+use crate::extract::extract_zip;
+use crate::tweak::{InjectedTweak, detect_injected};
+use crate::{Error, ExtractLimits, SignerApp, SignerOptions, StageDir};
 use plist::Dictionary;
+use plume_core::MachO;
 use std::path::PathBuf;
-use std::{env, fs, io::Read};
-use uuid::Uuid;
+use std::sync::Arc;
+use std::{fs, io::Read};
 use zip::ZipArchive;
 use zip::write::FileOptions;
 
 #[derive(Debug, Clone)]
 pub struct Package {
     package_file: PathBuf,
-    stage_dir: PathBuf,
+    /// Shared by clones, removed once the last one is dropped.
+    stage: Arc<StageDir>,
     stage_payload_dir: PathBuf,
     info_plist_dictionary: Dictionary,
     archive_entries: Vec<String>,
+    extract_limits: ExtractLimits,
 }
 
 impl Package {
     pub fn new(package_file: PathBuf) -> Result<Self, Error> {
-        let stage_dir = env::temp_dir().join(format!(
-            "plume_stage_{:08}",
-            Uuid::new_v4().to_string().to_uppercase()
-        ));
-        let out_package_file = stage_dir.join("stage.ipa");
+        let stage = StageDir::new("plume_stage")?;
+        let out_package_file = stage.join("stage.ipa");
 
-        fs::create_dir_all(&stage_dir).ok();
         fs::copy(&package_file, &out_package_file)?;
 
         let file = fs::File::open(&out_package_file)?;
@@ -38,13 +39,20 @@ impl Package {
 
         Ok(Self {
             package_file: out_package_file,
-            stage_dir: stage_dir.clone(),
-            stage_payload_dir: stage_dir.join("Payload"),
+            stage_payload_dir: stage.join("Payload"),
+            stage: Arc::new(stage),
             info_plist_dictionary,
             archive_entries,
+            extract_limits: ExtractLimits::default(),
         })
     }
 
+    /// Bounds for unpacking the package in [`Package::get_package_bundle`].
+    pub fn with_extract_limits(mut self, extract_limits: ExtractLimits) -> Self {
+        self.extract_limits = extract_limits;
+        self
+    }
+
     pub fn package_file(&self) -> &PathBuf {
         &self.package_file
     }
@@ -72,10 +80,82 @@ impl Package {
         Ok(plist::from_bytes(&plist_data)?)
     }
 
+    fn executable_entry(&self) -> Option<&String> {
+        let executable = self.get_executable()?;
+
+        self.archive_entries.iter().find(|entry| {
+            entry.starts_with("Payload/")
+                && entry.matches('/').count() == 2
+                && entry.ends_with(&format!(".app/{executable}"))
+        })
+    }
+
+    fn read_archive_entry(
+        archive: &mut ZipArchive<fs::File>,
+        entry: &str,
+    ) -> Result<Vec<u8>, Error> {
+        let mut file = archive.by_name(entry)?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Checks the main executable inside the archive for FairPlay encryption,
+    /// without extracting the whole package.
+    pub fn is_encrypted(&self) -> Result<bool, Error> {
+        let Some(executable_entry) = self.executable_entry() else {
+            return Ok(false);
+        };
+
+        let mut archive = ZipArchive::new(fs::File::open(&self.package_file)?)?;
+        let executable_data = Self::read_archive_entry(&mut archive, executable_entry)?;
+
+        Ok(MachO::is_encrypted_bytes(&executable_data)?)
+    }
+
+    /// Lists tweaks injected into the main executable and its extensions,
+    /// without extracting the whole package.
+    pub fn injected_tweaks(&self) -> Result<Vec<InjectedTweak>, Error> {
+        let (Some(executable_entry), Some(executable)) =
+            (self.executable_entry(), self.get_executable())
+        else {
+            return Ok(Vec::new());
+        };
+        let app_prefix = &executable_entry[..=executable_entry.rfind('/').unwrap_or(0)];
+
+        let mut archive = ZipArchive::new(fs::File::open(&self.package_file)?)?;
+
+        detect_injected(&executable, &self.app_extension_names(), |relative_path| {
+            Self::read_archive_entry(&mut archive, &format!("{app_prefix}{relative_path}")).ok()
+        })
+    }
+
+    /// Directory names of the app extensions in `PlugIns/`, without extracting the package.
+    pub fn app_extension_names(&self) -> Vec<String> {
+        let mut names = self
+            .archive_entries
+            .iter()
+            .filter_map(|entry| {
+                // Payload/App.app/PlugIns/Share.appex/...
+                let mut parts = entry.split('/');
+                match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                    (Some("Payload"), Some(_), Some("PlugIns"), Some(name))
+                        if name.ends_with(".appex") =>
+                    {
+                        Some(name.to_string())
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        names.sort();
+        names.dedup();
+        names
+    }
+
     pub fn get_package_bundle(&self) -> Result<Bundle, Error> {
-        let file = fs::File::open(&self.package_file)?;
-        let mut archive = ZipArchive::new(file)?;
-        archive.extract(&self.stage_dir)?;
+        extract_zip(&self.package_file, self.stage.path(), &self.extract_limits)?;
 
         let app_dir = fs::read_dir(&self.stage_payload_dir)?
             .filter_map(Result::ok)
@@ -95,7 +175,7 @@ impl Package {
     }
 
     fn archive_package_bundle(self) -> Result<PathBuf, Error> {
-        let zip_file_path = self.stage_dir.join("resigned.ipa");
+        let zip_file_path = self.stage.join("resigned.ipa");
         let file = fs::File::create(&zip_file_path)?;
         let mut zip = zip::ZipWriter::new(file);
 
@@ -142,14 +222,20 @@ impl Package {
             Ok(())
         }
 
-        add_dir_to_zip(&mut zip, &payload_dir, &self.stage_dir, &options)?;
+        add_dir_to_zip(
+            &mut zip,
+            &payload_dir,
+            &self.stage.path().to_path_buf(),
+            &options,
+        )?;
         zip.finish()?;
 
         Ok(zip_file_path)
     }
 
+    /// Removes the stage right away instead of when the last clone is dropped.
     pub fn remove_package_stage(self) {
-        fs::remove_dir_all(&self.stage_dir).ok();
+        fs::remove_dir_all(self.stage.path()).ok();
     }
 }
 
@@ -0,0 +1,238 @@
+use std::path::Path;
+
+use plist::{Dictionary, Value};
+
+use crate::Error;
+
+/// Plist a tweak drops in `Library/PreferenceLoader/Preferences/`, the row it gets in Settings.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PreferenceEntry {
+    pub label: String,
+    /// `.bundle` in `PreferenceBundles/` the row opens, `None` for plist-only entries.
+    pub bundle: Option<String>,
+    /// Specifiers of plist-only entries.
+    pub items: Vec<Value>,
+}
+
+impl PreferenceEntry {
+    pub fn from_file(path: &Path) -> Result<Option<Self>, Error> {
+        let fallback_label = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Ok(Self::from_value(&Value::from_file(path)?, &fallback_label))
+    }
+
+    pub fn from_value(value: &Value, fallback_label: &str) -> Option<Self> {
+        let dict = value.as_dictionary()?;
+        let entry = dict.get("entry")?.as_dictionary()?;
+
+        let label = entry
+            .get("label")
+            .or_else(|| dict.get("title"))
+            .and_then(Value::as_string)
+            .unwrap_or(fallback_label)
+            .to_string();
+
+        Some(Self {
+            label,
+            bundle: entry
+                .get("bundle")
+                .and_then(Value::as_string)
+                .map(str::to_string),
+            items: dict
+                .get("items")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Specifiers of the entry, read from its preference bundle's `Root.plist` when it has one.
+    pub fn specifiers(&self, bundles_dir: &Path) -> Result<Vec<Value>, Error> {
+        let Some(bundle) = &self.bundle else {
+            return Ok(self.items.clone());
+        };
+
+        let bundle_dir = bundles_dir.join(format!("{bundle}.bundle"));
+        for name in ["Root.plist", &format!("{bundle}.plist")] {
+            let path = bundle_dir.join(name);
+            if !path.exists() {
+                continue;
+            }
+
+            let value = Value::from_file(&path)?;
+            return Ok(value
+                .as_dictionary()
+                .and_then(|dict| dict.get("items"))
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default());
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// Name of the child pane plist in `Settings.bundle`.
+    fn pane_name(&self) -> String {
+        self.bundle
+            .clone()
+            .unwrap_or_else(|| self.label.clone())
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            .collect()
+    }
+}
+
+/// Adds the entry as a child pane of the app's `Settings.bundle`, creating the bundle if needed.
+///
+/// Returns how many specifiers have no `Settings.bundle` equivalent and were left out.
+pub(crate) fn merge_into_settings_bundle(
+    app_dir: &Path,
+    entry: &PreferenceEntry,
+    specifiers: &[Value],
+) -> Result<usize, Error> {
+    let settings_dir = app_dir.join("Settings.bundle");
+    std::fs::create_dir_all(&settings_dir)?;
+
+    let converted: Vec<Value> = specifiers.iter().filter_map(settings_specifier).collect();
+    let skipped = specifiers.len() - converted.len();
+    let pane_name = entry.pane_name();
+
+    let mut pane = Dictionary::new();
+    pane.insert("Title".into(), entry.label.clone().into());
+    pane.insert("PreferenceSpecifiers".into(), Value::Array(converted));
+    Value::Dictionary(pane).to_file_xml(settings_dir.join(format!("{pane_name}.plist")))?;
+
+    let root_path = settings_dir.join("Root.plist");
+    let mut root = if root_path.exists() {
+        Value::from_file(&root_path)?
+            .into_dictionary()
+            .unwrap_or_default()
+    } else {
+        Dictionary::new()
+    };
+
+    let mut root_specifiers = root
+        .remove("PreferenceSpecifiers")
+        .and_then(Value::into_array)
+        .unwrap_or_default();
+    root_specifiers.retain(|specifier| {
+        specifier
+            .as_dictionary()
+            .and_then(|dict| dict.get("File"))
+            .and_then(Value::as_string)
+            != Some(pane_name.as_str())
+    });
+
+    let mut child = Dictionary::new();
+    child.insert("Type".into(), "PSChildPaneSpecifier".into());
+    child.insert("Title".into(), entry.label.clone().into());
+    child.insert("File".into(), pane_name.into());
+    root_specifiers.push(Value::Dictionary(child));
+
+    root.insert("PreferenceSpecifiers".into(), Value::Array(root_specifiers));
+    Value::Dictionary(root).to_file_xml(&root_path)?;
+
+    Ok(skipped)
+}
+
+/// Converts a Preferences.framework specifier to its `Settings.bundle` counterpart.
+///
+/// Cells backed by code (buttons, links to controllers) have none.
+fn settings_specifier(value: &Value) -> Option<Value> {
+    let dict = value.as_dictionary()?;
+    let cell = dict.get("cell").and_then(Value::as_string)?;
+
+    let mut specifier = Dictionary::new();
+    let copy = |specifier: &mut Dictionary, from: &str, to: &str| {
+        if let Some(value) = dict.get(from) {
+            specifier.insert(to.into(), value.clone());
+        }
+    };
+
+    copy(&mut specifier, "label", "Title");
+    let kind = match cell {
+        "PSGroupCell" | "PSStaticTextCell" => {
+            copy(&mut specifier, "footerText", "FooterText");
+            "PSGroupSpecifier"
+        }
+        "PSSwitchCell" => "PSToggleSwitchSpecifier",
+        "PSEditTextCell" => "PSTextFieldSpecifier",
+        "PSSecureEditTextCell" => {
+            specifier.insert("IsSecure".into(), true.into());
+            "PSTextFieldSpecifier"
+        }
+        "PSSliderCell" => {
+            copy(&mut specifier, "min", "MinimumValue");
+            copy(&mut specifier, "max", "MaximumValue");
+            "PSSliderSpecifier"
+        }
+        "PSLinkListCell" | "PSSegmentCell" if dict.contains_key("validValues") => {
+            copy(&mut specifier, "validTitles", "Titles");
+            copy(&mut specifier, "validValues", "Values");
+            "PSMultiValueSpecifier"
+        }
+        _ => return None,
+    };
+
+    if kind != "PSGroupSpecifier" {
+        // Everything but groups stores a value
+        dict.get("key")?;
+        copy(&mut specifier, "key", "Key");
+        copy(&mut specifier, "default", "DefaultValue");
+    }
+
+    specifier.insert("Type".into(), kind.into());
+    Some(Value::Dictionary(specifier))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(plist: &str) -> Value {
+        Value::from_reader(std::io::Cursor::new(plist)).unwrap()
+    }
+
+    #[test]
+    fn test_preference_entry() {
+        let entry = PreferenceEntry::from_value(
+            &value(
+                r#"{ entry = { bundle = "TweakPrefs"; cell = PSLinkCell; label = "Tweak"; }; }"#,
+            ),
+            "fallback",
+        )
+        .unwrap();
+        assert_eq!(entry.label, "Tweak");
+        assert_eq!(entry.bundle.as_deref(), Some("TweakPrefs"));
+        assert_eq!(entry.pane_name(), "TweakPrefs");
+    }
+
+    #[test]
+    fn test_settings_specifier() {
+        let switch = settings_specifier(&value(
+            r#"{ cell = PSSwitchCell; label = "Enabled"; key = "enabled"; default = 1; }"#,
+        ))
+        .unwrap();
+        let switch = switch.as_dictionary().unwrap();
+        assert_eq!(
+            switch.get("Type").and_then(Value::as_string),
+            Some("PSToggleSwitchSpecifier")
+        );
+        assert_eq!(
+            switch.get("Key").and_then(Value::as_string),
+            Some("enabled")
+        );
+
+        // No key to store the value under
+        assert!(
+            settings_specifier(&value(r#"{ cell = PSSwitchCell; label = "Enabled"; }"#)).is_none()
+        );
+        assert!(
+            settings_specifier(&value(r#"{ cell = PSButtonCell; action = "respring"; }"#))
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::{fmt, path::PathBuf};
+
+/// What [`crate::Signer::register_bundle`] did for every bundle it registers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistrationReport {
+    pub bundles: Vec<BundleRegistration>,
+    /// Resources created during the run that rolling back failed to delete.
+    pub leftovers: Vec<String>,
+    /// Set when the run needed more new App IDs than [`crate::SignerOptions::app_id_budget`].
+    pub quota_warning: Option<String>,
+}
+
+impl RegistrationReport {
+    /// Capabilities left out of any bundle, each listed once.
+    pub fn skipped_capabilities(&self) -> Vec<String> {
+        let mut skipped = Vec::new();
+        for capability in self.bundles.iter().flat_map(|b| &b.skipped_capabilities) {
+            if !skipped.contains(capability) {
+                skipped.push(capability.clone());
+            }
+        }
+        skipped
+    }
+
+    /// IDs of the App IDs the registration created, rolled back ones included.
+    pub fn created_app_ids(&self) -> impl Iterator<Item = &str> {
+        self.bundles
+            .iter()
+            .filter_map(|b| b.created_app_id.as_deref())
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.bundles
+            .iter()
+            .all(|b| b.outcome == RegistrationOutcome::Registered)
+    }
+}
+
+impl fmt::Display for RegistrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(warning) = &self.quota_warning {
+            writeln!(f, "warning: {warning}")?;
+        }
+        for bundle in &self.bundles {
+            writeln!(f, "{bundle}")?;
+        }
+        for leftover in &self.leftovers {
+            writeln!(f, "left behind: {leftover}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleRegistration {
+    pub bundle_dir: PathBuf,
+    /// `None` when the bundle has no identifier to register.
+    pub identifier: Option<String>,
+    pub outcome: RegistrationOutcome,
+    /// App ID this run created, `None` when it already existed.
+    pub created_app_id: Option<String>,
+    /// App groups this run created, by their portal ID.
+    pub created_app_groups: Vec<String>,
+    /// Capabilities the entitlements ask for that the team can't enable, by their ID.
+    pub skipped_capabilities: Vec<String>,
+}
+
+impl BundleRegistration {
+    pub(crate) fn new(bundle_dir: PathBuf, identifier: Option<String>) -> Self {
+        Self {
+            bundle_dir,
+            identifier,
+            outcome: RegistrationOutcome::NotAttempted,
+            created_app_id: None,
+            created_app_groups: Vec::new(),
+            skipped_capabilities: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for BundleRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .identifier
+            .clone()
+            .unwrap_or_else(|| self.bundle_dir.display().to_string());
+        write!(f, "{name}: {}", self.outcome)?;
+        if !self.skipped_capabilities.is_empty() {
+            write!(
+                f,
+                " (capabilities not available to this team: {})",
+                self.skipped_capabilities.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistrationOutcome {
+    /// App ID, capabilities, app groups and profile are in place.
+    Registered,
+    Failed(String),
+    /// Another bundle failed before this one started.
+    NotAttempted,
+    /// Registered, or partly, and undone because another bundle failed.
+    RolledBack,
+}
+
+impl fmt::Display for RegistrationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistrationOutcome::Registered => write!(f, "registered"),
+            RegistrationOutcome::Failed(e) => write!(f, "failed: {e}"),
+            RegistrationOutcome::NotAttempted => write!(f, "not attempted"),
+            RegistrationOutcome::RolledBack => write!(f, "rolled back"),
+        }
+    }
+}
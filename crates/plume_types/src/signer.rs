@@ -1,19 +1,28 @@
-use futures::future::try_join_all;
+use futures::{StreamExt, stream};
 use plist::Value;
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::fs;
 
 use plume_core::{
     CertificateIdentity, MobileProvision, SettingsScope, SigningSettings, UnifiedSigner,
-    developer::DeveloperSession,
+    developer::{DeveloperSession, qh::teams::TeamType, v1::capabilities::CapabilityPolicy},
 };
 
-use crate::{Bundle, BundleType, Error, PlistInfoTrait, SignerApp, SignerMode, SignerOptions};
+use crate::{
+    Bundle, BundleRegistration, BundleType, Error, FilterTarget, PlistInfoTrait,
+    RegistrationOutcome, RegistrationReport, SignerApp, SignerMode, SignerOptions, SignerRuntime,
+    SkippedTweak, TweakTarget,
+};
 
 pub struct Signer {
     certificate: Option<CertificateIdentity>,
     pub options: SignerOptions,
     pub provisioning_files: Vec<MobileProvision>,
+    /// Tweak dylibs left out by [`Signer::modify_bundle`] because of their filters.
+    pub skipped_tweaks: Vec<SkippedTweak>,
+    /// Outcome of the last [`Signer::register_bundle`].
+    pub registration_report: Option<RegistrationReport>,
 }
 
 impl Signer {
@@ -22,6 +31,8 @@ impl Signer {
             certificate,
             options,
             provisioning_files: Vec::new(),
+            skipped_tweaks: Vec::new(),
+            registration_report: None,
         }
     }
 
@@ -34,6 +45,22 @@ impl Signer {
             return Ok(());
         }
 
+        // Encrypted binaries sign and install fine, but crash on launch
+        let encrypted_binaries = bundle.encrypted_binaries()?;
+        if !encrypted_binaries.is_empty() {
+            return Err(Error::BundleEncrypted(
+                encrypted_binaries
+                    .iter()
+                    .map(|path| {
+                        path.strip_prefix(bundle.bundle_dir())
+                            .unwrap_or(path)
+                            .display()
+                            .to_string()
+                    })
+                    .collect(),
+            ));
+        }
+
         let bundles = bundle
             .collect_bundles_sorted()?
             .into_iter()
@@ -71,6 +98,14 @@ impl Signer {
 
         let identifier = bundle.get_bundle_identifier();
 
+        // Filters target the original identifier, collect them before it changes
+        let filter_target = match self.options.tweaks.as_ref() {
+            Some(_) if !self.options.ignore_tweak_filters => {
+                Some(FilterTarget::new(bundle, identifier.clone())?)
+            }
+            _ => None,
+        };
+
         if self.options.mode != SignerMode::Adhoc && self.options.custom_identifier.is_none() {
             if let (Some(identifier), Some(team_id)) = (identifier.as_ref(), team_id.as_ref()) {
                 self.options.custom_identifier = Some(format!("{identifier}.{team_id}"));
@@ -124,12 +159,41 @@ impl Signer {
             }
         }
 
+        if let Some(remove_tweaks) = self.options.remove_tweaks.as_ref() {
+            for injected in crate::Tweak::injected(bundle)? {
+                if remove_tweaks.contains(&injected.name) {
+                    crate::Tweak::uninstall(bundle, &injected).await?;
+                }
+            }
+        }
+
         if let Some(tweak_files) = self.options.tweaks.as_ref() {
-            crate::Tweak::install_ellekit(&bundle).await?;
+            match &self.options.runtime {
+                SignerRuntime::ElleKit => crate::Tweak::install_ellekit(bundle).await?,
+                SignerRuntime::Custom(path) => crate::Tweak::install_runtime(bundle, path).await?,
+                SignerRuntime::None => {}
+            }
 
-            for tweak_file in tweak_files {
-                let tweak = crate::Tweak::new(tweak_file, bundle).await?;
-                tweak.apply().await?;
+            for tweak_file in &crate::deb::sort_tweaks(tweak_files, &self.options.extract_limits) {
+                let injection = self
+                    .options
+                    .tweak_injection
+                    .get(tweak_file)
+                    .cloned()
+                    .unwrap_or_default();
+
+                // Filters describe the main app, explicit targets take precedence
+                let filter_target = filter_target
+                    .clone()
+                    .filter(|_| injection.target == TweakTarget::MainApp);
+
+                let tweak = crate::Tweak::new(tweak_file, bundle)
+                    .await?
+                    .with_filter_target(filter_target)
+                    .with_injection(injection)
+                    .with_settings(self.options.tweak_settings.clone())
+                    .with_extract_limits(self.options.extract_limits);
+                self.skipped_tweaks.extend(tweak.apply().await?);
             }
         }
 
@@ -152,121 +216,302 @@ impl Signer {
         Ok(())
     }
 
+    /// Registers the app and its extensions with the developer portal and fetches their profiles.
+    ///
+    /// `team_type` comes from the team list the caller picked `team_id` from.
+    /// Fills [`Signer::registration_report`], on failure too.
     pub async fn register_bundle(
         &mut self,
         bundle: &Bundle,
         session: &DeveloperSession,
         team_id: &String,
+        team_type: TeamType,
     ) -> Result<(), Error> {
         if self.options.mode != SignerMode::Pem {
             return Ok(());
         }
 
+        let signer_settings = &self.options;
         let bundles = bundle
             .collect_bundles_sorted()?
             .into_iter()
             .filter(|b| b.bundle_type().should_have_entitlements())
+            .filter(|sub_bundle| {
+                if signer_settings.embedding.single_profile
+                    && sub_bundle.bundle_dir() != bundle.bundle_dir()
+                {
+                    return false;
+                }
+
+                *sub_bundle.bundle_type() == BundleType::AppExtension
+                    || *sub_bundle.bundle_type() == BundleType::App
+            })
             .collect::<Vec<_>>();
-        let signer_settings = &self.options;
 
-        let bundle_arc = Arc::new(bundle.clone());
-        let session_arc = Arc::new(session);
-        let team_id_arc = Arc::new(team_id.clone());
+        let policy = CapabilityPolicy::for_team_type(team_type)
+            .with_granted(&self.options.granted_capabilities);
 
-        let futures = bundles.iter().filter_map(|sub_bundle| {
-            let sub_bundle = sub_bundle.clone();
-            let bundle = bundle_arc.clone();
-            let session = session_arc.clone();
-            let team_id = team_id_arc.clone();
-            let signer_settings = signer_settings.clone();
+        // Paid teams have no weekly App ID limit
+        let quota_warning = match (team_type, self.options.app_id_budget) {
+            (TeamType::Free, Some(budget)) => {
+                Self::check_app_id_budget(&bundles, session, team_id, budget).await?
+            }
+            _ => None,
+        };
+
+        let failed = AtomicBool::new(false);
+        let results = stream::iter(&bundles)
+            .map(|sub_bundle| {
+                let failed = &failed;
+                let policy = &policy;
+                async move {
+                    let mut registration = BundleRegistration::new(
+                        sub_bundle.bundle_dir().to_path_buf(),
+                        sub_bundle.get_bundle_identifier(),
+                    );
+
+                    // Nothing new starts once a bundle failed, the ones in flight finish so
+                    // whatever they create is known
+                    if failed.load(Ordering::SeqCst) {
+                        return (registration, None);
+                    }
 
-            if signer_settings.embedding.single_profile
-                && sub_bundle.bundle_dir() != bundle.bundle_dir()
-            {
-                return None;
+                    let result = Self::register_single_bundle(
+                        bundle,
+                        sub_bundle,
+                        session,
+                        team_id,
+                        signer_settings,
+                        policy,
+                        &mut registration,
+                    )
+                    .await;
+
+                    match &result {
+                        Ok(_) => registration.outcome = RegistrationOutcome::Registered,
+                        Err(e) => {
+                            failed.store(true, Ordering::SeqCst);
+                            registration.outcome = RegistrationOutcome::Failed(e.to_string());
+                        }
+                    }
+                    (registration, Some(result))
+                }
+            })
+            .buffered(signer_settings.register_concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut report = RegistrationReport {
+            quota_warning,
+            ..Default::default()
+        };
+        let mut provisionings = Vec::new();
+        let mut error = None;
+        for (registration, result) in results {
+            match result {
+                Some(Ok(provisioning)) => provisionings.push(provisioning),
+                Some(Err(e)) if error.is_none() => error = Some(e),
+                _ => {}
             }
-            if *sub_bundle.bundle_type() != BundleType::AppExtension
-                && *sub_bundle.bundle_type() != BundleType::App
-            {
-                return None;
+            report.bundles.push(registration);
+        }
+
+        if let Some(error) = error {
+            let rollback = self
+                .options
+                .rollback_registration
+                .unwrap_or(team_type != TeamType::Free);
+            if rollback {
+                if team_type == TeamType::Free {
+                    log::warn!(
+                        "Rolling back App IDs on a free team, they still count against its limit"
+                    );
+                }
+                Self::rollback_registration(session, team_id, &mut report).await;
             }
 
-            Some(async move {
-                let bundle_executable_name = sub_bundle
-                    .get_executable()
-                    .ok_or_else(|| Error::Other("Failed to get bundle executable name.".into()))?;
-                let bundle_executable_path = sub_bundle.bundle_dir().join(&bundle_executable_name);
+            self.registration_report = Some(report);
+            return Err(error);
+        }
 
-                let macho = plume_core::MachO::new(&bundle_executable_path)?;
+        self.provisioning_files = provisionings;
+        self.registration_report = Some(report);
 
-                let id = sub_bundle
-                    .get_bundle_identifier()
-                    .ok_or_else(|| Error::Other("Failed to get bundle identifier.".into()))?;
+        Ok(())
+    }
 
-                session
-                    .qh_ensure_app_id(&team_id, &sub_bundle.get_name().unwrap_or_default(), &id)
-                    .await?;
+    /// Warns when registering `bundles` would create more App IDs than `budget` allows, the
+    /// portal refuses the ones past it.
+    async fn check_app_id_budget(
+        bundles: &[Bundle],
+        session: &DeveloperSession,
+        team_id: &String,
+        budget: usize,
+    ) -> Result<Option<String>, Error> {
+        let existing = session
+            .qh_list_app_ids(team_id)
+            .await?
+            .app_ids
+            .into_iter()
+            .map(|app_id| app_id.identifier)
+            .collect::<HashSet<_>>();
 
-                let app_id_id = session
-                    .qh_get_app_id(&team_id, &id)
+        let needed = bundles
+            .iter()
+            .filter_map(|b| b.get_bundle_identifier())
+            .filter(|identifier| !existing.contains(identifier))
+            .count();
+
+        if needed <= budget {
+            return Ok(None);
+        }
+
+        let warning = format!(
+            "registering needs {needed} new App IDs but only {budget} can be created right now"
+        );
+        log::warn!("{warning}");
+        Ok(Some(warning))
+    }
+
+    async fn register_single_bundle(
+        bundle: &Bundle,
+        sub_bundle: &Bundle,
+        session: &DeveloperSession,
+        team_id: &String,
+        signer_settings: &SignerOptions,
+        policy: &CapabilityPolicy,
+        registration: &mut BundleRegistration,
+    ) -> Result<MobileProvision, Error> {
+        let bundle_executable_name = sub_bundle
+            .get_executable()
+            .ok_or_else(|| Error::Other("Failed to get bundle executable name.".into()))?;
+        let bundle_executable_path = sub_bundle.bundle_dir().join(&bundle_executable_name);
+
+        let macho = plume_core::MachO::new(&bundle_executable_path)?;
+
+        let id = registration
+            .identifier
+            .clone()
+            .ok_or_else(|| Error::Other("Failed to get bundle identifier.".into()))?;
+
+        let app_id_id = match session.qh_get_app_id(team_id, &id).await? {
+            Some(app_id) => app_id.app_id_id,
+            None => {
+                let app_id = session
+                    .qh_add_app_id(team_id, &sub_bundle.get_name().unwrap_or_default(), &id)
                     .await?
-                    .ok_or_else(|| Error::Other("Failed to get ensured app ID.".into()))?;
+                    .app_id
+                    .app_id_id;
+                registration.created_app_id = Some(app_id.clone());
+                app_id
+            }
+        };
 
-                if let Some(e) = macho.entitlements().as_ref() {
-                    session
-                        .v1_request_capabilities_for_entitlements(&team_id, &id, e)
-                        .await?;
-                }
+        if let Some(e) = macho.entitlements().as_ref() {
+            registration.skipped_capabilities = session
+                .v1_request_capabilities_for_entitlements(team_id, &id, e, policy)
+                .await?;
+        }
 
-                if let Some(app_groups) = macho.app_groups_for_entitlements() {
-                    let mut app_group_ids: Vec<String> = Vec::new();
-                    for group in &app_groups {
-                        let group = format!("{group}.{team_id}");
+        if let Some(app_groups) = macho.app_groups_for_entitlements() {
+            let mut app_group_ids: Vec<String> = Vec::new();
+            for group in &app_groups {
+                let group = format!("{group}.{team_id}");
+                let group_id = match session.qh_get_app_group(team_id, &group).await? {
+                    Some(app_group) => app_group.application_group,
+                    None => {
                         let group_id = session
-                            .qh_ensure_app_group(&team_id, &group, &group)
-                            .await?;
-                        app_group_ids.push(group_id.application_group);
+                            .qh_add_app_group(team_id, &group, &group)
+                            .await?
+                            .application_group
+                            .application_group;
+                        registration.created_app_groups.push(group_id.clone());
+                        group_id
                     }
+                };
+                app_group_ids.push(group_id);
+            }
 
-                    if signer_settings.app == SignerApp::SideStore
-                        || signer_settings.app == SignerApp::AltStore
-                    {
-                        bundle.set_info_plist_key(
-                            "ALTAppGroups",
-                            Value::Array(
-                                app_groups
-                                    .iter()
-                                    .map(|s| Value::String(format!("{s}.{team_id}")))
-                                    .collect(),
-                            ),
-                        )?;
-                    }
+            if signer_settings.app == SignerApp::SideStore
+                || signer_settings.app == SignerApp::AltStore
+            {
+                bundle.set_info_plist_key(
+                    "ALTAppGroups",
+                    Value::Array(
+                        app_groups
+                            .iter()
+                            .map(|s| Value::String(format!("{s}.{team_id}")))
+                            .collect(),
+                    ),
+                )?;
+            }
 
-                    session
-                        .qh_assign_app_group(&team_id, &app_id_id.app_id_id, &app_group_ids)
-                        .await?;
-                }
+            session
+                .qh_assign_app_group(team_id, &app_id_id, &app_group_ids)
+                .await?;
+        }
 
-                let profiles = session
-                    .qh_get_profile(&team_id, &app_id_id.app_id_id)
-                    .await?;
-                let profile_data = profiles.provisioning_profile.encoded_profile;
+        let profiles = session.qh_get_profile(team_id, &app_id_id).await?;
+        let profile_data = profiles.provisioning_profile.encoded_profile;
 
-                tokio::fs::write(
-                    sub_bundle.bundle_dir().join("embedded.mobileprovision"),
-                    &profile_data,
-                )
-                .await?;
-                let mobile_provision =
-                    MobileProvision::load_with_bytes(profile_data.as_ref().to_vec())?;
-                Ok::<_, Error>(mobile_provision)
-            })
-        });
+        tokio::fs::write(
+            sub_bundle.bundle_dir().join("embedded.mobileprovision"),
+            &profile_data,
+        )
+        .await?;
 
-        let provisionings: Vec<MobileProvision> = try_join_all(futures).await?;
-        self.provisioning_files = provisionings;
+        Ok(MobileProvision::load_with_bytes(
+            profile_data.as_ref().to_vec(),
+        )?)
+    }
 
-        Ok(())
+    /// Deletes what a failed [`Signer::register_bundle`] created, App IDs before the groups
+    /// assigned to them.
+    async fn rollback_registration(
+        session: &DeveloperSession,
+        team_id: &String,
+        report: &mut RegistrationReport,
+    ) {
+        let mut rolled_back = vec![false; report.bundles.len()];
+
+        for (index, registration) in report.bundles.iter().enumerate() {
+            let Some(app_id_id) = &registration.created_app_id else {
+                continue;
+            };
+
+            match session.qh_delete_app_id(team_id, app_id_id).await {
+                Ok(_) => {
+                    log::info!("Rolled back App ID {app_id_id}");
+                    rolled_back[index] = true;
+                }
+                Err(e) => {
+                    log::warn!("Failed to roll back App ID {app_id_id}: {e}");
+                    report.leftovers.push(format!("App ID {app_id_id}"));
+                }
+            }
+        }
+
+        for (index, registration) in report.bundles.iter().enumerate() {
+            for group_id in &registration.created_app_groups {
+                match session.qh_delete_app_group(team_id, group_id).await {
+                    Ok(_) => {
+                        log::info!("Rolled back app group {group_id}");
+                        rolled_back[index] = true;
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to roll back app group {group_id}: {e}");
+                        report.leftovers.push(format!("app group {group_id}"));
+                    }
+                }
+            }
+        }
+
+        // Bundles that only reused what the team already had stay registered
+        for (registration, rolled_back) in report.bundles.iter_mut().zip(rolled_back) {
+            if rolled_back && registration.outcome == RegistrationOutcome::Registered {
+                registration.outcome = RegistrationOutcome::RolledBack;
+            }
+        }
     }
 
     pub async fn sign_bundle(&self, bundle: &Bundle) -> Result<(), Error> {
@@ -0,0 +1,194 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::{Duration, SystemTime},
+};
+
+use uuid::Uuid;
+
+use crate::Error;
+
+/// Held locked by the owning process, a stage whose lock can be taken belongs to a dead one.
+const LOCK_FILE: &str = ".plume_lock";
+
+/// Prefixes of the directories [`StageDir`] creates.
+const STAGE_PREFIXES: &[&str] = &[
+    "plume_stage_",
+    "plume_tweak_",
+    "plume_ellekit_",
+    "plume_control_",
+    "plume_mac_stage_",
+];
+
+/// Stages from before lock files are only swept once they're this old.
+const UNLOCKED_STAGE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A new stage's lock file exists a moment before it's locked, younger ones are never swept.
+const LOCK_GRACE: Duration = Duration::from_secs(60);
+
+static STAGE_ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Where stage directories go: the root set with [`set_stage_root`], `PLUME_STAGE_DIR`,
+/// or the system temp dir.
+pub fn stage_root() -> PathBuf {
+    if let Some(root) = STAGE_ROOT.read().unwrap().clone() {
+        return root;
+    }
+
+    env::var_os("PLUME_STAGE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir)
+}
+
+/// Overrides the stage root, `None` goes back to the default.
+pub fn set_stage_root(root: Option<PathBuf>) {
+    *STAGE_ROOT.write().unwrap() = root;
+}
+
+/// Scratch directory under the stage root, removed when dropped.
+#[derive(Debug)]
+pub struct StageDir {
+    path: PathBuf,
+    lock: Option<fs::File>,
+}
+
+impl StageDir {
+    /// Creates `<stage root>/<prefix>_<UUID>`, the sweep only knows the `plume_*` prefixes used here.
+    pub fn new(prefix: &str) -> Result<Self, Error> {
+        Self::new_in(&stage_root(), prefix)
+    }
+
+    fn new_in(root: &Path, prefix: &str) -> Result<Self, Error> {
+        let path = root.join(format!(
+            "{prefix}_{}",
+            Uuid::new_v4().to_string().to_uppercase()
+        ));
+        fs::create_dir_all(&path)?;
+
+        let lock = fs::File::create(path.join(LOCK_FILE))?;
+        lock.lock()?;
+
+        Ok(Self {
+            path,
+            lock: Some(lock),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+impl Drop for StageDir {
+    fn drop(&mut self) {
+        // Windows won't remove a directory with an open file in it
+        drop(self.lock.take());
+        fs::remove_dir_all(&self.path).ok();
+    }
+}
+
+/// Removes stage directories left behind by processes that crashed or were killed,
+/// returning how many were removed.
+pub fn sweep_stale_stages() -> usize {
+    sweep_stale_stages_in(&stage_root())
+}
+
+fn sweep_stale_stages_in(root: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(root) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !STAGE_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) || !path.is_dir() {
+            continue;
+        }
+
+        if is_stale(&path) && fs::remove_dir_all(&path).is_ok() {
+            log::info!("Removed leftover stage {}", path.display());
+            removed += 1;
+        }
+    }
+
+    removed
+}
+
+fn is_stale(path: &Path) -> bool {
+    let lock_path = path.join(LOCK_FILE);
+    match fs::File::open(&lock_path) {
+        // Taking the lock means nobody holds it, it's released as soon as the file closes
+        Ok(lock) => lock.try_lock().is_ok() && age(&lock_path).is_some_and(|age| age > LOCK_GRACE),
+        // Older stages, or one that was created a moment ago and isn't locked yet
+        Err(_) => age(path).is_some_and(|age| age > UNLOCKED_STAGE_AGE),
+    }
+}
+
+fn age(path: &Path) -> Option<Duration> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    SystemTime::now().duration_since(modified).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What a killed process leaves behind, a lock file nobody holds that's past [`LOCK_GRACE`].
+    fn abandon(stage: &mut StageDir) {
+        drop(stage.lock.take());
+        fs::File::options()
+            .write(true)
+            .open(stage.join(LOCK_FILE))
+            .unwrap()
+            .set_modified(SystemTime::now() - 2 * LOCK_GRACE)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let live = StageDir::new("plume_stage").unwrap();
+        assert!(!is_stale(live.path()));
+
+        // Created a moment ago and not locked yet
+        let mut starting = StageDir::new("plume_stage").unwrap();
+        drop(starting.lock.take());
+        assert!(!is_stale(starting.path()));
+
+        let mut abandoned = StageDir::new("plume_stage").unwrap();
+        abandon(&mut abandoned);
+        assert!(is_stale(abandoned.path()));
+
+        // From before lock files, but too new to be abandoned
+        fs::remove_file(abandoned.join(LOCK_FILE)).unwrap();
+        assert!(!is_stale(abandoned.path()));
+    }
+
+    #[test]
+    fn test_sweep_stale_stages() {
+        // A root of its own, so the sweep can't touch stages of other tests
+        let root = StageDir::new("plume_stage").unwrap();
+
+        let live = StageDir::new_in(root.path(), "plume_stage").unwrap();
+        let mut abandoned = StageDir::new_in(root.path(), "plume_tweak").unwrap();
+        abandon(&mut abandoned);
+
+        let unrelated = root.join("other_stage");
+        fs::create_dir_all(&unrelated).unwrap();
+        fs::File::create(unrelated.join(LOCK_FILE))
+            .unwrap()
+            .set_modified(SystemTime::now() - 2 * LOCK_GRACE)
+            .unwrap();
+
+        assert_eq!(sweep_stale_stages_in(root.path()), 1);
+
+        assert!(live.path().exists());
+        assert!(!abandoned.path().exists());
+        assert!(unrelated.exists());
+    }
+}
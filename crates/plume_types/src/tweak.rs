@@ -1,33 +1,316 @@
 use std::{
-    env,
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
 use plume_core::MachO;
-use uuid::Uuid;
 
-use crate::{Bundle, Error, PlistInfoTrait, copy_dir_recursively};
+use crate::{
+    Bundle, BundleType, Error, PlistInfoTrait, StageDir, TweakInjection, TweakSettings,
+    TweakTarget, copy_dir_recursively,
+    extract::{ExtractLimits, extract_deb, extract_zip},
+    filter::{FilterTarget, SkippedTweak, TweakFilter},
+    preferences::{self, PreferenceEntry},
+};
 
 const ELLEKIT_BYTES: &[u8] = include_bytes!("./ellekit.deb");
 
+/// Runtime binaries relative to `Frameworks/`, the ones providing the Substrate API first.
+const RUNTIME_BINARIES: &[&str] = &[
+    "CydiaSubstrate.framework/CydiaSubstrate",
+    "libsubstrate.dylib",
+    "libellekit.dylib",
+    "libhooker.dylib",
+    "libsubstitute.dylib",
+];
+
+/// Names tweaks use to link the Substrate API, which every runtime provides.
+const SUBSTRATE_NAMES: &[&str] = &["CydiaSubstrate", "libsubstrate.dylib"];
+
+/// Top-level directories of a rootfs-style tweak folder.
+const ROOTFS_DIRS: &[&str] = &["Library", "usr", "var"];
+
+/// Load path prefixes that only exist on jailbroken devices, rootful and rootless.
+const JAILBREAK_PREFIXES: &[&str] = &["/var/jb/", "/Library/", "/usr/local/", "/private/preboot/"];
+
+/// A dylib or framework that was injected into an app's main executable or its extensions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InjectedTweak {
+    /// File or directory name inside `Frameworks/`, e.g. `Foo.dylib` or `Foo.framework`.
+    pub name: String,
+    /// Binary relative to the app bundle, e.g. `Frameworks/Foo.framework/Foo`.
+    pub path: String,
+    /// Load command path in the executable it was found in, e.g. `@rpath/Foo.dylib`.
+    pub load_path: String,
+    /// Whether this is the hooking runtime (ElleKit/CydiaSubstrate) rather than a tweak.
+    pub is_runtime: bool,
+}
+
+/// An injection runtime found in an app bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledRuntime {
+    /// File or directory name inside `Frameworks/`.
+    pub name: String,
+    /// `CFBundleShortVersionString` of the runtime framework, if it has one.
+    pub version: Option<String>,
+    /// `LC_UUID` of the runtime binary, which survives re-signing.
+    pub uuid: Option<String>,
+    /// ElleKit ships as `CydiaSubstrate.framework` too, but with its own install name.
+    pub is_ellekit: bool,
+}
+
 pub struct Tweak {
     path: PathBuf,
     app_bundle: PathBuf,
-    stage_dir: PathBuf,
+    /// Removed along with the tweak.
+    stage_dir: StageDir,
+    /// Load path of the runtime in the bundle, injected dylibs get their Substrate links pointed here.
+    runtime_load_path: Option<String>,
+    /// Filtered dylibs that don't match this target are skipped, `None` injects everything.
+    filter_target: Option<FilterTarget>,
+    skipped: Mutex<Vec<SkippedTweak>>,
+    injection: TweakInjection,
+    /// Binaries copied into `Frameworks/`, patched once every file of the tweak is in place.
+    installed: Mutex<Vec<PathBuf>>,
+    settings: TweakSettings,
+    /// Preference bundle binaries, patched like `installed` but the app still launches without them.
+    preference_binaries: Mutex<Vec<PathBuf>>,
+    extract_limits: ExtractLimits,
 }
 
 impl Tweak {
+    /// Installs the bundled ElleKit, see [`Tweak::install_runtime`].
     pub async fn install_ellekit(app_bundle: &Bundle) -> Result<(), Error> {
-        let stage_dir = env::temp_dir().join(format!("plume_ellekit_{}", Uuid::new_v4()));
-        tokio::fs::create_dir_all(&stage_dir).await?;
+        let stage_dir = StageDir::new("plume_ellekit")?;
 
         let deb_path = stage_dir.join("ellekit.deb");
         tokio::fs::write(&deb_path, ELLEKIT_BYTES).await?;
 
-        let tweak = Tweak::new(&deb_path, app_bundle).await?;
-        tweak.install_deb().await?;
+        Self::install_runtime(app_bundle, &deb_path).await
+    }
+
+    /// Installs a hooking runtime (ElleKit, Substitute, libhooker) from a `.deb`, `.dylib`
+    /// or `.framework`, unless the same build is already in the bundle.
+    ///
+    /// A different runtime is replaced, and the main executable is left with
+    /// exactly one runtime load command.
+    pub async fn install_runtime(app_bundle: &Bundle, source: &Path) -> Result<(), Error> {
+        let tweak = Tweak::new(source, app_bundle).await?;
+        tweak.replace_runtime(app_bundle).await?;
+
+        Self::dedupe_runtime_load_commands(app_bundle)
+    }
+
+    async fn replace_runtime(&self, app_bundle: &Bundle) -> Result<(), Error> {
+        let is_deb = self.path.extension().and_then(|e| e.to_str()) == Some("deb");
+
+        let extract_dir = if is_deb {
+            Some(self.extract_deb().await?)
+        } else {
+            None
+        };
+
+        let source_binary = match &extract_dir {
+            Some(dir) => find_runtime_binary(dir),
+            None if self.path.is_dir() => Bundle::new(&self.path)
+                .ok()
+                .and_then(|b| b.executable_path()),
+            None => Some(self.path.clone()),
+        };
+        let source_uuid = source_binary.as_deref().and_then(runtime_uuid);
+
+        match Self::installed_runtime(app_bundle)? {
+            Some(existing) if existing.uuid.is_some() && existing.uuid == source_uuid => {
+                log::info!(
+                    "{} {} is already installed, skipping",
+                    existing.name,
+                    existing.version.as_deref().unwrap_or("(unknown version)")
+                );
+                return Ok(());
+            }
+            Some(existing) => {
+                log::info!(
+                    "Replacing {} {} with {}",
+                    existing.name,
+                    existing.version.as_deref().unwrap_or("(unknown version)"),
+                    self.path.display()
+                );
+                Self::remove_runtime(app_bundle).await?;
+            }
+            None => {}
+        }
+
+        match extract_dir {
+            Some(dir) => {
+                self.scan_and_install(&dir).await?;
+                self.patch_installed()
+            }
+            None => self.apply().await.map(|_| ()),
+        }
+    }
 
-        tokio::fs::remove_dir_all(&stage_dir).await.ok();
+    /// Detects an injection runtime (ElleKit, CydiaSubstrate, Substitute, libhooker)
+    /// already shipped in the bundle.
+    pub fn installed_runtime(app_bundle: &Bundle) -> Result<Option<InstalledRuntime>, Error> {
+        let frameworks_dir = app_bundle.bundle_dir().join("Frameworks");
+
+        let Some(relative) = RUNTIME_BINARIES
+            .iter()
+            .find(|relative| frameworks_dir.join(relative).exists())
+        else {
+            return Ok(None);
+        };
+
+        let name = relative.split('/').next().unwrap_or(relative).to_string();
+        let version = if name.ends_with(".framework") {
+            Bundle::new(frameworks_dir.join(&name))
+                .ok()
+                .and_then(|b| b.get_version())
+        } else {
+            None
+        };
+
+        let info = MachO::new(frameworks_dir.join(relative))?.info()?;
+        let slice = info.first();
+
+        Ok(Some(InstalledRuntime {
+            is_ellekit: slice
+                .and_then(|s| s.id_dylib.as_deref())
+                .is_some_and(|id| id.contains("ellekit")),
+            uuid: slice.and_then(|s| s.uuid.clone()),
+            name,
+            version,
+        }))
+    }
+
+    /// Load path the main executable should use for the installed runtime.
+    pub fn runtime_load_path(app_bundle: &Bundle) -> Option<String> {
+        let frameworks_dir = app_bundle.bundle_dir().join("Frameworks");
+
+        RUNTIME_BINARIES
+            .iter()
+            .find(|relative| frameworks_dir.join(relative).exists())
+            .map(|relative| format!("@rpath/{relative}"))
+    }
+
+    async fn remove_runtime(app_bundle: &Bundle) -> Result<(), Error> {
+        for injected in Self::injected(app_bundle)? {
+            if injected.is_runtime {
+                Self::uninstall(app_bundle, &injected).await?;
+            }
+        }
+
+        // Runtimes can ship several binaries, not all of them linked by the executable
+        for relative in RUNTIME_BINARIES {
+            let name = relative.split('/').next().unwrap_or(relative);
+            let path = app_bundle.bundle_dir().join("Frameworks").join(name);
+            if path.is_dir() {
+                tokio::fs::remove_dir_all(&path).await?;
+            } else if path.exists() {
+                tokio::fs::remove_file(&path).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Collapses every runtime load command in the main executable into a single one.
+    fn dedupe_runtime_load_commands(app_bundle: &Bundle) -> Result<(), Error> {
+        let Some(runtime_load_path) = Self::runtime_load_path(app_bundle) else {
+            return Ok(());
+        };
+
+        let executable_path = app_bundle
+            .executable_path()
+            .ok_or(Error::BundleInfoPlistMissing)?;
+
+        let mut macho = MachO::new(&executable_path)?;
+        let runtime_paths = macho
+            .dylib_load_paths()?
+            .into_iter()
+            .filter(|p| is_runtime_path(p))
+            .collect::<Vec<_>>();
+
+        if runtime_paths.len() == 1 && runtime_paths[0] == runtime_load_path {
+            return Ok(());
+        }
+
+        let mut removed = Vec::new();
+        for path in &runtime_paths {
+            if !removed.contains(path) {
+                macho.remove_dylib(path)?;
+                removed.push(path.clone());
+            }
+        }
+        macho.add_dylib(&runtime_load_path)?;
+
+        Ok(())
+    }
+
+    /// Lists tweaks previously injected into the app's main executable and its extensions.
+    pub fn injected(app_bundle: &Bundle) -> Result<Vec<InjectedTweak>, Error> {
+        let bundle_dir = app_bundle.bundle_dir();
+        let executable = app_bundle
+            .get_executable()
+            .ok_or(Error::BundleInfoPlistMissing)?;
+
+        let mut extensions = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(bundle_dir.join("PlugIns")) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.ends_with(".appex") {
+                    extensions.push(name);
+                }
+            }
+        }
+        extensions.sort();
+
+        detect_injected(&executable, &extensions, |relative_path| {
+            std::fs::read(bundle_dir.join(relative_path)).ok()
+        })
+    }
+
+    /// Removes an injected tweak's load commands and deletes its files from `Frameworks/`.
+    pub async fn uninstall(app_bundle: &Bundle, injected: &InjectedTweak) -> Result<(), Error> {
+        let bundle_dir = app_bundle.bundle_dir();
+        let binary = bundle_dir.join(&injected.path);
+        let path = bundle_dir.join("Frameworks").join(&injected.name);
+
+        let mut executables = vec![
+            app_bundle
+                .executable_path()
+                .ok_or(Error::BundleInfoPlistMissing)?,
+        ];
+        for bundle in app_bundle.collect_nested_bundles()? {
+            match bundle.executable_path() {
+                Some(executable) if !executable.starts_with(&path) => executables.push(executable),
+                _ => {}
+            }
+        }
+
+        // Extensions and frameworks load their tweaks through `@loader_path`
+        for executable in executables {
+            let load_path = load_path_for(bundle_dir, &executable, &binary)?;
+
+            let mut macho = MachO::new(&executable)?;
+            for candidate in [&load_path, &injected.load_path] {
+                if macho.dylib_load_paths()?.contains(candidate) {
+                    macho.remove_dylib(candidate)?;
+                }
+            }
+        }
+
+        if path.is_dir() {
+            tokio::fs::remove_dir_all(&path).await?;
+        } else if path.exists() {
+            tokio::fs::remove_file(&path).await?;
+        }
+
+        // Injected dylibs keep their MobileSubstrate filter next to them
+        let filter_plist = path.with_extension("plist");
+        if injected.name.ends_with(".dylib") && filter_plist.is_file() {
+            tokio::fs::remove_file(&filter_plist).await?;
+        }
 
         Ok(())
     }
@@ -43,26 +326,58 @@ impl Tweak {
             .and_then(|n| n.to_str())
             .ok_or(Error::TweakInvalidPath)?;
 
+        // any other folder is treated as an unpacked tweak
         if !file_name.ends_with(".deb")
             && !file_name.ends_with(".dylib")
-            && !file_name.ends_with(".framework")
-            && !file_name.ends_with(".bundle")
-            && !file_name.ends_with(".appex")
+            && !file_name.ends_with(".zip")
+            && !path.is_dir()
         {
             return Err(Error::UnsupportedFileType(file_name.to_string()));
         }
 
-        let stage_dir = env::temp_dir().join(format!("plume_tweak_{}", Uuid::new_v4()));
-        tokio::fs::create_dir_all(&stage_dir).await?;
+        let stage_dir = StageDir::new("plume_tweak")?;
 
         Ok(Self {
             path: path.to_path_buf(),
             app_bundle: app_bundle.bundle_dir().clone(),
             stage_dir,
+            runtime_load_path: Self::runtime_load_path(app_bundle),
+            filter_target: None,
+            skipped: Mutex::new(Vec::new()),
+            injection: TweakInjection::default(),
+            installed: Mutex::new(Vec::new()),
+            settings: TweakSettings::default(),
+            preference_binaries: Mutex::new(Vec::new()),
+            extract_limits: ExtractLimits::default(),
         })
     }
 
-    pub async fn apply(&self) -> Result<(), Error> {
+    /// Honors MobileSubstrate filter plists, dylibs that target other processes won't be injected.
+    pub fn with_filter_target(mut self, filter_target: Option<FilterTarget>) -> Self {
+        self.filter_target = filter_target;
+        self
+    }
+
+    /// Picks the binaries that load the tweak and whether they require it.
+    pub fn with_injection(mut self, injection: TweakInjection) -> Self {
+        self.injection = injection;
+        self
+    }
+
+    /// Decides where the tweak's PreferenceLoader entries go.
+    pub fn with_settings(mut self, settings: TweakSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Bounds for unpacking `.deb` and `.zip` tweaks.
+    pub fn with_extract_limits(mut self, extract_limits: ExtractLimits) -> Self {
+        self.extract_limits = extract_limits;
+        self
+    }
+
+    /// Installs the tweak, returning the dylibs its filters ruled out.
+    pub async fn apply(&self) -> Result<Vec<SkippedTweak>, Error> {
         let file_name = self
             .path
             .file_name()
@@ -71,6 +386,8 @@ impl Tweak {
 
         if file_name.ends_with(".deb") {
             self.install_deb().await?;
+        } else if file_name.ends_with(".zip") {
+            self.install_zip().await?;
         } else if file_name.ends_with(".framework") {
             self.install_framework(&self.path).await?;
         } else if file_name.ends_with(".bundle") {
@@ -79,79 +396,79 @@ impl Tweak {
             self.install_appex(&self.path).await?;
         } else if file_name.ends_with(".dylib") {
             self.install_dylib(&self.path).await?;
+        } else if self.path.is_dir() {
+            self.install_directory(&self.path).await?;
         }
 
-        tokio::fs::remove_dir_all(&self.stage_dir).await.ok();
+        self.patch_installed()?;
 
-        Ok(())
+        Ok(std::mem::take(&mut *self.skipped.lock().unwrap()))
     }
 
     async fn install_deb(&self) -> Result<(), Error> {
-        use decompress::ExtractOpts;
+        let extract_dir = self.extract_deb().await?;
+        self.scan_and_install(&extract_dir).await
+    }
 
-        let extract_dir = self.stage_dir.join("deb_contents");
-        tokio::fs::create_dir_all(&extract_dir).await?;
+    async fn install_zip(&self) -> Result<(), Error> {
+        let extract_dir = self.stage_dir.join("zip_contents");
+
+        let zip_path = self.path.clone();
+        let limits = self.extract_limits;
+        let extract_dir_sync = extract_dir.clone();
+
+        tokio::task::spawn_blocking(move || extract_zip(&zip_path, &extract_dir_sync, &limits))
+            .await
+            .map_err(|e| {
+                Error::TweakExtractionFailed(format!("Failed to extract .zip: {}", e))
+            })??;
 
-        let ar_extract_dir = self.stage_dir.join("ar_contents");
-        tokio::fs::create_dir_all(&ar_extract_dir).await?;
+        self.install_directory(&extract_dir).await
+    }
 
-        let ar_path_sync = self.path.clone();
-        let ar_extract_dir_sync = ar_extract_dir.clone();
+    /// Installs an unpacked tweak, either laid out like a rootfs (`Library/`, `usr/`, `var/jb/`)
+    /// or as loose dylibs and bundles.
+    async fn install_directory(&self, root: &Path) -> Result<(), Error> {
+        let mut root = root.to_path_buf();
+
+        // Archives usually wrap everything in a single folder
+        loop {
+            let mut children = Vec::new();
+            let mut entries = tokio::fs::read_dir(&root).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                if !is_archive_junk(&entry.file_name().to_string_lossy()) {
+                    children.push(entry.path());
+                }
+            }
+
+            match children.as_slice() {
+                [child] if is_wrapper_dir(child) => root = child.clone(),
+                _ => break,
+            }
+        }
+
+        if ROOTFS_DIRS.iter().any(|dir| root.join(dir).is_dir()) {
+            self.scan_and_install(&root).await
+        } else {
+            self.scan_directory(&root).await
+        }
+    }
+
+    /// Unpacks the `.deb` into the stage directory and returns where its data went.
+    async fn extract_deb(&self) -> Result<PathBuf, Error> {
+        let extract_dir = self.stage_dir.join("deb_contents");
+
+        let deb_path = self.path.clone();
+        let limits = self.extract_limits;
+        let extract_dir_sync = extract_dir.clone();
 
         tokio::task::spawn_blocking(move || {
-            decompress::decompress(
-                &ar_path_sync,
-                &ar_extract_dir_sync,
-                &ExtractOpts {
-                    strip: 0,
-                    detect_content: false,
-                    filter: Box::new(|_: &std::path::Path| true),
-                    map: Box::new(|p| std::borrow::Cow::Borrowed(p)),
-                },
-            )
+            extract_deb(&deb_path, "data.tar", &extract_dir_sync, &limits).map(|_| ())
         })
         .await
-        .ok()
-        .and_then(|r| r.ok())
-        .ok_or_else(|| Error::TweakExtractionFailed("Failed to extract .ar archive".to_string()))?;
-
-        for archive_name in [
-            "data.tar.lzma",
-            "data.tar.gz",
-            "data.tar.xz",
-            "data.tar.bz2",
-            "data.tar",
-        ] {
-            let data_path = ar_extract_dir.join(archive_name);
-            if data_path.exists() {
-                let extract_dir_sync = extract_dir.clone();
-                let data_path_sync = data_path.clone();
-
-                tokio::task::spawn_blocking(move || {
-                    decompress::decompress(
-                        &data_path_sync,
-                        &extract_dir_sync,
-                        &ExtractOpts {
-                            strip: 0,
-                            detect_content: false,
-                            filter: Box::new(|_: &std::path::Path| true),
-                            map: Box::new(|p| std::borrow::Cow::Borrowed(p)),
-                        },
-                    )
-                })
-                .await
-                .map_err(|e| {
-                    Error::TweakExtractionFailed(format!("Failed to extract data.tar: {}", e))
-                })?
-                .map_err(|e| {
-                    Error::TweakExtractionFailed(format!("Failed to extract data.tar: {}", e))
-                })?;
-
-                break;
-            }
-        }
+        .map_err(|e| Error::TweakExtractionFailed(format!("Failed to extract .deb: {}", e)))??;
 
-        self.scan_and_install(&extract_dir).await
+        Ok(extract_dir)
     }
 
     async fn scan_and_install(&self, root: &Path) -> Result<(), Error> {
@@ -173,6 +490,86 @@ impl Tweak {
             }
         }
 
+        for library_dir in ["Library", "var/jb/Library"] {
+            self.install_preferences(&root.join(library_dir)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies PreferenceLoader entries and the preference bundles they open into the app,
+    /// merging them into `Settings.bundle` when asked to.
+    async fn install_preferences(&self, library_dir: &Path) -> Result<(), Error> {
+        let entries_dir = library_dir.join("PreferenceLoader/Preferences");
+        let bundles_dir = library_dir.join("PreferenceBundles");
+        if self.settings == TweakSettings::None || !entries_dir.is_dir() {
+            return Ok(());
+        }
+
+        copy_dir_recursively(
+            &entries_dir,
+            &self.app_bundle.join("PreferenceLoader/Preferences"),
+        )
+        .await?;
+
+        if bundles_dir.is_dir() {
+            let app_bundles_dir = self.app_bundle.join("PreferenceBundles");
+            copy_dir_recursively(&bundles_dir, &app_bundles_dir).await?;
+
+            let mut entries = tokio::fs::read_dir(&app_bundles_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let executable_path = Bundle::new(entry.path())
+                    .ok()
+                    .and_then(|bundle| bundle.executable_path());
+                if let Some(executable_path) = executable_path {
+                    self.preference_binaries
+                        .lock()
+                        .unwrap()
+                        .push(executable_path);
+                }
+            }
+        }
+
+        if self.settings != TweakSettings::SettingsBundle {
+            return Ok(());
+        }
+
+        // Entries sit either directly in `Preferences/` or in a folder per tweak
+        let mut plists = Vec::new();
+        let mut entries = tokio::fs::read_dir(&entries_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.is_dir() {
+                let mut nested = tokio::fs::read_dir(&path).await?;
+                while let Some(entry) = nested.next_entry().await? {
+                    plists.push(entry.path());
+                }
+            } else {
+                plists.push(path);
+            }
+        }
+
+        for plist_path in plists {
+            let name = plist_path.file_name().map(|n| n.to_string_lossy());
+            if !name.is_some_and(|n| n.ends_with(".plist") && !is_archive_junk(&n)) {
+                continue;
+            }
+
+            let Some(entry) = PreferenceEntry::from_file(&plist_path)? else {
+                continue;
+            };
+
+            let specifiers = entry.specifiers(&bundles_dir)?;
+            let skipped =
+                preferences::merge_into_settings_bundle(&self.app_bundle, &entry, &specifiers)?;
+            if skipped > 0 {
+                log::warn!(
+                    "{skipped} settings of {} have no Settings.bundle equivalent",
+                    entry.label
+                );
+            }
+        }
+
         Ok(())
     }
 
@@ -191,6 +588,10 @@ impl Tweak {
                         continue;
                     }
 
+                    if is_archive_junk(&entry.file_name().to_string_lossy()) {
+                        continue;
+                    }
+
                     if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                         if path.is_file() && name.ends_with(".dylib") {
                             tweak.install_dylib(&path).await?;
@@ -217,6 +618,12 @@ impl Tweak {
     }
 
     async fn install_dylib(&self, dylib_path: &Path) -> Result<(), Error> {
+        if let Some(skipped) = self.filtered_out(dylib_path)? {
+            log::warn!("Skipping {skipped}, its filter doesn't match this app");
+            self.skipped.lock().unwrap().push(skipped);
+            return Ok(());
+        }
+
         let frameworks_dir = self.app_bundle.join("Frameworks");
         tokio::fs::create_dir_all(&frameworks_dir).await?;
 
@@ -225,8 +632,35 @@ impl Tweak {
 
         tokio::fs::copy(dylib_path, &dest).await?;
 
-        Self::patch_cydiasubstrate(&dest);
-        self.inject_dylib(&dest, false).await
+        // Kept next to the dylib, it marks it as a tweak for `Tweak::injected`
+        let filter_plist = dylib_path.with_extension("plist");
+        if filter_plist.is_file() {
+            tokio::fs::copy(&filter_plist, dest.with_extension("plist")).await?;
+        }
+
+        self.installed.lock().unwrap().push(dest.clone());
+        self.inject_dylib(&dest).await
+    }
+
+    fn filtered_out(&self, dylib_path: &Path) -> Result<Option<SkippedTweak>, Error> {
+        let Some(target) = &self.filter_target else {
+            return Ok(None);
+        };
+        let Some(filter) = TweakFilter::for_dylib(dylib_path)? else {
+            return Ok(None);
+        };
+
+        if filter.matches(target) {
+            return Ok(None);
+        }
+
+        Ok(Some(SkippedTweak {
+            name: dylib_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            filter,
+        }))
     }
 
     async fn install_framework(&self, framework_path: &Path) -> Result<(), Error> {
@@ -242,8 +676,8 @@ impl Tweak {
             if let Some(exec_name) = bundle.get_executable() {
                 let exec_path = dest.join(exec_name);
                 if exec_path.exists() {
-                    Self::patch_cydiasubstrate(&exec_path);
-                    self.inject_dylib(&exec_path, true).await?;
+                    self.installed.lock().unwrap().push(exec_path.clone());
+                    self.inject_dylib(&exec_path).await?;
                 }
             }
         }
@@ -268,52 +702,793 @@ impl Tweak {
         copy_dir_recursively(appex_path, &dest).await
     }
 
-    async fn inject_dylib(&self, dylib_path: &Path, is_framework: bool) -> Result<(), Error> {
-        let bundle = Bundle::new(&self.app_bundle)?;
-        let executable_name = bundle
-            .get_executable()
-            .ok_or(Error::BundleInfoPlistMissing)?;
+    async fn inject_dylib(&self, dylib_path: &Path) -> Result<(), Error> {
+        for executable_path in self.target_executables()? {
+            let load_path = load_path_for(&self.app_bundle, &executable_path, dylib_path)?;
 
-        let executable_path = self.app_bundle.join(&executable_name);
-        if !executable_path.exists() {
-            return Err(Error::BundleInfoPlistMissing);
-        }
-
-        let inject_path = if is_framework {
-            let components: Vec<_> = dylib_path.components().rev().take(2).collect();
-            format!(
-                "@rpath/{}/{}",
-                components[1]
-                    .as_os_str()
-                    .to_str()
-                    .ok_or(Error::TweakInvalidPath)?,
-                components[0]
-                    .as_os_str()
-                    .to_str()
-                    .ok_or(Error::TweakInvalidPath)?
-            )
-        } else {
-            let file_name = dylib_path
-                .file_name()
-                .and_then(|f| f.to_str())
-                .ok_or(Error::TweakInvalidPath)?;
-            format!("@rpath/{}", file_name)
+            let mut macho = MachO::new(&executable_path)?;
+            if self.injection.weak {
+                macho.add_dylib(&load_path)?;
+            } else {
+                macho.add_required_dylib(&load_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn target_executables(&self) -> Result<Vec<PathBuf>, Error> {
+        let app_bundle = Bundle::new(&self.app_bundle)?;
+
+        let bundles = match &self.injection.target {
+            TweakTarget::MainApp => vec![app_bundle],
+            TweakTarget::AllExtensions => app_bundle
+                .collect_nested_bundles()?
+                .into_iter()
+                .filter(|b| *b.bundle_type() == BundleType::AppExtension)
+                .collect(),
+            TweakTarget::Bundles(names) => app_bundle
+                .collect_bundles_sorted()?
+                .into_iter()
+                .filter(|b| {
+                    let dir_name = b.bundle_dir().file_name().and_then(|n| n.to_str());
+                    let identifier = b.get_bundle_identifier();
+                    names.iter().any(|name| {
+                        dir_name == Some(name.as_str()) || identifier.as_ref() == Some(name)
+                    })
+                })
+                .collect(),
         };
 
-        let mut macho = MachO::new(&executable_path)?;
-        macho.add_dylib(&inject_path)?;
-        macho.write_changes()?;
+        if bundles.is_empty() {
+            return Err(Error::TweakTargetNotFound(
+                self.injection.target.to_string(),
+            ));
+        }
+
+        bundles
+            .iter()
+            .map(|b| b.executable_path().ok_or(Error::BundleInfoPlistMissing))
+            .collect()
+    }
+
+    fn patch_installed(&self) -> Result<(), Error> {
+        let installed = std::mem::take(&mut *self.installed.lock().unwrap());
+        for binary_path in installed {
+            self.patch_dependencies(&binary_path)?;
+        }
+
+        let preference_binaries = std::mem::take(&mut *self.preference_binaries.lock().unwrap());
+        for binary_path in preference_binaries {
+            if let Err(e) = self.patch_dependencies(&binary_path) {
+                log::warn!("Tweak settings may not load: {e}");
+            }
+        }
 
         Ok(())
     }
 
-    fn patch_cydiasubstrate(binary_path: &Path) {
-        if let Ok(mut macho) = MachO::new(binary_path) {
-            let _ = macho.replace_dylib(
-                "/Library/Frameworks/CydiaSubstrate.framework/CydiaSubstrate",
-                "@rpath/CydiaSubstrate.framework/CydiaSubstrate",
+    /// Points every jailbreak path the binary links at its copy in the bundle and gives it
+    /// an `@rpath` install name.
+    ///
+    /// Fails when a non-weak jailbreak dependency isn't in the bundle, the app would crash on launch.
+    fn patch_dependencies(&self, binary_path: &Path) -> Result<(), Error> {
+        let mut macho = MachO::new(binary_path)?;
+        let weak_paths = macho.weak_dylib_load_paths()?;
+        let mut unresolved = Vec::new();
+
+        for load_path in macho.dylib_load_paths()? {
+            // System libraries stay as they are, even when the app ships one by the same name
+            let is_substrate = load_path
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| SUBSTRATE_NAMES.contains(&name));
+            if !load_path.starts_with('/') || (!is_jailbreak_path(&load_path) && !is_substrate) {
+                continue;
+            }
+
+            match self.resolve_dependency(&load_path) {
+                Some(new_path) => macho.replace_dylib(&load_path, &new_path)?,
+                None if !is_jailbreak_path(&load_path) => {}
+                None if weak_paths.contains(&load_path) => {
+                    log::warn!("Weak dependency {load_path} is not in the app, leaving it as is");
+                }
+                None => unresolved.push(load_path),
+            }
+        }
+
+        if !unresolved.is_empty() {
+            return Err(Error::TweakUnresolvedDependencies(
+                binary_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                unresolved,
+            ));
+        }
+
+        // Runtimes keep their install name, it tells ElleKit builds apart
+        let relative = binary_path
+            .strip_prefix(self.app_bundle.join("Frameworks"))
+            .ok()
+            .and_then(|p| p.to_str());
+        if let (Some(relative), Some(id)) = (relative, macho.id_dylib()?) {
+            let new_id = format!("@rpath/{relative}");
+            if id != new_id && !is_runtime_path(&new_id) {
+                macho.set_id_dylib(&new_id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Maps an absolute load path to its copy in `Frameworks/`, Substrate links go to the chosen runtime.
+    fn resolve_dependency(&self, load_path: &str) -> Option<String> {
+        let mut components = load_path.rsplit('/');
+        let file_name = components.next()?;
+
+        // Runtime specific APIs only resolve against that exact runtime
+        if SUBSTRATE_NAMES.contains(&file_name) {
+            return self.runtime_load_path.clone();
+        }
+
+        // `/Library/Frameworks/Foo.framework/Foo` keeps its framework directory
+        let relative = match components.next() {
+            Some(dir) if dir.ends_with(".framework") => format!("{dir}/{file_name}"),
+            _ => file_name.to_string(),
+        };
+
+        self.app_bundle
+            .join("Frameworks")
+            .join(&relative)
+            .exists()
+            .then(|| format!("@rpath/{relative}"))
+    }
+}
+
+/// Finds the load commands of the main executable and its extensions that point at tweaks
+/// in `Frameworks/`.
+///
+/// Only dylibs that are a runtime, link one, or carry a MobileSubstrate filter plist count,
+/// so the app's own frameworks and Swift libraries are never reported.
+///
+/// `read` gets a path relative to the app bundle and returns the file contents if it exists,
+/// so this works on both extracted bundles and unextracted packages. `extensions` are directory
+/// names inside `PlugIns/`.
+pub(crate) fn detect_injected<F>(
+    executable: &str,
+    extensions: &[String],
+    mut read: F,
+) -> Result<Vec<InjectedTweak>, Error>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let mut executables = vec![(String::new(), executable.to_string())];
+    for extension in extensions {
+        let extension_dir = format!("PlugIns/{extension}");
+        let Some(executable) = read(&format!("{extension_dir}/Info.plist"))
+            .and_then(|data| plist::from_bytes::<plist::Dictionary>(&data).ok())
+            .and_then(|info| {
+                info.get("CFBundleExecutable")?
+                    .as_string()
+                    .map(str::to_string)
+            })
+        else {
+            continue;
+        };
+        executables.push((extension_dir, executable));
+    }
+
+    let mut injected: Vec<InjectedTweak> = Vec::new();
+
+    for (executable_dir, executable) in executables {
+        let executable_path = if executable_dir.is_empty() {
+            executable
+        } else {
+            format!("{executable_dir}/{executable}")
+        };
+        let Some(executable_data) = read(&executable_path) else {
+            continue;
+        };
+
+        for load_path in MachO::dylib_load_paths_bytes(&executable_data)? {
+            let Some(path) = bundle_path_for(&executable_dir, &load_path) else {
+                continue;
+            };
+            let Some(relative) = path.strip_prefix("Frameworks/") else {
+                continue;
+            };
+            if injected.iter().any(|tweak| tweak.path == path) {
+                continue;
+            }
+            let Some(data) = read(&path) else {
+                continue;
+            };
+
+            let name = relative.split('/').next().unwrap_or(relative).to_string();
+            let file_name = relative.rsplit('/').next().unwrap_or(relative);
+            let is_runtime = is_runtime_name(file_name);
+
+            let has_filter = relative
+                .strip_suffix(".dylib")
+                .filter(|stem| !stem.contains('/'))
+                .is_some_and(|stem| read(&format!("Frameworks/{stem}.plist")).is_some());
+
+            let is_tweak = is_runtime
+                || has_filter
+                || MachO::dylib_load_paths_bytes(&data)
+                    .is_ok_and(|paths| paths.iter().any(|p| is_runtime_path(p)));
+
+            if is_tweak {
+                injected.push(InjectedTweak {
+                    name,
+                    path,
+                    load_path,
+                    is_runtime,
+                });
+            }
+        }
+    }
+
+    Ok(injected)
+}
+
+/// Path relative to the app bundle that `load_path` resolves to, for an executable in
+/// `executable_dir` (empty for the main executable).
+///
+/// `@rpath` is taken as the app's `Frameworks/`, which is where everything Plume injects goes.
+fn bundle_path_for(executable_dir: &str, load_path: &str) -> Option<String> {
+    let (base, rest) = if let Some(rest) = load_path.strip_prefix("@rpath/") {
+        ("Frameworks", rest)
+    } else if let Some(rest) = load_path
+        .strip_prefix("@executable_path/")
+        .or_else(|| load_path.strip_prefix("@loader_path/"))
+    {
+        (executable_dir, rest)
+    } else {
+        return None;
+    };
+
+    let mut components: Vec<&str> = base.split('/').filter(|c| !c.is_empty()).collect();
+    for component in rest.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop()?;
+            }
+            _ => components.push(component),
+        }
+    }
+
+    Some(components.join("/"))
+}
+
+/// Load path of `dylib` for `executable`, both inside the app at `app_dir`.
+///
+/// The main executable has `Frameworks/` on its rpath, everything else goes through `@loader_path`.
+fn load_path_for(app_dir: &Path, executable: &Path, dylib: &Path) -> Result<String, Error> {
+    let components = |path: &Path| -> Vec<String> {
+        path.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect()
+    };
+
+    let dylib_relative = dylib
+        .strip_prefix(app_dir)
+        .map_err(|_| Error::TweakInvalidPath)?;
+    let executable_dir = executable
+        .parent()
+        .and_then(|dir| dir.strip_prefix(app_dir).ok())
+        .ok_or(Error::TweakInvalidPath)?;
+
+    if let Some(relative) = dylib_relative
+        .strip_prefix("Frameworks")
+        .ok()
+        .filter(|_| executable_dir.as_os_str().is_empty())
+    {
+        return Ok(format!("@rpath/{}", components(relative).join("/")));
+    }
+
+    let mut load_path = vec!["@loader_path".to_string()];
+    load_path.extend(executable_dir.components().map(|_| "..".to_string()));
+    load_path.extend(components(dylib_relative));
+
+    Ok(load_path.join("/"))
+}
+
+/// A plain folder around the actual tweak contents, rather than part of its layout.
+fn is_wrapper_dir(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+
+    path.is_dir()
+        && !ROOTFS_DIRS.contains(&name)
+        && ![".framework", ".bundle", ".appex"]
+            .iter()
+            .any(|ext| name.ends_with(ext))
+}
+
+/// Finder metadata that ends up in zips made on macOS.
+fn is_archive_junk(name: &str) -> bool {
+    name == "__MACOSX" || name == ".DS_Store" || name.starts_with("._")
+}
+
+fn is_jailbreak_path(load_path: &str) -> bool {
+    is_runtime_path(load_path)
+        || JAILBREAK_PREFIXES
+            .iter()
+            .any(|prefix| load_path.starts_with(prefix))
+}
+
+fn is_runtime_path(load_path: &str) -> bool {
+    load_path.rsplit('/').next().is_some_and(is_runtime_name)
+}
+
+/// Hooking runtimes tweaks link against are matched by the file name of [`RUNTIME_BINARIES`].
+fn is_runtime_name(name: &str) -> bool {
+    RUNTIME_BINARIES
+        .iter()
+        .any(|binary| binary.rsplit('/').next() == Some(name))
+}
+
+/// Finds the binary providing the Substrate API in an extracted runtime package.
+fn find_runtime_binary(root: &Path) -> Option<PathBuf> {
+    for prefix in [
+        "Library/Frameworks",
+        "usr/lib",
+        "var/jb/Library/Frameworks",
+        "var/jb/usr/lib",
+    ] {
+        if let Some(binary) = RUNTIME_BINARIES
+            .iter()
+            .map(|relative| root.join(prefix).join(relative))
+            .find(|path| path.is_file())
+        {
+            return Some(binary);
+        }
+    }
+
+    None
+}
+
+fn runtime_uuid(binary: &Path) -> Option<String> {
+    MachO::new(binary)
+        .and_then(|m| m.info())
+        .ok()?
+        .into_iter()
+        .find_map(|slice| slice.uuid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goblin::mach::{
+        cputype::CPU_TYPE_ARM64,
+        header::{MH_DYLIB, MH_EXECUTE, MH_MAGIC_64},
+        load_command::{LC_LOAD_DYLIB, LC_SEGMENT_64},
+    };
+    use plist::{Dictionary, Value};
+    use std::fs;
+
+    const PAGE: usize = 0x4000;
+
+    fn segment(name: &str, vmaddr: u64, fileoff: u64, filesize: u64) -> Vec<u8> {
+        let mut segname = [0u8; 16];
+        segname[..name.len()].copy_from_slice(name.as_bytes());
+
+        let mut cmd = Vec::new();
+        cmd.extend_from_slice(&LC_SEGMENT_64.to_le_bytes());
+        cmd.extend_from_slice(&72u32.to_le_bytes());
+        cmd.extend_from_slice(&segname);
+        for value in [vmaddr, PAGE as u64, fileoff, filesize] {
+            cmd.extend_from_slice(&value.to_le_bytes());
+        }
+        // maxprot, initprot, nsects, flags
+        for value in [5u32, 5, 0, 0] {
+            cmd.extend_from_slice(&value.to_le_bytes());
+        }
+        cmd
+    }
+
+    /// Unsigned arm64 binary linking `load_paths`, with room for more load commands.
+    fn binary(filetype: u32, load_paths: &[&str]) -> Vec<u8> {
+        let mut commands = vec![
+            segment("__TEXT", 0, 0, PAGE as u64),
+            segment("__LINKEDIT", PAGE as u64, PAGE as u64, 0x100),
+        ];
+        for path in load_paths {
+            let cmdsize = (24 + path.len() + 1).next_multiple_of(8);
+            let mut command = Vec::new();
+            for value in [LC_LOAD_DYLIB, cmdsize as u32, 24, 2, 0x10000, 0x10000] {
+                command.extend_from_slice(&value.to_le_bytes());
+            }
+            command.extend_from_slice(path.as_bytes());
+            command.resize(cmdsize, 0);
+            commands.push(command);
+        }
+        let sizeofcmds: usize = commands.iter().map(Vec::len).sum();
+
+        let mut data = Vec::new();
+        for value in [
+            MH_MAGIC_64,
+            CPU_TYPE_ARM64,
+            0,
+            filetype,
+            commands.len() as u32,
+            sizeofcmds as u32,
+            0,
+            0,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend(commands.concat());
+        data.resize(PAGE + 0x100, 0);
+        data
+    }
+
+    fn write_bundle(dir: &Path, executable: &str, identifier: &str, data: Vec<u8>) {
+        fs::create_dir_all(dir).unwrap();
+
+        let mut info = Dictionary::new();
+        info.insert("CFBundleExecutable".into(), executable.into());
+        info.insert("CFBundleIdentifier".into(), identifier.into());
+        Value::Dictionary(info)
+            .to_file_xml(dir.join("Info.plist"))
+            .unwrap();
+
+        fs::write(dir.join(executable), data).unwrap();
+    }
+
+    const FILTER: &str = r#"{ Filter = { Bundles = ( "com.example.test" ); }; }"#;
+
+    /// App with an injected tweak, the runtime, a tweak only its extension loads,
+    /// and a dylib and framework of its own.
+    fn test_app(root: &Path) -> Bundle {
+        let app = root.join("Test.app");
+        let frameworks = app.join("Frameworks");
+
+        write_bundle(
+            &app,
+            "Test",
+            "com.example.test",
+            binary(
+                MH_EXECUTE,
+                &[
+                    "/usr/lib/libSystem.B.dylib",
+                    "@rpath/App.framework/App",
+                    "@rpath/Plain.dylib",
+                    "@rpath/Tweak.dylib",
+                    "@rpath/CydiaSubstrate.framework/CydiaSubstrate",
+                ],
+            ),
+        );
+        write_bundle(
+            &frameworks.join("App.framework"),
+            "App",
+            "com.example.test.framework",
+            binary(MH_DYLIB, &["/usr/lib/libSystem.B.dylib"]),
+        );
+        write_bundle(
+            &frameworks.join("CydiaSubstrate.framework"),
+            "CydiaSubstrate",
+            "com.saurik.substrate",
+            binary(MH_DYLIB, &[]),
+        );
+        fs::write(
+            frameworks.join("Plain.dylib"),
+            binary(MH_DYLIB, &["/usr/lib/libSystem.B.dylib"]),
+        )
+        .unwrap();
+        fs::write(frameworks.join("Tweak.dylib"), binary(MH_DYLIB, &[])).unwrap();
+        fs::write(frameworks.join("Tweak.plist"), FILTER).unwrap();
+        fs::write(
+            frameworks.join("Hooked.dylib"),
+            binary(
+                MH_DYLIB,
+                &["@rpath/CydiaSubstrate.framework/CydiaSubstrate"],
+            ),
+        )
+        .unwrap();
+
+        write_bundle(
+            &app.join("PlugIns/Share.appex"),
+            "Share",
+            "com.example.test.share",
+            binary(
+                MH_EXECUTE,
+                &[
+                    "@rpath/Tweak.dylib",
+                    "@loader_path/../../Frameworks/Hooked.dylib",
+                ],
+            ),
+        );
+        write_bundle(
+            &app.join("PlugIns/Widget.appex"),
+            "Widget",
+            "com.example.test.widget",
+            binary(MH_EXECUTE, &[]),
+        );
+
+        Bundle::new(app).unwrap()
+    }
+
+    fn load_paths(path: PathBuf) -> Vec<String> {
+        MachO::new(path).unwrap().dylib_load_paths().unwrap()
+    }
+
+    fn names(injected: &[InjectedTweak]) -> Vec<&str> {
+        injected.iter().map(|tweak| tweak.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_load_path_for() {
+        let app = Path::new("/tmp/Test.app");
+        let load_path = |executable: &str, dylib: &str| {
+            load_path_for(app, &app.join(executable), &app.join(dylib)).unwrap()
+        };
+
+        // Main executable, `Frameworks/` is on its rpath
+        assert_eq!(
+            load_path("Test", "Frameworks/Tweak.dylib"),
+            "@rpath/Tweak.dylib"
+        );
+        assert_eq!(
+            load_path("Test", "Frameworks/Tweak.framework/Tweak"),
+            "@rpath/Tweak.framework/Tweak"
+        );
+        // App extension
+        assert_eq!(
+            load_path("PlugIns/Share.appex/Share", "Frameworks/Tweak.dylib"),
+            "@loader_path/../../Frameworks/Tweak.dylib"
+        );
+        // Framework
+        assert_eq!(
+            load_path("Frameworks/App.framework/App", "Frameworks/Tweak.dylib"),
+            "@loader_path/../../Frameworks/Tweak.dylib"
+        );
+        // Watch app nested two levels deep
+        assert_eq!(
+            load_path(
+                "Watch/Watch.app/PlugIns/Ext.appex/Ext",
+                "Frameworks/Tweak.dylib"
+            ),
+            "@loader_path/../../../../Frameworks/Tweak.dylib"
+        );
+
+        assert!(load_path_for(app, &app.join("Test"), Path::new("/tmp/Tweak.dylib")).is_err());
+    }
+
+    #[test]
+    fn test_bundle_path_for() {
+        assert_eq!(
+            bundle_path_for("", "@rpath/Tweak.dylib").as_deref(),
+            Some("Frameworks/Tweak.dylib")
+        );
+        assert_eq!(
+            bundle_path_for("", "@executable_path/Frameworks/Tweak.dylib").as_deref(),
+            Some("Frameworks/Tweak.dylib")
+        );
+        assert_eq!(
+            bundle_path_for(
+                "PlugIns/Share.appex",
+                "@loader_path/../../Frameworks/Tweak.dylib"
+            )
+            .as_deref(),
+            Some("Frameworks/Tweak.dylib")
+        );
+        assert_eq!(bundle_path_for("", "@loader_path/../Tweak.dylib"), None);
+        assert_eq!(bundle_path_for("", "/usr/lib/libSystem.B.dylib"), None);
+
+        // Undoes `load_path_for` for every kind of binary
+        let app = Path::new("/tmp/Test.app");
+        for executable_dir in ["", "PlugIns/Share.appex", "Frameworks/App.framework"] {
+            let executable = app.join(executable_dir).join("Binary");
+            let load_path =
+                load_path_for(app, &executable, &app.join("Frameworks/Tweak.dylib")).unwrap();
+            assert_eq!(
+                bundle_path_for(executable_dir, &load_path).as_deref(),
+                Some("Frameworks/Tweak.dylib")
             );
-            let _ = macho.write_changes();
         }
     }
+
+    #[test]
+    fn test_injected() {
+        let stage = StageDir::new("plume_stage").unwrap();
+        let app = test_app(stage.path());
+
+        let injected = Tweak::injected(&app).unwrap();
+        assert_eq!(
+            names(&injected),
+            ["Tweak.dylib", "CydiaSubstrate.framework", "Hooked.dylib"]
+        );
+
+        assert_eq!(injected[0].path, "Frameworks/Tweak.dylib");
+        assert_eq!(injected[0].load_path, "@rpath/Tweak.dylib");
+        assert!(!injected[0].is_runtime);
+        assert_eq!(
+            injected[1].path,
+            "Frameworks/CydiaSubstrate.framework/CydiaSubstrate"
+        );
+        assert!(injected[1].is_runtime);
+        // Only the extension loads it
+        assert_eq!(
+            injected[2].load_path,
+            "@loader_path/../../Frameworks/Hooked.dylib"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_uninstall() {
+        let stage = StageDir::new("plume_stage").unwrap();
+        let app = test_app(stage.path());
+        let app_dir = app.bundle_dir().clone();
+
+        let injected = Tweak::injected(&app).unwrap();
+        Tweak::uninstall(&app, &injected[0]).await.unwrap();
+
+        assert!(!load_paths(app_dir.join("Test")).contains(&"@rpath/Tweak.dylib".to_string()));
+        assert_eq!(
+            load_paths(app_dir.join("PlugIns/Share.appex/Share")),
+            ["@loader_path/../../Frameworks/Hooked.dylib"]
+        );
+        assert!(!app_dir.join("Frameworks/Tweak.dylib").exists());
+        assert!(!app_dir.join("Frameworks/Tweak.plist").exists());
+
+        // Found through the extension, removed from it
+        Tweak::uninstall(&app, &injected[2]).await.unwrap();
+        assert!(load_paths(app_dir.join("PlugIns/Share.appex/Share")).is_empty());
+        assert!(!app_dir.join("Frameworks/Hooked.dylib").exists());
+
+        assert_eq!(
+            names(&Tweak::injected(&app).unwrap()),
+            ["CydiaSubstrate.framework"]
+        );
+        // The app's own binaries are left alone
+        assert!(app_dir.join("Frameworks/Plain.dylib").exists());
+        assert_eq!(
+            load_paths(app_dir.join("Test")),
+            [
+                "/usr/lib/libSystem.B.dylib",
+                "@rpath/App.framework/App",
+                "@rpath/Plain.dylib",
+                "@rpath/CydiaSubstrate.framework/CydiaSubstrate",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_target_executables() {
+        let stage = StageDir::new("plume_stage").unwrap();
+        let app = test_app(stage.path());
+        let app_dir = app.bundle_dir().clone();
+        let tweak_path = app_dir.join("Frameworks/Tweak.dylib");
+
+        let targets = |target: TweakTarget| {
+            let app = app.clone();
+            let tweak_path = tweak_path.clone();
+            async move {
+                Tweak::new(&tweak_path, &app)
+                    .await
+                    .unwrap()
+                    .with_injection(TweakInjection { target, weak: true })
+                    .target_executables()
+            }
+        };
+
+        assert_eq!(
+            targets(TweakTarget::MainApp).await.unwrap(),
+            [app_dir.join("Test")]
+        );
+
+        let mut extensions = targets(TweakTarget::AllExtensions).await.unwrap();
+        extensions.sort();
+        assert_eq!(
+            extensions,
+            [
+                app_dir.join("PlugIns/Share.appex/Share"),
+                app_dir.join("PlugIns/Widget.appex/Widget"),
+            ]
+        );
+
+        // By directory name or bundle identifier
+        let mut bundles = targets(TweakTarget::Bundles(vec![
+            "Share.appex".to_string(),
+            "com.example.test.framework".to_string(),
+        ]))
+        .await
+        .unwrap();
+        bundles.sort();
+        assert_eq!(
+            bundles,
+            [
+                app_dir.join("Frameworks/App.framework/App"),
+                app_dir.join("PlugIns/Share.appex/Share"),
+            ]
+        );
+
+        assert!(matches!(
+            targets(TweakTarget::Bundles(vec!["Missing.appex".to_string()])).await,
+            Err(Error::TweakTargetNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_inject_into_extension() {
+        let stage = StageDir::new("plume_stage").unwrap();
+        let app = test_app(stage.path());
+        let app_dir = app.bundle_dir().clone();
+
+        let source = stage.join("New.dylib");
+        fs::write(&source, binary(MH_DYLIB, &[])).unwrap();
+        fs::write(stage.join("New.plist"), FILTER).unwrap();
+
+        Tweak::new(&source, &app)
+            .await
+            .unwrap()
+            .with_injection(TweakInjection {
+                target: TweakTarget::Bundles(vec!["Share.appex".to_string()]),
+                weak: false,
+            })
+            .apply()
+            .await
+            .unwrap();
+
+        let share = MachO::new(app_dir.join("PlugIns/Share.appex/Share")).unwrap();
+        let load_path = "@loader_path/../../Frameworks/New.dylib".to_string();
+        assert!(share.dylib_load_paths().unwrap().contains(&load_path));
+        assert!(!share.weak_dylib_load_paths().unwrap().contains(&load_path));
+        assert!(app_dir.join("Frameworks/New.plist").exists());
+
+        // Only the extension links it, the filter plist marks it as a tweak
+        let injected = Tweak::injected(&app).unwrap();
+        let new = injected
+            .iter()
+            .find(|tweak| tweak.name == "New.dylib")
+            .unwrap();
+        assert_eq!(new.load_path, load_path);
+
+        Tweak::uninstall(&app, new).await.unwrap();
+        assert!(!load_paths(app_dir.join("PlugIns/Share.appex/Share")).contains(&load_path));
+        assert!(!app_dir.join("Frameworks/New.dylib").exists());
+        assert!(!app_dir.join("Frameworks/New.plist").exists());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_dependency() {
+        let stage = StageDir::new("plume_stage").unwrap();
+        let app = test_app(stage.path());
+        let tweak = Tweak::new(app.bundle_dir().join("Frameworks/Tweak.dylib"), &app)
+            .await
+            .unwrap();
+
+        let runtime = Some("@rpath/CydiaSubstrate.framework/CydiaSubstrate".to_string());
+        // Substrate links go to whichever runtime the app ships
+        assert_eq!(
+            tweak.resolve_dependency("/Library/Frameworks/CydiaSubstrate.framework/CydiaSubstrate"),
+            runtime
+        );
+        assert_eq!(
+            tweak.resolve_dependency("/var/jb/usr/lib/libsubstrate.dylib"),
+            runtime
+        );
+
+        assert_eq!(
+            tweak
+                .resolve_dependency("/var/jb/Library/Frameworks/App.framework/App")
+                .as_deref(),
+            Some("@rpath/App.framework/App")
+        );
+        assert_eq!(
+            tweak
+                .resolve_dependency("/var/jb/usr/lib/Plain.dylib")
+                .as_deref(),
+            Some("@rpath/Plain.dylib")
+        );
+        assert_eq!(
+            tweak.resolve_dependency("/var/jb/usr/lib/libMissing.dylib"),
+            None
+        );
+    }
 }
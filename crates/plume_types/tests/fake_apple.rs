@@ -0,0 +1,590 @@
+//! Runs the login → registration → signing flow against an in-process stand-in for Apple's
+//! servers, plugged in through [`Transport`].
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use aes_gcm::{
+    AesGcm, KeyInit,
+    aead::{
+        Aead, Payload,
+        generic_array::{GenericArray, typenum::U16},
+    },
+    aes::Aes256,
+};
+use cbc::cipher::{BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
+use goblin::mach::{
+    cputype::CPU_TYPE_ARM64,
+    header::{MH_EXECUTE, MH_MAGIC_64},
+    load_command::LC_SEGMENT_64,
+};
+use hmac::{Hmac, Mac};
+use plist::{Date, Dictionary, Value};
+use rsa::BigUint;
+use sha2::{Digest, Sha256};
+use srp::{client::SrpClient, groups::G_2048};
+
+use plume_core::{
+    AnisetteConfiguration, Endpoints, HttpClient, HttpRequest, HttpResponse, MachO, Transport,
+    TransportFuture,
+    auth::{Account, anisette_data::AnisetteData},
+    developer::DeveloperSession,
+};
+use plume_utils::{Bundle, Signer, SignerMode, SignerOptions, StageDir};
+
+const BASE_URL: &str = "https://apple.invalid";
+const SESSION_KEY: [u8; 32] = [7; 32];
+const XCODE_TOKEN: &str = "fake-xcode-token";
+const TEAM_ID: &str = "TEAM123456";
+const APPLE_ID: &str = "test@example.com";
+const PASSWORD: &str = "hunter2";
+const SECURITY_CODE: &str = "123456";
+const SALT: &[u8] = b"fake-salt";
+const ITERATIONS: u32 = 1000;
+const BUNDLE_ID: &str = "com.example.test";
+const PAGE: usize = 0x4000;
+
+/// Server half of an SRP exchange, kept from `init` until `complete`.
+#[derive(Debug)]
+struct SrpSession {
+    a_pub: Vec<u8>,
+    key: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+struct FakeApple {
+    /// Paths requested so far, in order.
+    requests: Mutex<Vec<String>>,
+    srp: Mutex<Option<SrpSession>>,
+    /// Whether the 2FA code was entered, logins only complete after it.
+    verified: Mutex<bool>,
+    app_ids: Mutex<Vec<Value>>,
+}
+
+impl Transport for FakeApple {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let path = request
+                .url
+                .strip_prefix(BASE_URL)
+                .expect("request left the configured endpoints")
+                .to_string();
+            self.requests.lock().unwrap().push(path.clone());
+
+            let response = match path.as_str() {
+                // Pushes the code to the trusted devices, nothing comes back
+                "/auth/verify/trusteddevice" => {
+                    return Ok(HttpResponse {
+                        status: 200,
+                        headers: Default::default(),
+                        body: Vec::new(),
+                    });
+                }
+                "/grandslam/GsService2/validate" => self.validate(&request),
+                "/grandslam/GsService2" => self.grandslam(&plist_body(&request)),
+                developer => {
+                    let token = request.headers.get("X-Apple-GS-Token").unwrap();
+                    assert_eq!(token, XCODE_TOKEN);
+                    self.developer(developer, &plist_body(&request))
+                }
+            };
+
+            let mut buffer = Vec::new();
+            plist::to_writer_xml(&mut buffer, &response)?;
+
+            Ok(HttpResponse {
+                status: 200,
+                headers: Default::default(),
+                body: buffer,
+            })
+        })
+    }
+}
+
+impl FakeApple {
+    fn grandslam(&self, body: &Dictionary) -> Dictionary {
+        let request = body.get("Request").unwrap().as_dictionary().unwrap();
+
+        let response = match string(request, "o") {
+            "init" => self.srp_init(request),
+            "complete" => self.srp_complete(request),
+            "apptokens" => app_tokens(),
+            other => panic!("unexpected GrandSlam operation {other}"),
+        };
+
+        let mut envelope = Dictionary::new();
+        envelope.insert("Response".into(), response.into());
+        envelope
+    }
+
+    fn srp_init(&self, request: &Dictionary) -> Dictionary {
+        assert_eq!(string(request, "u"), APPLE_ID);
+        let a_pub = data(request, "A2k").to_vec();
+
+        let n = BigUint::from_bytes_be(&G_2048.n.to_bytes_be());
+        let g = BigUint::from_bytes_be(&G_2048.g.to_bytes_be());
+        let verifier = SrpClient::<Sha256>::new(&G_2048).compute_verifier(
+            APPLE_ID.as_bytes(),
+            &password_key(),
+            SALT,
+        );
+        let v = BigUint::from_bytes_be(&verifier);
+
+        // k = H(N | PAD(g))
+        let n_bytes = n.to_bytes_be();
+        let g_bytes = g.to_bytes_be();
+        let mut padded_g = vec![0; n_bytes.len() - g_bytes.len()];
+        padded_g.extend_from_slice(&g_bytes);
+        let k = BigUint::from_bytes_be(
+            &Sha256::new()
+                .chain_update(&n_bytes)
+                .chain_update(&padded_g)
+                .finalize(),
+        );
+
+        let b = BigUint::from_bytes_be(&[9; 32]);
+        let b_pub = (&k * &v + g.modpow(&b, &n)) % &n;
+        let b_pub = b_pub.to_bytes_be();
+
+        // S = (A * v^u)^b, the client gets the same from B
+        let u = BigUint::from_bytes_be(
+            &Sha256::new()
+                .chain_update(&a_pub)
+                .chain_update(&b_pub)
+                .finalize(),
+        );
+        let premaster = (BigUint::from_bytes_be(&a_pub) * v.modpow(&u, &n) % &n).modpow(&b, &n);
+        let key = Sha256::digest(premaster.to_bytes_be()).to_vec();
+
+        *self.srp.lock().unwrap() = Some(SrpSession { a_pub, key });
+
+        dict([
+            ("Status", dict([("ec", 0.into())])),
+            ("s", Value::Data(SALT.to_vec())),
+            ("B", Value::Data(b_pub)),
+            ("i", ITERATIONS.into()),
+            ("c", "fake-srp-cookie".into()),
+        ])
+        .into_dictionary()
+        .unwrap()
+    }
+
+    fn srp_complete(&self, request: &Dictionary) -> Dictionary {
+        let session = self
+            .srp
+            .lock()
+            .unwrap()
+            .take()
+            .expect("complete without init");
+        assert_eq!(string(request, "c"), "fake-srp-cookie");
+
+        // M2 = H(A | M1 | K), only a client holding the same key accepts it
+        let m2 = Sha256::new()
+            .chain_update(&session.a_pub)
+            .chain_update(data(request, "M1"))
+            .chain_update(&session.key)
+            .finalize();
+
+        let mut status = dict([("ec", 0.into())]).into_dictionary().unwrap();
+        if !*self.verified.lock().unwrap() {
+            status.insert("au".into(), "trustedDeviceSecondaryAuth".into());
+        }
+
+        dict([
+            ("Status", status.into()),
+            ("M2", Value::Data(m2.to_vec())),
+            ("spd", Value::Data(encrypt_spd(&session.key))),
+        ])
+        .into_dictionary()
+        .unwrap()
+    }
+
+    fn validate(&self, request: &HttpRequest) -> Dictionary {
+        let code = request.headers.get("security-code").unwrap();
+        assert_eq!(code, SECURITY_CODE);
+        *self.verified.lock().unwrap() = true;
+
+        dict([("ec", 0.into())]).into_dictionary().unwrap()
+    }
+
+    fn developer(&self, path: &str, body: &Dictionary) -> Dictionary {
+        let mut response = meta();
+
+        match path {
+            "/services/QH65B2/listTeams.action" => {
+                response.insert("teams".into(), vec![team()].into());
+            }
+            "/services/QH65B2/ios/listAppIds.action" => {
+                response.insert("appIds".into(), self.app_ids.lock().unwrap().clone().into());
+            }
+            "/services/QH65B2/ios/addAppId.action" => {
+                let app_id = app_id(string(body, "name"), string(body, "identifier"));
+                self.app_ids.lock().unwrap().push(app_id.clone());
+                response.insert("appId".into(), app_id);
+            }
+            "/services/QH65B2/ios/downloadTeamProvisioningProfile.action" => {
+                response.insert(
+                    "provisioningProfile".into(),
+                    profile(string(body, "appIdId")),
+                );
+            }
+            other => panic!("unexpected request to {other}"),
+        }
+
+        response
+    }
+}
+
+fn plist_body(request: &HttpRequest) -> Dictionary {
+    plist::from_bytes(request.body.as_deref().unwrap_or_default()).unwrap()
+}
+
+fn string<'a>(body: &'a Dictionary, key: &str) -> &'a str {
+    body.get(key).and_then(Value::as_string).unwrap()
+}
+
+fn data<'a>(body: &'a Dictionary, key: &str) -> &'a [u8] {
+    body.get(key).and_then(Value::as_data).unwrap()
+}
+
+fn dict<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Dictionary(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+/// What the client derives from the password before SRP.
+fn password_key() -> Vec<u8> {
+    let mut key = vec![0; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(&Sha256::digest(PASSWORD), SALT, ITERATIONS, &mut key);
+    key
+}
+
+/// The account details the client decrypts with the SRP session key.
+fn encrypt_spd(key: &[u8]) -> Vec<u8> {
+    let derive = |name: &str| {
+        <Hmac<Sha256> as Mac>::new_from_slice(key)
+            .unwrap()
+            .chain_update(name.as_bytes())
+            .finalize()
+            .into_bytes()
+    };
+
+    let spd = dict([
+        ("adsid", "000000-00-fake".into()),
+        ("GsIdmsToken", "fake-idms-token".into()),
+        ("sk", Value::Data(SESSION_KEY.to_vec())),
+        ("c", Value::Data(b"cookie".to_vec())),
+        ("fn", "Test".into()),
+        ("ln", "User".into()),
+    ]);
+    let mut buffer = Vec::new();
+    plist::to_writer_xml(&mut buffer, &spd).unwrap();
+
+    cbc::Encryptor::<aes::Aes256>::new_from_slices(
+        &derive("extra data key:"),
+        &derive("extra data iv:")[..16],
+    )
+    .unwrap()
+    .encrypt_padded_vec_mut::<Pkcs7>(&buffer)
+}
+
+fn app_tokens() -> Dictionary {
+    let mut token = Dictionary::new();
+    token.insert("token".into(), XCODE_TOKEN.into());
+    let mut tokens = Dictionary::new();
+    tokens.insert("com.apple.gs.xcode.auth".into(), token.into());
+    let mut plaintext = Dictionary::new();
+    plaintext.insert("t".into(), tokens.into());
+    let mut buffer = Vec::new();
+    plist::to_writer_xml(&mut buffer, &plaintext).unwrap();
+
+    let iv = [3u8; 16];
+    let ciphertext = AesGcm::<Aes256, U16>::new(GenericArray::from_slice(&SESSION_KEY))
+        .encrypt(
+            GenericArray::from_slice(&iv),
+            Payload {
+                msg: &buffer,
+                aad: b"XYZ",
+            },
+        )
+        .unwrap();
+
+    let mut status = Dictionary::new();
+    status.insert("ec".into(), 0.into());
+    let mut response = Dictionary::new();
+    response.insert("Status".into(), status.into());
+    response.insert(
+        "et".into(),
+        Value::Data([&b"XYZ"[..], &iv[..], &ciphertext[..]].concat()),
+    );
+    response
+}
+
+fn meta() -> Dictionary {
+    dict([
+        ("creationTimestamp", "2026-01-01T00:00:00Z".into()),
+        ("resultCode", 0.into()),
+        ("userLocale", "en_US".into()),
+        ("protocolVersion", "QH65B2".into()),
+        ("responseId", "00000000-0000-0000-0000-000000000000".into()),
+    ])
+    .into_dictionary()
+    .unwrap()
+}
+
+fn team() -> Value {
+    dict([
+        ("status", "active".into()),
+        ("name", "Test Team".into()),
+        ("teamId", TEAM_ID.into()),
+        ("type", "Individual".into()),
+        ("memberships", Value::Array(Vec::new())),
+        (
+            "currentTeamMember",
+            dict([
+                ("teamMemberId", "MEMBER".into()),
+                ("personId", 1.into()),
+                ("firstName", "Test".into()),
+                ("lastName", "User".into()),
+                ("email", APPLE_ID.into()),
+            ]),
+        ),
+        ("xcodeFreeOnly", true.into()),
+        (
+            "teamProvisioningSettings",
+            dict([
+                ("canDeveloperRoleRegisterDevices", true.into()),
+                ("canDeveloperRoleAddAppIds", true.into()),
+                ("canDeveloperRoleUpdateAppIds", true.into()),
+            ]),
+        ),
+    ])
+}
+
+fn app_id(name: &str, identifier: &str) -> Value {
+    dict([
+        ("appIdId", "APPID1".into()),
+        ("name", name.into()),
+        ("appIdPlatform", "ios".into()),
+        ("prefix", TEAM_ID.into()),
+        ("identifier", identifier.into()),
+        ("isWildCard", false.into()),
+        ("isDuplicate", false.into()),
+        (
+            "features",
+            dict([
+                ("push", false.into()),
+                ("iCloud", false.into()),
+                ("inAppPurchase", false.into()),
+                ("gameCenter", false.into()),
+                ("passbook", false.into()),
+                ("dataProtection", "".into()),
+                ("homeKit", false.into()),
+                ("cloudKitVersion", 1.into()),
+            ]),
+        ),
+        ("isDevPushEnabled", false.into()),
+        ("isProdPushEnabled", false.into()),
+    ])
+}
+
+/// A provisioning profile without its CMS wrapper, the signer only reads the plist inside.
+fn profile_data() -> Vec<u8> {
+    let profile = dict([
+        ("TeamIdentifier", vec![Value::from(TEAM_ID)].into()),
+        (
+            "Entitlements",
+            dict([
+                (
+                    "application-identifier",
+                    format!("{TEAM_ID}.{BUNDLE_ID}").into(),
+                ),
+                ("com.apple.developer.team-identifier", TEAM_ID.into()),
+                ("get-task-allow", true.into()),
+            ]),
+        ),
+    ]);
+
+    let mut buffer = Vec::new();
+    plist::to_writer_xml(&mut buffer, &profile).unwrap();
+    buffer
+}
+
+fn profile(app_id_id: &str) -> Value {
+    dict([
+        ("provisioningProfileId", "PROFILE1".into()),
+        ("name", "Test Profile".into()),
+        ("status", "Active".into()),
+        ("type", "Development".into()),
+        ("distributionMethod", "limited".into()),
+        ("UUID", "11111111-1111-1111-1111-111111111111".into()),
+        ("dateExpire", Date::from(SystemTime::now()).into()),
+        ("appIdId", app_id_id.into()),
+        ("encodedProfile", Value::Data(profile_data())),
+        ("filename", "test.mobileprovision".into()),
+        ("isTemplateProfile", false.into()),
+        ("isTeamProfile", true.into()),
+    ])
+}
+
+fn anisette() -> AnisetteData {
+    let base_headers = HashMap::from([
+        (
+            "X-Mme-Client-Info".to_string(),
+            "<MacBookPro13,2> <macOS;13.1;22C65> <com.apple.AuthKit/1 (com.apple.dt.Xcode/3594.4.19)>"
+                .to_string(),
+        ),
+        ("X-Apple-I-MD".to_string(), "AAAA".to_string()),
+        ("X-Apple-I-MD-M".to_string(), "BBBB".to_string()),
+        ("X-Apple-Locale".to_string(), "en_US".to_string()),
+    ]);
+
+    AnisetteData {
+        base_headers,
+        generated_at: SystemTime::now(),
+        config: AnisetteConfiguration::default(),
+    }
+}
+
+fn segment(name: &str, vmaddr: u64, fileoff: u64, filesize: u64) -> Vec<u8> {
+    let mut segname = [0u8; 16];
+    segname[..name.len()].copy_from_slice(name.as_bytes());
+
+    let mut cmd = Vec::new();
+    cmd.extend_from_slice(&LC_SEGMENT_64.to_le_bytes());
+    cmd.extend_from_slice(&72u32.to_le_bytes());
+    cmd.extend_from_slice(&segname);
+    for value in [vmaddr, PAGE as u64, fileoff, filesize] {
+        cmd.extend_from_slice(&value.to_le_bytes());
+    }
+    // maxprot, initprot, nsects, flags
+    for value in [5u32, 5, 0, 0] {
+        cmd.extend_from_slice(&value.to_le_bytes());
+    }
+    cmd
+}
+
+/// Unsigned `Test.app` with a thin arm64 executable and no entitlements.
+fn write_app(dir: &Path) -> Bundle {
+    let app = dir.join("Test.app");
+    fs::create_dir_all(&app).unwrap();
+
+    let info = dict([
+        ("CFBundleExecutable", "Test".into()),
+        ("CFBundleIdentifier", BUNDLE_ID.into()),
+        ("CFBundleName", "Test".into()),
+        ("CFBundlePackageType", "APPL".into()),
+        ("CFBundleShortVersionString", "1.0".into()),
+        ("CFBundleVersion", "1".into()),
+    ]);
+    info.to_file_xml(app.join("Info.plist")).unwrap();
+
+    let commands = [
+        segment("__TEXT", 0, 0, PAGE as u64),
+        segment("__LINKEDIT", PAGE as u64, PAGE as u64, 0x100),
+    ];
+    let mut executable = Vec::new();
+    for value in [
+        MH_MAGIC_64,
+        CPU_TYPE_ARM64,
+        0,
+        MH_EXECUTE,
+        commands.len() as u32,
+        commands.iter().map(Vec::len).sum::<usize>() as u32,
+        0,
+        0,
+    ] {
+        executable.extend_from_slice(&value.to_le_bytes());
+    }
+    executable.extend(commands.concat());
+    executable.resize(PAGE + 0x100, 0);
+    fs::write(app.join("Test"), executable).unwrap();
+
+    Bundle::new(app).unwrap()
+}
+
+#[tokio::test]
+async fn signs_against_fake_endpoints() {
+    let apple = Arc::new(FakeApple::default());
+    let http = HttpClient::with_transport(apple.clone())
+        .with_endpoints(Endpoints::with_base_url(BASE_URL));
+
+    let account = Account::login_with_http(
+        || Ok((APPLE_ID.to_string(), PASSWORD.to_string())),
+        || Ok(SECURITY_CODE.to_string()),
+        anisette(),
+        http,
+    )
+    .await
+    .unwrap();
+    assert_eq!(account.get_name(), ("Test".to_string(), "User".to_string()));
+
+    let session = DeveloperSession::using_account(account).await.unwrap();
+    assert_eq!(session.xcode_gs_token(), XCODE_TOKEN);
+
+    let team = session.qh_list_teams().await.unwrap().teams.remove(0);
+    assert_eq!(team.team_id, TEAM_ID);
+
+    let stage = StageDir::new("plume_stage").unwrap();
+    let bundle = write_app(stage.path());
+
+    let mut signer = Signer::new(
+        None,
+        SignerOptions {
+            mode: SignerMode::Pem,
+            ..Default::default()
+        },
+    );
+    signer
+        .register_bundle(&bundle, &session, &team.team_id, team.team_type())
+        .await
+        .unwrap();
+    let report = signer.registration_report.as_ref().unwrap();
+    assert_eq!(report.created_app_ids().collect::<Vec<_>>(), ["APPID1"]);
+
+    signer.sign_bundle(&bundle).await.unwrap();
+
+    assert_eq!(
+        fs::read(bundle.bundle_dir().join("embedded.mobileprovision")).unwrap(),
+        profile_data()
+    );
+    let info = MachO::new(bundle.bundle_dir().join("Test"))
+        .unwrap()
+        .info()
+        .unwrap();
+    let signature = info[0].code_signature.as_ref().unwrap();
+    assert!(signature.parsed);
+    assert!(signature.has_entitlements);
+    assert_eq!(
+        signature.code_directories[0].identifier.as_deref(),
+        Some(BUNDLE_ID)
+    );
+
+    assert_eq!(
+        *apple.requests.lock().unwrap(),
+        [
+            // Password, then the code from a trusted device, then the password again
+            "/grandslam/GsService2",
+            "/grandslam/GsService2",
+            "/auth/verify/trusteddevice",
+            "/grandslam/GsService2/validate",
+            "/grandslam/GsService2",
+            "/grandslam/GsService2",
+            // App token
+            "/grandslam/GsService2",
+            "/services/QH65B2/listTeams.action",
+            "/services/QH65B2/ios/listAppIds.action",
+            "/services/QH65B2/ios/addAppId.action",
+            "/services/QH65B2/ios/downloadTeamProvisioningProfile.action",
+        ]
+    );
+}